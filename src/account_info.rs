@@ -0,0 +1,39 @@
+use std::fmt::Display;
+
+/// An account, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-accounts.html#account-info>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct AccountInfo {
+    #[serde(rename = "_account_id")]
+    pub account_id: Option<u64>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub username: Option<String>,
+}
+
+impl AccountInfo {
+    /// Does this account match the given username, email, or name?
+    pub fn matches(&self, user: &str) -> bool {
+        [&self.username, &self.email, &self.name]
+            .into_iter()
+            .flatten()
+            .any(|field| field == user)
+            || self
+                .account_id
+                .map(|id| id.to_string() == user)
+                .unwrap_or(false)
+    }
+}
+
+impl Display for AccountInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.username, &self.name, self.account_id) {
+            (Some(username), _, _) => write!(f, "{username}"),
+            (None, Some(name), _) => write!(f, "{name}"),
+            (None, None, Some(id)) => write!(f, "{id}"),
+            (None, None, None) => write!(f, "(unknown account)"),
+        }
+    }
+}
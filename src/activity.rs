@@ -0,0 +1,229 @@
+use std::fmt::Write;
+
+use miette::IntoDiagnostic;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use time::Duration;
+use time::OffsetDateTime;
+use time::PrimitiveDateTime;
+
+use crate::change_info::ChangeInfo;
+use crate::change_number::ChangeNumber;
+use crate::gerrit::Gerrit;
+use crate::labels::format_vote;
+use crate::timezone::local_time_zone;
+
+/// The tag Gerrit puts on the messages it generates for new patchsets.
+const NEW_PATCHSET_TAG: &str = "autogenerated:gerrit:newPatchSet";
+
+/// Something that happened on a change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityEvent {
+    pub date: OffsetDateTime,
+    /// Who did it.
+    pub author: String,
+    pub description: String,
+}
+
+/// Show a chronological feed of messages, votes, and patchset uploads on a change.
+pub fn activity(
+    gerrit: &mut Gerrit,
+    change: ChangeNumber,
+    since: Option<Duration>,
+) -> miette::Result<String> {
+    let info = gerrit.change_info(
+        change,
+        &[
+            "ALL_REVISIONS",
+            "MESSAGES",
+            "DETAILED_LABELS",
+            "DETAILED_ACCOUNTS",
+        ],
+    )?;
+
+    let mut events = activity_events(&info);
+    if let Some(since) = since {
+        let cutoff = OffsetDateTime::now_utc() - since;
+        events.retain(|event| event.date >= cutoff);
+    }
+
+    let time_zone = local_time_zone();
+    let mut output = String::new();
+    for event in events {
+        let date = time_zone
+            .to_local(event.date)
+            .format(format_description!("[year]-[month]-[day] [hour]:[minute]"))
+            .into_diagnostic()?;
+        let mut lines = event.description.lines();
+        let _ = writeln!(
+            output,
+            "{} {}: {}",
+            date.if_supports_color(Stdout, |date| date.dimmed()),
+            event
+                .author
+                .if_supports_color(Stdout, |author| author.bold()),
+            lines.next().unwrap_or_default()
+        );
+        for line in lines {
+            let _ = writeln!(output, "    {line}");
+        }
+    }
+    Ok(output)
+}
+
+/// Collect a change's messages, votes, and patchset uploads, oldest first.
+///
+/// Gerrit's messages for new patchsets are skipped in favor of the patchsets themselves.
+pub fn activity_events(info: &ChangeInfo) -> Vec<ActivityEvent> {
+    let mut events = Vec::new();
+
+    for revision in info.revisions.values() {
+        let Some(date) = parse_timestamp(&revision.created) else {
+            continue;
+        };
+        events.push(ActivityEvent {
+            date,
+            author: revision.uploader.to_string(),
+            description: match revision.kind {
+                Some(kind) => format!("Uploaded patchset {} ({kind})", revision.number),
+                None => format!("Uploaded patchset {}", revision.number),
+            },
+        });
+    }
+
+    for (label, label_info) in &info.labels {
+        for vote in &label_info.all {
+            let (Some(value), Some(date)) = (
+                vote.value.filter(|value| *value != 0),
+                vote.date.as_deref().and_then(parse_timestamp),
+            ) else {
+                continue;
+            };
+            events.push(ActivityEvent {
+                date,
+                author: vote.account.to_string(),
+                description: format!("Voted {label}{}", format_vote(value)),
+            });
+        }
+    }
+
+    for message in &info.messages {
+        if message.tag.as_deref() == Some(NEW_PATCHSET_TAG) {
+            continue;
+        }
+        let Some(date) = parse_timestamp(&message.date) else {
+            continue;
+        };
+        events.push(ActivityEvent {
+            date,
+            author: message
+                .author
+                .as_ref()
+                .map(|author| author.to_string())
+                .unwrap_or_else(|| "Gerrit".to_owned()),
+            description: message.message.trim().to_owned(),
+        });
+    }
+
+    // Stable, so ties keep patchsets before votes before messages.
+    events.sort_by_key(|event| event.date);
+    events
+}
+
+/// Parse a REST API timestamp like `2013-02-01 09:59:32.126000000`, which is in UTC.
+fn parse_timestamp(timestamp: &str) -> Option<OffsetDateTime> {
+    const FORMAT: &[FormatItem<'_>] =
+        format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond]");
+    match PrimitiveDateTime::parse(timestamp, FORMAT) {
+        Ok(datetime) => Some(datetime.assume_utc()),
+        Err(error) => {
+            tracing::debug!("Failed to parse timestamp `{timestamp}`: {error}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use time::macros::datetime;
+
+    #[test]
+    fn test_activity_events() {
+        let info = serde_json::from_str::<ChangeInfo>(indoc!(
+            r#"
+            {
+                "project": "gr",
+                "branch": "main",
+                "change_id": "I0123456789abcdef0123456789abcdef01234567",
+                "subject": "Add a mock Gerrit server",
+                "status": "NEW",
+                "_number": 123,
+                "owner": { "username": "rbt" },
+                "created": "2024-04-01 09:00:00.000000000",
+                "updated": "2024-04-02 10:00:00.000000000",
+                "labels": {
+                    "Code-Review": {
+                        "all": [
+                            { "username": "jade", "value": 2, "date": "2024-04-02 10:00:00.000000000" },
+                            { "username": "rbt", "value": 0 }
+                        ]
+                    }
+                },
+                "messages": [
+                    {
+                        "id": "1",
+                        "author": { "username": "rbt" },
+                        "date": "2024-04-01 09:00:00.000000000",
+                        "message": "Uploaded patch set 1.",
+                        "tag": "autogenerated:gerrit:newPatchSet",
+                        "_revision_number": 1
+                    },
+                    {
+                        "id": "2",
+                        "author": { "username": "jade" },
+                        "date": "2024-04-02 10:00:00.000000000",
+                        "message": "Patch Set 1: Code-Review+2\n\nLooks good!",
+                        "_revision_number": 1
+                    }
+                ],
+                "revisions": {
+                    "0123456789abcdef0123456789abcdef01234567": {
+                        "_number": 1,
+                        "kind": "REWORK",
+                        "created": "2024-04-01 09:00:00.000000000",
+                        "uploader": { "username": "rbt" },
+                        "ref": "refs/changes/23/123/1"
+                    }
+                }
+            }
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(
+            activity_events(&info),
+            vec![
+                ActivityEvent {
+                    date: datetime!(2024-04-01 09:00 UTC),
+                    author: "rbt".to_owned(),
+                    description: "Uploaded patchset 1 (rework)".to_owned(),
+                },
+                ActivityEvent {
+                    date: datetime!(2024-04-02 10:00 UTC),
+                    author: "jade".to_owned(),
+                    description: "Voted Code-Review+2".to_owned(),
+                },
+                ActivityEvent {
+                    date: datetime!(2024-04-02 10:00 UTC),
+                    author: "jade".to_owned(),
+                    description: "Patch Set 1: Code-Review+2\n\nLooks good!".to_owned(),
+                },
+            ]
+        );
+    }
+}
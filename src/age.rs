@@ -0,0 +1,104 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use time::Duration;
+
+/// How long a change can wait for review without updates before it's considered stalled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalledAfter(Duration);
+
+impl StalledAfter {
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl Default for StalledAfter {
+    fn default() -> Self {
+        Self(Duration::weeks(1))
+    }
+}
+
+impl Display for StalledAfter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format_age(self.0))
+    }
+}
+
+impl FromStr for StalledAfter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s).map(Self)
+    }
+}
+
+/// Parse a duration like `30s`, `30m`, `36h`, `3d`, or `2w`. Plain numbers are days.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number = number
+        .parse::<i64>()
+        .map_err(|_| format!("Expected a duration like `3d` or `2w`: {s}"))?;
+    match unit {
+        "s" => Ok(Duration::seconds(number)),
+        "m" => Ok(Duration::minutes(number)),
+        "h" => Ok(Duration::hours(number)),
+        "" | "d" => Ok(Duration::days(number)),
+        "w" => Ok(Duration::weeks(number)),
+        _ => Err(format!(
+            "Unknown duration unit `{unit}`; expected `s`, `m`, `h`, `d`, or `w`"
+        )),
+    }
+}
+
+/// Format a duration compactly, in the largest whole unit, like `5m`, `3h`, `4d`, or `2w`.
+pub fn format_age(duration: Duration) -> String {
+    if duration >= Duration::weeks(2) {
+        format!("{}w", duration.whole_weeks())
+    } else if duration >= Duration::days(1) {
+        format!("{}d", duration.whole_days())
+    } else if duration >= Duration::hours(1) {
+        format!("{}h", duration.whole_hours())
+    } else {
+        format!("{}m", duration.whole_minutes().max(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(Duration::seconds(30)), "0m");
+        assert_eq!(format_age(Duration::minutes(90)), "1h");
+        assert_eq!(format_age(Duration::hours(47)), "1d");
+        assert_eq!(format_age(Duration::days(13)), "13d");
+        assert_eq!(format_age(Duration::days(20)), "2w");
+    }
+
+    #[test]
+    fn test_parse_stalled_after() {
+        assert_eq!(
+            "36h".parse::<StalledAfter>().unwrap().duration(),
+            Duration::hours(36)
+        );
+        assert_eq!(
+            "3".parse::<StalledAfter>().unwrap().duration(),
+            Duration::days(3)
+        );
+        assert_eq!(
+            "2w".parse::<StalledAfter>().unwrap().duration(),
+            Duration::weeks(2)
+        );
+        assert_eq!(
+            "30m".parse::<StalledAfter>().unwrap().duration(),
+            Duration::minutes(30)
+        );
+        assert!("2y".parse::<StalledAfter>().is_err());
+        assert!("w".parse::<StalledAfter>().is_err());
+    }
+}
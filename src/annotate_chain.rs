@@ -0,0 +1,119 @@
+use calm_io::stdoutln;
+
+use crate::change_number::ChangeNumber;
+use crate::dependency_graph::DependencyGraph;
+use crate::gerrit::GerritGitRemote;
+use crate::offline_queue::perform_or_queue;
+use crate::offline_queue::Action;
+use crate::offline_queue::PerformOutcome;
+use crate::review_input::ReviewInput;
+
+/// The tag for chain position messages.
+///
+/// Tags starting with `autogenerated:` can be hidden in the web UI.
+const CHAIN_MESSAGE_TAG: &str = "autogenerated:git-gr:chain";
+
+/// Post a message on each open change in the `HEAD` commit's stack describing its position.
+///
+/// Changes whose latest position message is already up-to-date are skipped, so this can be
+/// re-run after the stack changes.
+pub fn annotate_chain(gerrit: &mut GerritGitRemote, dry_run: bool) -> miette::Result<()> {
+    let head = gerrit.get_change(gerrit.git().change_id("HEAD")?)?.number;
+    let mut graph = DependencyGraph::traverse(gerrit, head)?;
+    let changes = graph.changes_bottom_up()?;
+
+    for (index, change) in changes.iter().enumerate() {
+        let message = chain_position_message(
+            index + 1,
+            changes.len(),
+            graph.depends_on(*change),
+            &graph.needed_by(*change).iter().copied().collect::<Vec<_>>(),
+        );
+
+        if !gerrit.get_change(*change)?.open {
+            continue;
+        }
+
+        let info = gerrit.change_info(*change, &["MESSAGES"])?;
+        let previous = info
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.tag.as_deref() == Some(CHAIN_MESSAGE_TAG));
+        if previous.is_some_and(|previous| previous.message.ends_with(&message)) {
+            tracing::info!("{} is already annotated", change.pretty(gerrit)?);
+            continue;
+        }
+
+        if dry_run {
+            let _ = stdoutln!("{}: {message}", change.pretty(gerrit)?);
+            continue;
+        }
+
+        let outcome = perform_or_queue(
+            gerrit,
+            *change,
+            Action::Review(ReviewInput {
+                message: Some(message.clone()),
+                tag: Some(CHAIN_MESSAGE_TAG.to_owned()),
+                ..Default::default()
+            }),
+        )?;
+        if let PerformOutcome::Performed(_) = outcome {
+            tracing::info!("Annotated {}: {message}", change.pretty(gerrit)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Describe a change's position in a stack, like `2/5 in stack, depends on #123, needed by #125`.
+fn chain_position_message(
+    position: usize,
+    total: usize,
+    depends_on: Option<ChangeNumber>,
+    needed_by: &[ChangeNumber],
+) -> String {
+    let mut message = format!("{position}/{total} in stack");
+    if let Some(depends_on) = depends_on {
+        message.push_str(&format!(", depends on #{depends_on}"));
+    }
+    if !needed_by.is_empty() {
+        message.push_str(", needed by ");
+        message.push_str(
+            &needed_by
+                .iter()
+                .map(|change| format!("#{change}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_chain_position_message() {
+        let number = |n: &str| n.parse::<ChangeNumber>().unwrap();
+        assert_eq!(
+            chain_position_message(1, 3, None, &[number("124")]),
+            "1/3 in stack, needed by #124"
+        );
+        assert_eq!(
+            chain_position_message(2, 5, Some(number("123")), &[number("125")]),
+            "2/5 in stack, depends on #123, needed by #125"
+        );
+        assert_eq!(
+            chain_position_message(3, 3, Some(number("124")), &[]),
+            "3/3 in stack, depends on #124"
+        );
+        assert_eq!(
+            chain_position_message(1, 3, None, &[number("124"), number("125")]),
+            "1/3 in stack, needed by #124, #125"
+        );
+    }
+}
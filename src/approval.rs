@@ -1,18 +1,109 @@
+use std::collections::BTreeMap;
+
+use serde_with::serde_as;
+use serde_with::TimestampSeconds;
+use time::OffsetDateTime;
+
 use crate::author::Author;
 
-/// The current patch set in a Gerrit change.
+/// An approval (vote) on a patch set in a Gerrit change.
+#[serde_as]
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
 pub struct Approval {
     /// The approval type, like `Verified`.
     #[serde(rename = "type")]
-    type_: String,
+    pub type_: String,
     /// The approval description.
-    description: Option<String>,
+    pub description: Option<String>,
     /// The value.
     ///
     /// Generally(?) a number like `-1` or `+2`.
-    value: String,
-    by: Author,
+    pub value: String,
+    /// When the vote was cast.
+    #[serde(default)]
+    #[serde_as(as = "Option<TimestampSeconds<i64>>")]
+    pub granted_on: Option<OffsetDateTime>,
+    pub by: Author,
+}
+
+impl Approval {
+    /// Parse the approval's value as a number.
+    pub fn numeric_value(&self) -> Option<i32> {
+        self.value.trim().trim_start_matches('+').parse().ok()
+    }
+}
+
+/// Summarize votes compactly, like `CR+2 V-1`.
+///
+/// Labels are abbreviated to their initials, and each shows its most significant vote: the
+/// lowest vote if anyone voted negatively, and the highest vote otherwise. Zero votes are left
+/// out.
+pub fn format_votes(approvals: &[Approval]) -> String {
+    let mut votes = BTreeMap::<&str, i32>::new();
+    for approval in approvals {
+        let value = match approval.numeric_value() {
+            Some(0) | None => continue,
+            Some(value) => value,
+        };
+        votes
+            .entry(&approval.type_)
+            .and_modify(|vote| {
+                if value < 0 && value < *vote || *vote > 0 && value > *vote {
+                    *vote = value;
+                }
+            })
+            .or_insert(value);
+    }
+
+    votes
+        .into_iter()
+        .map(|(label, value)| format!("{}{value:+}", abbreviate_label(label)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Abbreviate a label name to its initials, like `CR` for `Code-Review`.
+fn abbreviate_label(label: &str) -> String {
+    label
+        .split(['-', '_', ' '])
+        .filter_map(|word| word.chars().next())
+        .flat_map(|c| c.to_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn approval(type_: &str, value: &str) -> Approval {
+        Approval {
+            type_: type_.to_owned(),
+            description: None,
+            value: value.to_owned(),
+            granted_on: None,
+            by: Author {
+                email: None,
+                name: "Rebecca Turner".to_owned(),
+                username: "rbt".to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_format_votes() {
+        assert_eq!(format_votes(&[]), "");
+        assert_eq!(
+            format_votes(&[
+                approval("Code-Review", "1"),
+                approval("Code-Review", "2"),
+                approval("Verified", "1"),
+                approval("Verified", "-1"),
+                approval("Presubmit-Ready", "0"),
+            ]),
+            "CR+2 V-1"
+        );
+    }
 }
@@ -0,0 +1,15 @@
+use crate::account_info::AccountInfo;
+
+/// A vote on a label, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#approval-info>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct ApprovalInfo {
+    #[serde(flatten)]
+    pub account: AccountInfo,
+    /// The vote. Unset if the account is a reviewer who hasn't voted.
+    pub value: Option<i32>,
+    /// When the vote was cast, like `2013-02-01 09:59:32.126000000`.
+    pub date: Option<String>,
+}
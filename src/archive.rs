@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use camino::Utf8Path;
+use fs_err as fs;
+use miette::Context;
+use miette::IntoDiagnostic;
+use reqwest::Method;
+
+use crate::change_info::ChangeInfo;
+use crate::change_number::ChangeNumber;
+use crate::comment_info::CommentInfo;
+use crate::gerrit::Gerrit;
+use crate::labels::format_vote;
+
+const CHANGE_OPTIONS: &str = "?o=ALL_REVISIONS&o=MESSAGES&o=DETAILED_LABELS&o=DETAILED_ACCOUNTS";
+
+/// Write a change's full history to `dir`.
+///
+/// This writes:
+/// - `change.json` and `comments.json`, the raw REST API responses.
+/// - `patchsets/N.patch`, each patchset formatted with `git format-patch`.
+/// - `README.md`, a human-readable summary of the change's patchsets, votes, messages, and
+///   inline comments.
+pub fn archive(gerrit: &mut Gerrit, change: ChangeNumber, dir: &Utf8Path) -> miette::Result<()> {
+    let patchsets_dir = dir.join("patchsets");
+    fs::create_dir_all(&patchsets_dir).into_diagnostic()?;
+
+    let change_json =
+        gerrit.http_request(Method::GET, &gerrit.change_endpoint(change, CHANGE_OPTIONS))?;
+    write_json(&dir.join("change.json"), &change_json)?;
+    let info = serde_json::from_str::<ChangeInfo>(&change_json)
+        .into_diagnostic()
+        .wrap_err("Failed to deserialize change")?;
+
+    let comments_json =
+        gerrit.http_request(Method::GET, &gerrit.change_endpoint(change, "/comments"))?;
+    write_json(&dir.join("comments.json"), &comments_json)?;
+    let comments = serde_json::from_str::<BTreeMap<String, Vec<CommentInfo>>>(&comments_json)
+        .into_diagnostic()
+        .wrap_err("Failed to deserialize comments")?;
+
+    for revision in info.revisions.values() {
+        let commit = gerrit.fetch_cl(change.with_patchset(revision.number))?;
+        let patch = gerrit.git().format_patch(&commit)?;
+        fs::write(
+            patchsets_dir.join(format!("{}.patch", revision.number)),
+            patch,
+        )
+        .into_diagnostic()?;
+    }
+
+    fs::write(dir.join("README.md"), format_markdown(&info, &comments)).into_diagnostic()?;
+
+    tracing::info!(
+        "Archived change {change} with {} patchsets to {dir}",
+        info.revisions.len()
+    );
+
+    Ok(())
+}
+
+fn write_json(path: &Utf8Path, json: &str) -> miette::Result<()> {
+    let value = serde_json::from_str::<serde_json::Value>(json).into_diagnostic()?;
+    fs::write(
+        path,
+        serde_json::to_string_pretty(&value).into_diagnostic()?,
+    )
+    .into_diagnostic()
+}
+
+fn format_markdown(info: &ChangeInfo, comments: &BTreeMap<String, Vec<CommentInfo>>) -> String {
+    let mut output = String::new();
+
+    let _ = writeln!(output, "# {}: {}\n", info.number, info.subject);
+    let _ = writeln!(output, "- Change-Id: `{}`", info.change_id);
+    let _ = writeln!(output, "- Project: `{}`", info.project);
+    let _ = writeln!(output, "- Branch: `{}`", info.branch);
+    let _ = writeln!(output, "- Owner: {}", info.owner);
+    let _ = writeln!(output, "- Status: {}", info.status);
+    let _ = writeln!(output, "- Created: {}", info.created);
+    let _ = writeln!(output, "- Updated: {}", info.updated);
+
+    let _ = writeln!(output, "\n## Patchsets\n");
+    let mut revisions = info.revisions.iter().collect::<Vec<_>>();
+    revisions.sort_by_key(|(_, revision)| revision.number);
+    for (commit, revision) in revisions {
+        let _ = writeln!(
+            output,
+            "- [{}](patchsets/{}.patch): `{}`{} by {} at {}",
+            revision.number,
+            revision.number,
+            commit.get(..8).unwrap_or(commit),
+            revision
+                .kind
+                .map(|kind| format!(" ({kind})"))
+                .unwrap_or_default(),
+            revision.uploader,
+            revision.created,
+        );
+    }
+
+    let _ = writeln!(output, "\n## Votes\n");
+    for (label, label_info) in &info.labels {
+        for vote in &label_info.all {
+            if let Some(value) = vote.value.filter(|value| *value != 0) {
+                let _ = writeln!(
+                    output,
+                    "- {label}{} by {}{}",
+                    format_vote(value),
+                    vote.account,
+                    vote.date
+                        .as_ref()
+                        .map(|date| format!(" at {date}"))
+                        .unwrap_or_default()
+                );
+            }
+        }
+    }
+
+    let _ = writeln!(output, "\n## Messages");
+    for message in &info.messages {
+        let _ = writeln!(
+            output,
+            "\n### {} at {}{}\n",
+            message
+                .author
+                .as_ref()
+                .map(|author| author.to_string())
+                .unwrap_or_else(|| "Gerrit".to_owned()),
+            message.date,
+            message
+                .revision_number
+                .map(|patchset| format!(" (patchset {patchset})"))
+                .unwrap_or_default(),
+        );
+        let _ = writeln!(output, "{}", message.message);
+    }
+
+    let _ = writeln!(output, "\n## Inline comments");
+    for (path, comments) in comments {
+        let _ = writeln!(output, "\n### `{path}`\n");
+        for comment in comments {
+            let _ = writeln!(
+                output,
+                "- Patchset {}, {}: {} at {}{}",
+                comment
+                    .patch_set
+                    .map(|patchset| patchset.to_string())
+                    .unwrap_or_else(|| "?".to_owned()),
+                comment
+                    .line
+                    .map(|line| format!("line {line}"))
+                    .unwrap_or_else(|| "file".to_owned()),
+                comment
+                    .author
+                    .as_ref()
+                    .map(|author| author.to_string())
+                    .unwrap_or_default(),
+                comment.updated,
+                if comment.unresolved == Some(true) {
+                    " (unresolved)"
+                } else {
+                    ""
+                },
+            );
+            for line in comment.message.as_deref().unwrap_or_default().lines() {
+                let _ = writeln!(output, "  > {line}");
+            }
+        }
+    }
+
+    output
+}
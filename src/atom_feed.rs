@@ -0,0 +1,104 @@
+use std::fmt::Write;
+
+use miette::IntoDiagnostic;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::change::Change;
+
+/// Format changes as an Atom feed, with one entry per change.
+///
+/// `id` should be a URL identifying the feed, like the URL of the query in Gerrit's web UI.
+pub fn format_atom_feed(
+    title: &str,
+    id: &str,
+    changes: &[Change],
+    now: OffsetDateTime,
+) -> miette::Result<String> {
+    let updated = changes
+        .iter()
+        .map(|change| change.last_updated)
+        .max()
+        .unwrap_or(now);
+
+    let mut output = String::new();
+    let _ = writeln!(output, r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    let _ = writeln!(output, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    let _ = writeln!(output, "  <title>{}</title>", escape_xml(title));
+    let _ = writeln!(output, "  <id>{}</id>", escape_xml(id));
+    let _ = writeln!(output, r#"  <link href="{}"/>"#, escape_xml(id));
+    let _ = writeln!(
+        output,
+        "  <updated>{}</updated>",
+        updated.format(&Rfc3339).into_diagnostic()?
+    );
+    let _ = writeln!(output, "  <generator>git-gr</generator>");
+
+    for change in changes {
+        let url = change.web_url();
+        let _ = writeln!(output, "  <entry>");
+        let _ = writeln!(
+            output,
+            "    <title>{}: {}</title>",
+            change.number,
+            escape_xml(change.subject.as_deref().unwrap_or_default())
+        );
+        let _ = writeln!(output, "    <id>{}</id>", escape_xml(&url));
+        let _ = writeln!(output, r#"    <link href="{}"/>"#, escape_xml(&url));
+        let _ = writeln!(
+            output,
+            "    <published>{}</published>",
+            change.created_on.format(&Rfc3339).into_diagnostic()?
+        );
+        let _ = writeln!(
+            output,
+            "    <updated>{}</updated>",
+            change.last_updated.format(&Rfc3339).into_diagnostic()?
+        );
+        let _ = writeln!(
+            output,
+            "    <author><name>{}</name></author>",
+            escape_xml(&change.owner.name)
+        );
+        let _ = writeln!(
+            output,
+            "    <summary>{} ({}), {}</summary>",
+            escape_xml(&change.project),
+            escape_xml(&change.branch),
+            change.status
+        );
+        let _ = writeln!(output, "  </entry>");
+    }
+
+    let _ = writeln!(output, "</feed>");
+    Ok(output)
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            escape_xml(r#"Don't <panic> on "A & B""#),
+            "Don&apos;t &lt;panic&gt; on &quot;A &amp; B&quot;"
+        );
+    }
+}
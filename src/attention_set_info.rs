@@ -0,0 +1,23 @@
+use std::fmt::Display;
+
+use crate::account_info::AccountInfo;
+
+/// An account in a change's attention set.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#attention-set-info>
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct AttentionSetInfo {
+    pub account: AccountInfo,
+    /// Why the account was added to the attention set.
+    pub reason: Option<String>,
+}
+
+impl Display for AttentionSetInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.account)?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({reason})")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,10 @@
+/// An account to add to or remove from a change's attention set.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#attention-set-input>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct AttentionSetInput {
+    /// A username, email, account ID, or `self`.
+    pub user: String,
+    /// Why the attention set is being updated. Gerrit requires a reason.
+    pub reason: String,
+}
@@ -0,0 +1,59 @@
+use calm_io::stdoutln;
+use miette::miette;
+
+use crate::foreach::stack_changes;
+use crate::foreach::StackWorktree;
+use crate::gerrit::GerritGitRemote;
+
+/// Binary search the stack for the first change where a command fails.
+///
+/// Assumes that once a change fails, every change after it fails too.
+pub fn bisect(
+    gerrit: &mut GerritGitRemote,
+    whole_stack: bool,
+    command: &[String],
+) -> miette::Result<()> {
+    let changes = stack_changes(gerrit, whole_stack)?;
+    let mut linear = true;
+    for window in changes.windows(2) {
+        if !gerrit
+            .get_change(window[1])?
+            .depends_on_numbers()
+            .contains(&window[0])
+        {
+            linear = false;
+        }
+    }
+    if !linear {
+        return Err(miette!(
+            help = "Run without `--stack` to bisect the changes up to `HEAD`",
+            "The stack has multiple branches, so it can't be bisected"
+        ));
+    }
+
+    let mut worktree = StackWorktree::new(gerrit.git())?;
+
+    let last = *changes.last().expect("Stacks have at least one change");
+    if worktree.run(gerrit, last, command)? {
+        worktree.remove()?;
+        tracing::info!("`{}` passes on every change", command.join(" "));
+        return Ok(());
+    }
+
+    // The first change that fails is in `low..=high`.
+    let mut low = 0;
+    let mut high = changes.len() - 1;
+    while low < high {
+        let middle = (low + high) / 2;
+        if worktree.run(gerrit, changes[middle], command)? {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+
+    worktree.remove()?;
+
+    let _ = stdoutln!("First failing change: {}", changes[low].pretty(gerrit)?);
+    Ok(())
+}
@@ -11,6 +11,7 @@ use crate::change::Change;
 use crate::change_id::ChangeId;
 use crate::change_number::ChangeNumber;
 use crate::commit_hash::CommitHash;
+use crate::config::Config;
 use crate::endpoint::Endpoint;
 use crate::gerrit_project::GerritProject;
 use crate::patchset::ChangePatchset;
@@ -31,7 +32,7 @@ impl GerritCache {
     pub fn new(host: &GerritProject) -> miette::Result<Self> {
         Ok(Self::Disk(
             DiskCache::new(&host.to_string())
-                .set_lifespan(CACHE_LIFESPAN.as_secs())
+                .set_lifespan(Config::global().cache_lifespan().as_secs())
                 .build()
                 .into_diagnostic()
                 .wrap_err("Failed to initialize Gerrit API cache")?,
@@ -40,7 +41,7 @@ impl GerritCache {
 
     pub fn clear_cache(&mut self) {
         match self {
-            GerritCache::None => todo!(),
+            GerritCache::None => {}
             GerritCache::Disk(cache) => {
                 // `cached` has no `cache_clear` operation, so we have to do this workaround.
                 // See: https://github.com/jaemk/cached/issues/197
@@ -57,7 +58,7 @@ impl GerritCache {
                         cache.cache_set_lifespan(lifespan);
                     }
                     None => {
-                        cache.cache_set_lifespan(CACHE_LIFESPAN.as_secs());
+                        cache.cache_set_lifespan(Config::global().cache_lifespan().as_secs());
                     }
                 }
             }
@@ -125,6 +126,12 @@ pub enum CacheKey {
     Query(String),
     /// A request to the REST API.
     Api(Endpoint),
+    /// The numbers of recently-fetched changes, most recent first.
+    RecentChanges,
+    /// Marks a change as modified since cached queries including it were made.
+    ///
+    /// Until the marker expires, cached query results including the change are refreshed.
+    Stale(ChangeNumber),
 }
 
 impl Display for CacheKey {
@@ -137,6 +144,8 @@ impl Display for CacheKey {
             CacheKey::Fetch(change) => write!(f, "fetch-{change}"),
             CacheKey::Query(query) => write!(f, "query-{query}"),
             CacheKey::Api(endpoint) => write!(f, "api-{endpoint}"),
+            CacheKey::RecentChanges => write!(f, "recent-changes"),
+            CacheKey::Stale(change) => write!(f, "stale-{change}"),
         }
     }
 }
@@ -147,4 +156,6 @@ pub enum CacheValue {
     Fetch(CommitHash),
     Query(QueryResult<Change>),
     Api(String),
+    RecentChanges(Vec<ChangeNumber>),
+    Stale,
 }
@@ -0,0 +1,83 @@
+use calm_io::stdoutln;
+use dialoguer::Confirm;
+use miette::miette;
+use miette::IntoDiagnostic;
+
+use crate::change_key::ChangeKey;
+use crate::change_number::ChangeNumber;
+use crate::dependency_graph::DependencyGraph;
+use crate::gerrit::Gerrit;
+use crate::offline_queue::perform_or_queue;
+use crate::offline_queue::Action;
+use crate::offline_queue::PerformOutcome;
+
+/// The changes to act on: a single change, or every open change in its chain.
+pub fn target_changes(
+    gerrit: &mut Gerrit,
+    change: ChangeKey,
+    chain: bool,
+) -> miette::Result<Vec<ChangeNumber>> {
+    let change = gerrit.get_change(change)?.number;
+    if !chain {
+        return Ok(vec![change]);
+    }
+
+    let graph = DependencyGraph::traverse(gerrit, change)?;
+    let mut changes = Vec::new();
+    for change in graph.changes() {
+        if gerrit.get_change(change)?.open {
+            changes.push(change);
+        }
+    }
+    Ok(changes)
+}
+
+/// List the given changes and ask for confirmation before performing an action on them.
+pub fn confirm_changes(
+    gerrit: &Gerrit,
+    changes: &[ChangeNumber],
+    action: &Action,
+) -> miette::Result<bool> {
+    let _ = stdoutln!("Changes to {action}:");
+    for change in changes {
+        let _ = stdoutln!("• {}", change.pretty(gerrit)?);
+    }
+    Confirm::new()
+        .with_prompt(format!("{action} {} changes?", changes.len()))
+        .default(false)
+        .interact()
+        .into_diagnostic()
+}
+
+/// Perform an action on each of the given changes, reporting which succeeded and which failed.
+///
+/// Keeps going after failures, so one bad change doesn't stop the rest of the chain from being
+/// updated.
+pub fn perform_on_changes(
+    gerrit: &mut Gerrit,
+    changes: &[ChangeNumber],
+    action: &Action,
+) -> miette::Result<()> {
+    let mut failed = 0;
+    for change in changes {
+        match perform_or_queue(gerrit, *change, action.clone()) {
+            Ok(PerformOutcome::Performed(_)) => {
+                tracing::info!("✔ {}: {action}", change.pretty(gerrit)?);
+            }
+            Ok(PerformOutcome::Queued) => {}
+            Err(error) => {
+                failed += 1;
+                tracing::error!("✗ {}: {error}", change.pretty(gerrit)?);
+            }
+        }
+    }
+
+    if failed > 0 {
+        Err(miette!(
+            "Failed to {action} on {failed} of {} changes",
+            changes.len()
+        ))
+    } else {
+        Ok(())
+    }
+}
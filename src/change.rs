@@ -4,23 +4,37 @@ use comfy_table::Attribute;
 use comfy_table::Cell;
 use comfy_table::Color;
 use miette::IntoDiagnostic;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
 use serde_with::serde_as;
 use serde_with::TimestampSeconds;
 use time::macros::format_description;
+use time::Duration;
 use time::OffsetDateTime;
 
+use crate::age::format_age;
+use crate::age::StalledAfter;
+use crate::approval::format_votes;
+use crate::approval::Approval;
 use crate::author::Author;
 use crate::change_id::ChangeId;
 use crate::change_number::ChangeNumber;
 use crate::change_status::ChangeStatus;
+use crate::config::expand_change_url;
+use crate::config::Config;
 use crate::current_patch_set::CurrentPatchSet;
+use crate::date_format::DateFormat;
 use crate::depends_on::DependsOn;
 use crate::gerrit::Gerrit;
 use crate::needed_by::NeededBy;
 use crate::patchset::ChangePatchset;
 use crate::patchset::Patchset;
+use crate::submit_label_status::SubmitLabelStatus;
 use crate::submit_records::SubmitRecord;
+use crate::submit_requirement::SubmitRequirement;
+use crate::submit_requirement_status::SubmitRequirementStatus;
 use crate::submit_status::SubmitStatus;
+use crate::timezone::LocalTimeZone;
 
 #[serde_as]
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -33,8 +47,14 @@ pub struct Change {
     pub subject: Option<String>,
     pub owner: Author,
     pub url: String,
+    #[serde(default)]
+    pub topic: Option<String>,
     pub hashtags: Vec<String>,
-    #[allow(dead_code)]
+    /// The full commit message, if queried with [`QueryOptions::commit_message`].
+    ///
+    /// [`QueryOptions::commit_message`]: crate::query::QueryOptions::commit_message
+    #[serde(default)]
+    pub commit_message: Option<String>,
     #[serde_as(as = "TimestampSeconds<i64>")]
     pub created_on: OffsetDateTime,
     #[serde_as(as = "TimestampSeconds<i64>")]
@@ -43,15 +63,32 @@ pub struct Change {
     pub status: ChangeStatus,
     #[serde(default)]
     pub wip: bool,
+    #[serde(default)]
+    pub private: bool,
     pub current_patch_set: CurrentPatchSet,
+    /// All of the change's patch sets, if queried with [`QueryOptions::patch_sets`].
+    ///
+    /// [`QueryOptions::patch_sets`]: crate::query::QueryOptions::patch_sets
+    #[serde(default)]
+    pub patch_sets: Vec<CurrentPatchSet>,
     pub submit_records: Vec<SubmitRecord>,
     #[serde(default)]
+    pub submit_requirements: Vec<SubmitRequirement>,
+    #[serde(default)]
     pub depends_on: Vec<DependsOn>,
     #[serde(default)]
     pub needed_by: Vec<NeededBy>,
 }
 
 impl Change {
+    /// The change's web URL, from the `change-url` config template if it's set.
+    pub fn web_url(&self) -> String {
+        match &Config::global().change_url {
+            Some(template) => expand_change_url(template, self.number, &self.project, &self.branch),
+            None => self.url.clone(),
+        }
+    }
+
     pub fn patchset(&self) -> ChangePatchset {
         ChangePatchset {
             change: self.number,
@@ -73,18 +110,29 @@ impl Change {
         }
     }
 
-    pub fn last_updated_cell(&self, timestamp_format: TimestampFormat) -> miette::Result<Cell> {
-        // _Something_ in my transitive dependency tree is making threads, which makes
-        // `time` refuse to get the local timezone. I don't think anything is going to
-        // change the local timezone so it's PRobably Fine.
-        //
-        // Safety: It's fine. It's FINE.
-        unsafe {
-            time::util::local_offset::set_soundness(time::util::local_offset::Soundness::Unsound)
-        };
-        let now = OffsetDateTime::now_local().into_diagnostic()?;
-        let now_date = now.date();
-        let date = self.last_updated.date();
+    pub fn last_updated_cell(
+        &self,
+        timestamp_format: TimestampFormat,
+        date_format: &DateFormat,
+        time_zone: &LocalTimeZone,
+    ) -> miette::Result<Cell> {
+        Ok(Cell::new(self.format_last_updated(
+            timestamp_format,
+            date_format,
+            time_zone,
+        )?))
+    }
+
+    /// Format the last-updated time compactly, omitting the date for changes updated today.
+    pub fn format_last_updated(
+        &self,
+        timestamp_format: TimestampFormat,
+        date_format: &DateFormat,
+        time_zone: &LocalTimeZone,
+    ) -> miette::Result<String> {
+        let now_date = time_zone.now().date();
+        let last_updated = time_zone.to_local(self.last_updated);
+        let date = last_updated.date();
         let formatted = {
             if now_date == date {
                 // Today.
@@ -98,20 +146,103 @@ impl Change {
                         format_description!("[hour padding:none repr:24]:[minute]")
                     }
                 };
-                self.last_updated.format(format)
+                last_updated.format(format)
             } else if now_date.year() == date.year() {
-                self.last_updated
-                    .format(format_description!("[month]-[day]"))
+                last_updated.format(&date_format.month_day)
             } else {
-                self.last_updated
-                    .format(format_description!("[year]-[month]-[day]"))
+                last_updated.format(&date_format.full)
             }
         }
         .into_diagnostic()?;
-        Ok(Cell::new(formatted))
+        Ok(formatted)
+    }
+
+    /// The number of inserted and deleted lines, like `+120/-30`.
+    ///
+    /// Colored by the total number of changed lines, so small changes stand out.
+    pub fn size_cell(&self) -> Cell {
+        let insertions = self.current_patch_set.size_insertions;
+        let deletions = self.current_patch_set.size_deletions;
+        let cell = Cell::new(format!("+{insertions}/-{deletions}"));
+        match insertions + deletions {
+            0..=49 => cell.fg(Color::Green),
+            50..=249 => cell.fg(Color::Yellow),
+            _ => cell.fg(Color::Red),
+        }
+    }
+
+    /// The kind of the current patchset relative to the previous one, like `trivial rebase`.
+    ///
+    /// Yellow if the code changed, so reviewers need to read it again.
+    pub fn kind_cell(&self) -> Cell {
+        let patchset = &self.current_patch_set;
+        if patchset.number == 1 {
+            Cell::new("new").add_attribute(Attribute::Dim)
+        } else if patchset.kind.is_rework() {
+            Cell::new(patchset.kind).fg(Color::Yellow)
+        } else {
+            Cell::new(patchset.kind).fg(Color::Green)
+        }
+    }
+
+    /// The votes on the current patchset, like `CR+2 V+1`.
+    ///
+    /// Red if anyone voted negatively.
+    pub fn votes_cell(&self) -> Cell {
+        votes_cell(&self.current_patch_set.approvals)
+    }
+
+    /// Our votes on the current patchset, like `CR+1`.
+    ///
+    /// If we haven't voted on the current patchset, shows our votes on the latest earlier
+    /// patchset we voted on, dimmed, like `CR+1 (PS2)`. Earlier patchsets are only known if the
+    /// change was queried with [`QueryOptions::all_approvals`].
+    ///
+    /// [`QueryOptions::all_approvals`]: crate::query::QueryOptions::all_approvals
+    pub fn my_votes_cell(&self, username: &str) -> Cell {
+        let mine = |patch_set: &CurrentPatchSet| {
+            patch_set
+                .approvals
+                .iter()
+                .filter(|approval| approval.by.username == username)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let current = mine(&self.current_patch_set);
+        if !format_votes(&current).is_empty() {
+            return votes_cell(&current);
+        }
+
+        self.patch_sets
+            .iter()
+            .rev()
+            .filter(|patch_set| patch_set.number < self.current_patch_set.number)
+            .find_map(|patch_set| {
+                let votes = format_votes(&mine(patch_set));
+                (!votes.is_empty()).then(|| format!("{votes} (PS{})", patch_set.number))
+            })
+            .map(|votes| Cell::new(votes).add_attribute(Attribute::Dim))
+            .unwrap_or_else(|| Cell::new(""))
     }
 
     pub fn ready_cell(&self) -> Cell {
+        if !self.submit_requirements.is_empty() {
+            return if !self.open {
+                Cell::new("closed").add_attribute(comfy_table::Attribute::Dim)
+            } else if self
+                .submit_requirements
+                .iter()
+                .any(|requirement| requirement.status == SubmitRequirementStatus::Error)
+            {
+                Cell::new("error").fg(Color::Red)
+            } else if self.is_submittable() == Some(true) {
+                Cell::new("✔").fg(Color::Green)
+            } else {
+                Cell::new("✗").fg(Color::Red)
+            };
+        }
+
         match self.submit_records.first() {
             Some(record) => match record.status {
                 SubmitStatus::Ok => Cell::new("✔").fg(Color::Green),
@@ -125,6 +256,134 @@ impl Change {
         }
     }
 
+    /// Can this change be submitted?
+    ///
+    /// Prefers submit requirements, falling back to legacy submit records. Returns `None` if
+    /// the change has neither.
+    pub fn is_submittable(&self) -> Option<bool> {
+        if !self.submit_requirements.is_empty() {
+            Some(
+                self.submit_requirements
+                    .iter()
+                    .all(|requirement| requirement.status.is_satisfied()),
+            )
+        } else {
+            self.submit_records
+                .first()
+                .map(|record| matches!(record.status, SubmitStatus::Ok))
+        }
+    }
+
+    /// Get the names of the requirements or labels blocking this change from being submitted.
+    pub fn unsatisfied_requirements(&self) -> Vec<&str> {
+        if !self.submit_requirements.is_empty() {
+            self.submit_requirements
+                .iter()
+                .filter(|requirement| !requirement.status.is_satisfied())
+                .map(|requirement| requirement.name.as_str())
+                .collect()
+        } else {
+            self.submit_records
+                .iter()
+                .flat_map(|record| &record.labels)
+                .filter(|label| {
+                    !matches!(label.status, SubmitLabelStatus::Ok | SubmitLabelStatus::May)
+                })
+                .map(|label| label.label.as_str())
+                .collect()
+        }
+    }
+
+    /// A short description of whether this change is ready to submit, for open changes.
+    /// How long it's been since the change was created.
+    pub fn age(&self, now: OffsetDateTime) -> Duration {
+        now - self.created_on
+    }
+
+    /// How long it's been since the change was last updated.
+    pub fn idle(&self, now: OffsetDateTime) -> Duration {
+        now - self.last_updated
+    }
+
+    /// Has this change been waiting for review without updates for too long?
+    pub fn is_stalled(&self, now: OffsetDateTime, stalled_after: StalledAfter) -> bool {
+        self.open && !self.wip && self.idle(now) > stalled_after.duration()
+    }
+
+    /// The change's age, like `3d`, highlighted if it's stalled.
+    pub fn age_cell(&self, now: OffsetDateTime, stalled_after: StalledAfter) -> Cell {
+        let cell = Cell::new(format_age(self.age(now)));
+        if self.is_stalled(now, stalled_after) {
+            cell.fg(Color::Yellow)
+        } else {
+            cell
+        }
+    }
+
+    /// A label for the change's age and how long it's been waiting, highlighted if it's
+    /// stalled.
+    pub fn age_label(&self, now: OffsetDateTime, stalled_after: StalledAfter) -> String {
+        let label = format!(
+            "updated {} ago, created {} ago",
+            format_age(self.idle(now)),
+            format_age(self.age(now))
+        );
+        if self.is_stalled(now, stalled_after) {
+            format!("stalled: {label}")
+                .if_supports_color(Stdout, |label| label.yellow())
+                .to_string()
+        } else {
+            label
+                .if_supports_color(Stdout, |label| label.dimmed())
+                .to_string()
+        }
+    }
+
+    pub fn readiness_label(&self) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+
+        let label = match self.is_submittable()? {
+            true => "✔ ready".to_owned(),
+            false => {
+                let unsatisfied = self.unsatisfied_requirements();
+                if unsatisfied.is_empty() {
+                    "✗ not ready".to_owned()
+                } else {
+                    format!("✗ needs {}", unsatisfied.join(", "))
+                }
+            }
+        };
+
+        Some(
+            label
+                .if_supports_color(Stdout, |label| label.dimmed())
+                .to_string(),
+        )
+    }
+
+    /// A label with the kind of the current patchset, like `PS3: trivial rebase`.
+    ///
+    /// `None` for closed changes and changes with only one patchset.
+    pub fn kind_label(&self) -> Option<String> {
+        let patchset = &self.current_patch_set;
+        if !self.open || patchset.number == 1 {
+            return None;
+        }
+
+        let label = format!("PS{}: {}", patchset.number, patchset.kind);
+        Some(if patchset.kind.is_rework() {
+            label
+                .if_supports_color(Stdout, |label| label.yellow())
+                .to_string()
+        } else {
+            label
+                .if_supports_color(Stdout, |label| label.dimmed())
+                .to_string()
+        })
+    }
+
     /// Remove merged and abandoned dependencies from this set.
     pub fn filter_unmerged(mut self, gerrit: &Gerrit) -> miette::Result<Self> {
         let depends_on = std::mem::take(&mut self.depends_on);
@@ -169,6 +428,19 @@ impl Change {
     }
 }
 
+/// Votes, like `CR+2 V+1`, in red if any are negative and green otherwise.
+fn votes_cell(approvals: &[Approval]) -> Cell {
+    let cell = Cell::new(format_votes(approvals));
+    if approvals
+        .iter()
+        .any(|approval| approval.numeric_value().is_some_and(|value| value < 0))
+    {
+        cell.fg(Color::Red)
+    } else {
+        cell.fg(Color::Green)
+    }
+}
+
 /// Support for Europeans.
 #[derive(Debug, Clone, Copy)]
 pub enum TimestampFormat {
@@ -177,3 +449,19 @@ pub enum TimestampFormat {
     /// 24-hour time.
     TwentyFourHour,
 }
+
+impl TimestampFormat {
+    /// Get the timestamp format from the `GIT_GR_24_HOUR_TIME` environment variable, or the
+    /// `24-hour-time` setting in the config file.
+    pub fn from_env() -> Self {
+        if std::env::var("GIT_GR_24_HOUR_TIME")
+            .map(|value| !value.is_empty())
+            .unwrap_or(false)
+            || Config::global().twenty_four_hour_time.unwrap_or(false)
+        {
+            Self::TwentyFourHour
+        } else {
+            Self::TwelveHour
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::str::FromStr;
+
+use miette::miette;
+
+use crate::change_id::ChangeId;
+use crate::change_key::ChangeKey;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+use crate::patchset::ChangeSpec;
+use crate::patchset::Patchset;
+
+/// A change given on the command line.
+///
+/// This may be a change number (optionally with a patchset, like `1234/5`), a Change-Id, or a
+/// URL copied from the Gerrit web UI, like `https://gerrit.example.com/c/project/+/1234/5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeArg {
+    Spec(ChangeSpec),
+    Id(ChangeId),
+    Url { host: String, spec: ChangeSpec },
+}
+
+impl ChangeArg {
+    /// The key to look up the change with.
+    pub fn key(&self) -> ChangeKey {
+        match self {
+            ChangeArg::Spec(spec) | ChangeArg::Url { spec, .. } => spec.change.into(),
+            ChangeArg::Id(change_id) => change_id.clone().into(),
+        }
+    }
+
+    /// The patchset given, if any.
+    pub fn patchset(&self) -> Option<Patchset> {
+        match self {
+            ChangeArg::Spec(spec) | ChangeArg::Url { spec, .. } => spec.patchset,
+            ChangeArg::Id(_) => None,
+        }
+    }
+
+    /// Check that a URL points to one of the given hosts.
+    ///
+    /// Change numbers are only unique per-host, so a URL for another host would check out an
+    /// unrelated change. The web UI is often served from a different host than SSH, so callers
+    /// should pass the host of the change's own web URL as well as the API host.
+    pub fn check_host(&self, expected: &[&str]) -> miette::Result<()> {
+        match self {
+            ChangeArg::Url { host, .. } if !expected.contains(&host.as_str()) => Err(miette!(
+                help = format!("This repository's Gerrit is at {}", expected.join(" or ")),
+                "Change URL is for another Gerrit host: {host}"
+            )
+            .classify(ErrorKind::Usage)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for ChangeArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(rest) = s
+            .strip_prefix("https://")
+            .or_else(|| s.strip_prefix("http://"))
+        {
+            return parse_url(rest).ok_or_else(|| format!("Invalid change URL `{s}`"));
+        }
+
+        if let Ok(change_id) = s.parse::<ChangeId>() {
+            return Ok(Self::Id(change_id));
+        }
+
+        s.parse().map(Self::Spec).map_err(|_| {
+            format!("Invalid change `{s}`; expected a change number, Change-Id, or URL")
+        })
+    }
+}
+
+/// Get the host of a URL like `https://gerrit.example.com:8443/c/project/+/1234`.
+pub fn url_host(url: &str) -> Option<&str> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    rest.split(['/', ':']).next()
+}
+
+/// Parse a change URL, with the scheme already removed.
+///
+/// Matches `HOST/c/PROJECT/+/1234/5`, `HOST/#/c/1234/5`, and `HOST/1234`. Anything after the
+/// patchset (a file path, or a comment link) is ignored, and a diff range like `1..5` is read as
+/// its newer patchset.
+fn parse_url(url: &str) -> Option<ChangeArg> {
+    let (authority, path) = url.split_once('/')?;
+    let host = authority.split(':').next()?;
+    let path = path.split('?').next()?;
+    let path = match path.strip_prefix("#/") {
+        Some(path) => path,
+        None => path.split('#').next()?,
+    };
+    let path = match path.split_once("/+/") {
+        Some((_project, path)) => path,
+        None => path.strip_prefix("c/").unwrap_or(path),
+    };
+
+    let mut segments = path.split('/');
+    let change = segments.next()?.parse().ok()?;
+    let patchset = segments
+        .next()
+        .map(|segment| segment.rsplit("..").next().unwrap_or(segment))
+        .and_then(|segment| segment.parse().ok())
+        .map(Patchset::new);
+
+    Some(ChangeArg::Url {
+        host: host.to_owned(),
+        spec: ChangeSpec { change, patchset },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn url(host: &str, change: &str, patchset: Option<u64>) -> ChangeArg {
+        ChangeArg::Url {
+            host: host.to_owned(),
+            spec: ChangeSpec {
+                change: change.parse().unwrap(),
+                patchset: patchset.map(Patchset::new),
+            },
+        }
+    }
+
+    #[test]
+    fn test_change_arg_from_str() {
+        assert_eq!(
+            "1234/5".parse::<ChangeArg>().unwrap(),
+            ChangeArg::Spec("1234/5".parse().unwrap())
+        );
+        assert_eq!(
+            "I0123456789abcdef0123456789abcdef01234567"
+                .parse::<ChangeArg>()
+                .unwrap(),
+            ChangeArg::Id("I0123456789abcdef0123456789abcdef01234567".parse().unwrap())
+        );
+        assert_eq!(
+            "https://gerrit.example.com/c/my/project/+/12345/3"
+                .parse::<ChangeArg>()
+                .unwrap(),
+            url("gerrit.example.com", "12345", Some(3))
+        );
+        assert_eq!(
+            "https://gerrit.example.com/c/project/+/12345"
+                .parse::<ChangeArg>()
+                .unwrap(),
+            url("gerrit.example.com", "12345", None)
+        );
+        assert_eq!(
+            "https://gerrit.example.com:8443/c/project/+/12345/1..4/src/main.rs?tab=comments"
+                .parse::<ChangeArg>()
+                .unwrap(),
+            url("gerrit.example.com", "12345", Some(4))
+        );
+        assert_eq!(
+            "https://gerrit.example.com/c/project/+/12345/comment/abcdef/"
+                .parse::<ChangeArg>()
+                .unwrap(),
+            url("gerrit.example.com", "12345", None)
+        );
+        assert_eq!(
+            "http://gerrit.example.com/#/c/12345/2"
+                .parse::<ChangeArg>()
+                .unwrap(),
+            url("gerrit.example.com", "12345", Some(2))
+        );
+        assert_eq!(
+            "https://gerrit.example.com/12345"
+                .parse::<ChangeArg>()
+                .unwrap(),
+            url("gerrit.example.com", "12345", None)
+        );
+        assert!("https://gerrit.example.com/dashboard/self"
+            .parse::<ChangeArg>()
+            .is_err());
+        assert!("abc".parse::<ChangeArg>().is_err());
+    }
+
+    #[test]
+    fn test_change_arg_check_host() {
+        let change = url("review.example.com", "12345", None);
+        assert!(change
+            .check_host(&["ssh.review.example.com", "review.example.com"])
+            .is_ok());
+        assert!(change.check_host(&["gerrit.other.com"]).is_err());
+        assert!(ChangeArg::Spec("1".parse().unwrap())
+            .check_host(&["gerrit.other.com"])
+            .is_ok());
+        assert_eq!(
+            url_host("https://review.example.com:8443/c/project/+/1"),
+            Some("review.example.com")
+        );
+    }
+}
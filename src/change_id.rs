@@ -1,4 +1,9 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+
 use derive_more::{AsRef, Constructor, Deref, DerefMut, Display, From, Into};
+use miette::miette;
+use regex::Regex;
 
 use crate::change::Change;
 
@@ -25,6 +30,43 @@ use crate::change::Change;
 #[serde(transparent)]
 pub struct ChangeId(String);
 
+impl ChangeId {
+    /// Find the `Change-Id` trailer in a commit message.
+    pub fn from_commit_message(commit_message: &str) -> Option<Self> {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        RE.get_or_init(|| {
+            Regex::new(
+                r"(?xm)
+                ^
+                Change-Id:\ (?P<change_id>I[[:xdigit:]]{40})
+                $
+                ",
+            )
+            .expect("Regex parses")
+        })
+        .captures(commit_message)
+        .map(|captures| Self(captures["change_id"].to_owned()))
+    }
+}
+
+impl FromStr for ChangeId {
+    type Err = miette::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        if RE
+            .get_or_init(|| Regex::new(r"^I[[:xdigit:]]{40}$").expect("Regex parses"))
+            .is_match(s)
+        {
+            Ok(Self(s.to_owned()))
+        } else {
+            Err(miette!(
+                "Change-Ids start with `I` followed by 40 hex characters: {s}"
+            ))
+        }
+    }
+}
+
 impl From<Change> for ChangeId {
     fn from(change: Change) -> Self {
         change.id
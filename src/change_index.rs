@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+use std::io::BufReader;
+use std::io::BufWriter;
+
+use camino::Utf8PathBuf;
+use fs_err::File;
+use miette::Context;
+use miette::IntoDiagnostic;
+use serde_with::serde_as;
+use serde_with::TimestampSeconds;
+use time::OffsetDateTime;
+
+use crate::change_id::ChangeId;
+use crate::change_number::ChangeNumber;
+use crate::git::Git;
+use crate::patchset::Patchset;
+
+/// What we last knew about a change, locally.
+#[serde_as]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct ChangeIndexEntry {
+    pub number: ChangeNumber,
+    pub patchset: Patchset,
+    /// The local branch the change was last pushed from, if any.
+    pub branch: Option<String>,
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// A local index from Change-Ids to change numbers, patchsets, and branches.
+///
+/// Updated whenever changes are fetched or pushed, so that changes can be resolved without
+/// talking to Gerrit. Stored in the `.git` directory.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
+pub struct ChangeIndex {
+    pub changes: BTreeMap<ChangeId, ChangeIndexEntry>,
+}
+
+impl ChangeIndex {
+    pub fn read(git: &Git) -> miette::Result<Self> {
+        let path = index_path(git)?;
+        if path.exists() {
+            serde_json::from_reader(BufReader::new(File::open(&path).into_diagnostic()?))
+                .into_diagnostic()
+                .wrap_err_with(|| format!("Failed to read Change-Id index from `{path}`"))
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn write(&self, git: &Git) -> miette::Result<()> {
+        let path = index_path(git)?;
+        let writer = BufWriter::new(File::create(path).into_diagnostic()?);
+        serde_json::to_writer(writer, self).into_diagnostic()?;
+        Ok(())
+    }
+
+    /// Record a change's number and patchset, and its branch if known.
+    ///
+    /// If `branch` is `None`, the previously-recorded branch is kept.
+    pub fn update(
+        git: &Git,
+        id: ChangeId,
+        number: ChangeNumber,
+        patchset: Patchset,
+        branch: Option<String>,
+    ) -> miette::Result<()> {
+        let mut index = Self::read(git)?;
+        let branch = branch.or_else(|| {
+            index
+                .changes
+                .get(&id)
+                .and_then(|entry| entry.branch.clone())
+        });
+        index.changes.insert(
+            id,
+            ChangeIndexEntry {
+                number,
+                patchset,
+                branch,
+                updated_at: OffsetDateTime::now_utc(),
+            },
+        );
+        index.write(git)
+    }
+
+    /// Look up a change, making sure its branch still points to a commit with its Change-Id.
+    ///
+    /// If the branch was renamed or deleted, local branches are searched for the Change-Id and
+    /// the index is updated.
+    pub fn resolve(git: &Git, id: &ChangeId) -> miette::Result<Option<ChangeIndexEntry>> {
+        let mut index = Self::read(git)?;
+        let entry = match index.changes.get_mut(id) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let branch_matches = |branch: &str| {
+            git.change_id(&format!("refs/heads/{branch}"))
+                .map(|branch_id| &branch_id == id)
+                .unwrap_or(false)
+        };
+
+        if !entry.branch.as_deref().is_some_and(branch_matches) {
+            let found = git
+                .refs("refs/heads/")?
+                .into_iter()
+                .filter_map(|name| name.strip_prefix("refs/heads/").map(ToOwned::to_owned))
+                .find(|branch| branch_matches(branch));
+            if found != entry.branch {
+                tracing::debug!(?found, previous = ?entry.branch, "Branch for {id} moved");
+                entry.branch = found;
+                let entry = entry.clone();
+                index.write(git)?;
+                return Ok(Some(entry));
+            }
+        }
+
+        Ok(Some(entry.clone()))
+    }
+}
+
+pub fn index_path(git: &Git) -> miette::Result<Utf8PathBuf> {
+    git.get_git_dir()
+        .map(|git_dir| git_dir.join("git-gr-change-index.json"))
+}
@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+
+use crate::account_info::AccountInfo;
+use crate::change_id::ChangeId;
+use crate::change_message_info::ChangeMessageInfo;
+use crate::change_number::ChangeNumber;
+use crate::change_status::ChangeStatus;
+use crate::label_info::LabelInfo;
+use crate::revision_info::RevisionInfo;
+
+/// A change, as returned from the REST API.
+///
+/// Most commands use [`crate::change::Change`] from `gerrit query` instead; this is used when
+/// we need data only the REST API provides.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#change-info>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct ChangeInfo {
+    pub project: String,
+    pub branch: String,
+    pub change_id: ChangeId,
+    pub subject: String,
+    pub status: ChangeStatus,
+    #[serde(rename = "_number")]
+    pub number: ChangeNumber,
+    pub owner: AccountInfo,
+    /// When the change was created, like `2013-02-01 09:59:32.126000000`.
+    pub created: String,
+    /// When the change was last updated, like `2013-02-01 09:59:32.126000000`.
+    pub updated: String,
+    /// Map from label names to votes, if requested with `o=LABELS` or `o=DETAILED_LABELS`.
+    #[serde(default)]
+    pub labels: BTreeMap<String, LabelInfo>,
+    /// Messages on the change, if requested with `o=MESSAGES`.
+    #[serde(default)]
+    pub messages: Vec<ChangeMessageInfo>,
+    /// Map from commit hashes to patchsets, if requested with `o=ALL_REVISIONS` or
+    /// `o=CURRENT_REVISION`.
+    #[serde(default)]
+    pub revisions: BTreeMap<String, RevisionInfo>,
+}
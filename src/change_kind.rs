@@ -0,0 +1,81 @@
+use std::fmt::Display;
+
+/// The kind of a patchset, relative to the previous patchset.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/json.html#patchSet>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangeKind {
+    /// Nontrivial content changes.
+    Rework,
+    /// Conflict-free merge between the new parent and the prior patch set.
+    TrivialRebase,
+    /// Conflict-free merge between the new parent and the prior patch set, with an updated
+    /// commit message.
+    TrivialRebaseWithMessageUpdate,
+    /// Conflict-free change of the first (left) parent of a merge commit.
+    MergeFirstParentUpdate,
+    /// No code changed; same tree and same parents.
+    NoCodeChange,
+    /// No changes; same commit message, tree, and parents.
+    NoChange,
+    /// A kind this version of `git-gr` doesn't know about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ChangeKind {
+    /// The name of this change kind as used in `changekind:` queries.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Rework => "REWORK",
+            ChangeKind::TrivialRebase => "TRIVIAL_REBASE",
+            ChangeKind::TrivialRebaseWithMessageUpdate => "TRIVIAL_REBASE_WITH_MESSAGE_UPDATE",
+            ChangeKind::MergeFirstParentUpdate => "MERGE_FIRST_PARENT_UPDATE",
+            ChangeKind::NoCodeChange => "NO_CODE_CHANGE",
+            ChangeKind::NoChange => "NO_CHANGE",
+            ChangeKind::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// Does this kind of patchset change the code, so reviewers need to read it again?
+    ///
+    /// Unknown kinds are assumed to.
+    pub fn is_rework(&self) -> bool {
+        matches!(self, ChangeKind::Rework | ChangeKind::Unknown)
+    }
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::Rework => write!(f, "rework"),
+            ChangeKind::TrivialRebase => write!(f, "trivial rebase"),
+            ChangeKind::TrivialRebaseWithMessageUpdate => {
+                write!(f, "trivial rebase with message update")
+            }
+            ChangeKind::MergeFirstParentUpdate => write!(f, "merge first parent update"),
+            ChangeKind::NoCodeChange => write!(f, "no code change"),
+            ChangeKind::NoChange => write!(f, "no change"),
+            ChangeKind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_change_kind_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<ChangeKind>(r#""TRIVIAL_REBASE""#).unwrap(),
+            ChangeKind::TrivialRebase
+        );
+        assert_eq!(
+            serde_json::from_str::<ChangeKind>(r#""SOME_NEW_KIND""#).unwrap(),
+            ChangeKind::Unknown
+        );
+    }
+}
@@ -0,0 +1,20 @@
+use crate::account_info::AccountInfo;
+use crate::patchset::Patchset;
+
+/// A message on a change, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#change-message-info>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct ChangeMessageInfo {
+    pub id: String,
+    /// The author of the message; unset for messages generated by Gerrit.
+    pub author: Option<AccountInfo>,
+    /// When the message was posted, like `2013-02-01 09:59:32.126000000`.
+    pub date: String,
+    pub message: String,
+    /// A tag like `autogenerated:gerrit:newPatchSet`.
+    pub tag: Option<String>,
+    #[serde(rename = "_revision_number")]
+    pub revision_number: Option<Patchset>,
+}
@@ -1,4 +1,6 @@
 use std::fmt::Display;
+use std::num::ParseIntError;
+use std::str::FromStr;
 
 use clap::builder::RangedU64ValueParser;
 use clap::builder::TypedValueParser;
@@ -11,6 +13,8 @@ use tap::Pipe;
 use crate::change::Change;
 use crate::change_status::ChangeStatus;
 use crate::gerrit::Gerrit;
+use crate::hyperlink::hyperlink;
+use crate::hyperlink::Stream;
 use crate::patchset::ChangePatchset;
 use crate::patchset::Patchset;
 
@@ -30,15 +34,10 @@ impl Display for ChangeNumber {
 }
 
 impl ChangeNumber {
+    /// The last two digits of the change number, zero-padded, as used in
+    /// `refs/changes/NN/...` refs.
     pub fn last_two(&self) -> String {
-        let str = self.to_string();
-        let len = str.len();
-        if len <= 2 {
-            str
-        } else {
-            // TODO: uhhhh
-            str[len - 2..].to_owned()
-        }
+        format!("{:02}", self.0 % 100)
     }
 
     pub fn with_patchset(&self, patchset: Patchset) -> ChangePatchset {
@@ -50,6 +49,7 @@ impl ChangeNumber {
 
     pub fn pretty(&self, gerrit: &Gerrit) -> miette::Result<String> {
         let change = gerrit.get_change(*self)?;
+        let url = change.web_url();
         let subject = change.subject;
         let abandoned = change.status == ChangeStatus::Abandoned;
         let merged = change.status == ChangeStatus::Merged;
@@ -67,10 +67,14 @@ impl ChangeNumber {
         };
         Ok(format!(
             "{}{}",
-            self.if_supports_color(Stderr, |change| Style::new()
-                .bold()
-                .pipe(styled)
-                .style(change)),
+            hyperlink(
+                self.if_supports_color(Stderr, |change| Style::new()
+                    .bold()
+                    .pipe(styled)
+                    .style(change)),
+                &url,
+                Stream::Stderr
+            ),
             subject
                 .map(|subject| format!(" ({subject})"))
                 .unwrap_or_default()
@@ -79,6 +83,14 @@ impl ChangeNumber {
     }
 }
 
+impl FromStr for ChangeNumber {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
 impl From<Change> for ChangeNumber {
     fn from(change: Change) -> Self {
         change.number
@@ -110,3 +122,27 @@ impl TypedValueParser for ChangeNumberParser {
             .map(ChangeNumber)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_last_two() {
+        assert_eq!(ChangeNumber(7).last_two(), "07");
+        assert_eq!(ChangeNumber(42).last_two(), "42");
+        assert_eq!(ChangeNumber(100).last_two(), "00");
+        assert_eq!(ChangeNumber(12345).last_two(), "45");
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_last_two(number in 1..10_000u64) {
+            let last_two = ChangeNumber(number).last_two();
+            prop_assert_eq!(last_two.len(), 2);
+            prop_assert_eq!(last_two.parse::<u64>().unwrap(), number % 100);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use dialoguer::FuzzySelect;
+use miette::miette;
+use miette::IntoDiagnostic;
+
+use crate::change::Change;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+use crate::gerrit::Gerrit;
+use crate::query::QueryOptions;
+
+/// Interactively choose one of the changes matching a query, filtering them by typing.
+pub fn pick_change(gerrit: &Gerrit, query: String) -> miette::Result<Change> {
+    let mut changes = gerrit
+        .query(
+            QueryOptions::new(query.clone())
+                .current_patch_set()
+                .no_limit(),
+        )?
+        .changes;
+    if changes.is_empty() {
+        return Err(miette!("No changes match `{query}`").classify(ErrorKind::NotFound));
+    }
+
+    let items = changes
+        .iter()
+        .map(|change| {
+            format!(
+                "{} {} ({})",
+                change.number,
+                change.subject.as_deref().unwrap_or_default(),
+                change.owner.username
+            )
+        })
+        .collect::<Vec<_>>();
+    let index = FuzzySelect::new()
+        .with_prompt("Which change?")
+        .items(&items)
+        .default(0)
+        .interact()
+        .into_diagnostic()?;
+
+    Ok(changes.swap_remove(index))
+}
@@ -0,0 +1,13 @@
+/// Where and how to cherry-pick a change.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#cherrypick-input>
+#[derive(serde::Serialize, Debug, Clone)]
+pub struct CherryPickInput {
+    /// The branch to cherry-pick onto.
+    pub destination: String,
+    /// The commit message for the new change; defaults to the original commit message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Add the original change's reviewers to the new change.
+    pub keep_reviewers: bool,
+}
@@ -0,0 +1,57 @@
+use calm_io::stdoutln;
+
+use crate::age::parse_duration;
+use crate::chain;
+use crate::gerrit::Gerrit;
+use crate::offline_queue::Action;
+use crate::query::QueryOptions;
+
+/// The default message posted when abandoning stale changes.
+///
+/// `{older_than}` is replaced with the `--older-than` duration.
+pub const DEFAULT_CLEANUP_MESSAGE: &str =
+    "Abandoning this change because it hasn't been updated in {older_than}.";
+
+/// Abandon open changes which haven't been updated recently.
+pub fn cleanup(
+    gerrit: &mut Gerrit,
+    older_than: &str,
+    owner: &str,
+    message: &str,
+    dry_run: bool,
+    yes: bool,
+) -> miette::Result<()> {
+    let duration = parse_duration(older_than).map_err(|err| miette::miette!("{err}"))?;
+    let query = format!(
+        "is:open owner:{owner} project:{} age:{}h",
+        gerrit.project(),
+        duration.whole_hours()
+    );
+    let changes = gerrit
+        .query(QueryOptions::new(query))?
+        .changes
+        .into_iter()
+        .map(|change| change.number)
+        .collect::<Vec<_>>();
+
+    if changes.is_empty() {
+        tracing::info!("No open changes have gone {older_than} without updates");
+        return Ok(());
+    }
+
+    let action = Action::Abandon(Some(message.replace("{older_than}", older_than)));
+
+    if dry_run {
+        let _ = stdoutln!("Would abandon:");
+        for change in &changes {
+            let _ = stdoutln!("• {}", change.pretty(gerrit)?);
+        }
+        return Ok(());
+    }
+
+    if !yes && !chain::confirm_changes(gerrit, &changes, &action)? {
+        return Ok(());
+    }
+
+    chain::perform_on_changes(gerrit, &changes, &action)
+}
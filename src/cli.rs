@@ -4,10 +4,20 @@ use clap::Parser;
 use clap::Subcommand;
 use reqwest::Method;
 
+use crate::age::StalledAfter;
+use crate::change_arg::ChangeArg;
+use crate::change_id::ChangeId;
 use crate::change_number::ChangeNumber;
 use crate::commit_hash::CommitHash;
 use crate::endpoint::Endpoint;
+use crate::label_vote::LabelVote;
+use crate::output_format::OutputFormat;
+use crate::owner_format::OwnerFormat;
+use crate::patchset::ChangeSpec;
 use crate::patchset::Patchset;
+use crate::push_wip::PushWip;
+use crate::query_format::QueryFormat;
+use crate::stdin_input::MaybeStdin;
 
 /// A Gerrit CLI.
 #[derive(Debug, Clone, Parser)]
@@ -21,6 +31,49 @@ pub struct Opts {
     #[arg(long, default_value = "info", env = "GIT_GR_LOG")]
     pub log: String,
 
+    /// Also write JSON logs to this file, rotated daily.
+    ///
+    /// Logs include spans for queries, REST API requests, and subprocesses, for debugging long
+    /// restacks after the fact.
+    #[arg(long, env = "GIT_GR_LOG_FILE")]
+    pub log_file: Option<Utf8PathBuf>,
+
+    /// Log filter directives for `--log-file`.
+    #[arg(
+        long,
+        default_value = "info,git_gr=debug,command_error=debug",
+        env = "GIT_GR_LOG_FILE_FILTER"
+    )]
+    pub log_file_filter: String,
+
+    /// The Git remote for Gerrit.
+    ///
+    /// Defaults to the `git-gr.remote` Git config or the `remote` config file setting, or the
+    /// first remote which looks like a Gerrit server.
+    #[arg(long, env = "GIT_GR_REMOTE", global = true)]
+    pub remote: Option<String>,
+
+    /// How to print errors.
+    ///
+    /// With `json`, errors are written to stderr as a JSON object with `code`, `kind`,
+    /// `message`, `causes`, `hint`, and `changes` fields.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t,
+        env = "GIT_GR_OUTPUT",
+        global = true
+    )]
+    pub output: OutputFormat,
+
+    /// Don't make change numbers into clickable links, even if the terminal supports them.
+    #[arg(long, env = "GIT_GR_NO_HYPERLINKS", global = true)]
+    pub no_hyperlinks: bool,
+
+    /// Send requests to this server instead of Gerrit, like one started with `git-gr mock-server`.
+    #[arg(long, env = "GIT_GR_SERVER_OVERRIDE", global = true, hide = true)]
+    pub server_override: Option<String>,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -43,24 +96,198 @@ pub enum Command {
         /// Push and then restack changes that depend on the branch.
         #[arg(long)]
         restack: bool,
+
+        /// With `--restack`, restack changes owned by other people without asking for
+        /// confirmation.
+        #[arg(long, requires = "restack")]
+        include_others: bool,
+
+        /// Use a push template from Git config, setting the target branch, topic, reviewers,
+        /// hashtags, and WIP state.
+        ///
+        /// Templates are configured with keys like `git-gr.template.NAME.branch`, `.topic`,
+        /// `.reviewer`, `.hashtag`, and `.wip`. `{branch}` in the topic is replaced with the
+        /// checked-out branch's name. An explicit `TARGET` overrides the template's branch.
+        #[arg(long)]
+        template: Option<String>,
+
+        /// The change the pushed commits are built on, like `1234` or `1234/5` for a specific
+        /// patchset.
+        ///
+        /// Sets Gerrit's `base` push option to the change's commit, which relates new changes
+        /// to an open change they depend on. Defaults to the change's current patchset.
+        #[arg(long)]
+        base: Option<ChangeSpec>,
+    },
+    /// Create a change from a patch or mbox file.
+    ///
+    /// The patch is applied onto the target branch with `git am`, given a Change-Id if it
+    /// doesn't have one, and pushed for review.
+    Import {
+        /// The patch or mbox file to apply.
+        patch: Utf8PathBuf,
+
+        /// The branch to target the CL against.
+        ///
+        /// Defaults to the default upstream branch.
+        #[arg(long)]
+        target: Option<String>,
+    },
+    /// Choose a change from a query's results by typing to filter them, and check it out.
+    Pick {
+        /// Print the chosen change's number instead of checking it out.
+        #[arg(long)]
+        print: bool,
+
+        /// Show changes in all projects.
+        ///
+        /// By default, `project:PROJECT` is added to the query for the current repository's
+        /// project, unless the query already mentions a project.
+        #[arg(long)]
+        all_projects: bool,
+
+        /// Query to search for, or `@NAME` for a named query from the config file.
+        ///
+        /// Defaults to the same query as `git gr query`.
+        query: Option<String>,
     },
     /// Checkout a CL.
     Checkout {
-        /// The change number to checkout.
-        number: ChangeNumber,
+        /// The change to checkout, or `-` to read a change number from stdin.
+        ///
+        /// This may be a change number, a change number and patchset like `1234/5`, a
+        /// Change-Id, or a URL copied from the Gerrit web UI. A patchset in a URL is checked out
+        /// unless `--patchset` is given.
+        change: MaybeStdin<ChangeArg>,
         /// The patchset number to checkout, if any.
         ///
         /// Defaults to the latest patchset.
         #[arg(short, long)]
         patchset: Option<Patchset>,
+        /// Choose the patchset to checkout interactively.
+        #[arg(long, conflicts_with = "patchset")]
+        pick_patchset: bool,
+        /// Check that the latest patchset is checked out, bypassing the cache.
+        #[arg(long, conflicts_with_all = ["patchset", "pick_patchset"])]
+        latest: bool,
     },
     /// Fetch a CL.
     Fetch {
         /// The change number to fetch.
-        number: ChangeNumber,
+        #[arg(required_unless_present = "all_stack")]
+        number: Option<ChangeNumber>,
+
+        /// Fetch every change in the current stack to a local `refs/changes/*` ref, without
+        /// touching the working tree.
+        ///
+        /// Prints each change number and its ref, from the bottom of the stack up, for running
+        /// builds or tests across the stack.
+        #[arg(long, conflicts_with = "number")]
+        all_stack: bool,
+    },
+    /// Show the commits of every change in the stack, like `git log --oneline`.
+    ///
+    /// Fetches the current patchset of each change, and lists changes from the top of the stack
+    /// down.
+    Log {
+        /// A change in the stack to show.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        query: Option<String>,
+    },
+    /// Run a command on each change in the stack, in a temporary worktree.
+    ///
+    /// Checks out each change in dependency order, runs the command, and reports which changes
+    /// passed and failed. Your checkout isn't touched.
+    Foreach {
+        /// Run on the whole stack, including changes that depend on the `HEAD` commit's change.
+        ///
+        /// By default, only the changes up to and including `HEAD`'s change are checked.
+        #[arg(long)]
+        stack: bool,
+
+        /// The command to run, like `-- cargo build`.
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// Find the first change in the stack where a command fails, by binary search.
+    ///
+    /// Each change is checked out in a temporary worktree, so your checkout isn't touched.
+    Bisect {
+        /// Search the whole stack, including changes that depend on the `HEAD` commit's change.
+        ///
+        /// The stack must be a single chain. By default, only the changes up to and including
+        /// `HEAD`'s change are searched.
+        #[arg(long)]
+        stack: bool,
+
+        /// The command to run, like `-- cargo test`.
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// Post a message on each change in the stack describing its position.
+    ///
+    /// Messages look like `2/5 in stack, depends on #123, needed by #125`, so reviewers using
+    /// the web UI can see the intended order. Re-running only posts on changes whose position
+    /// has changed.
+    AnnotateChain {
+        /// Print the messages without posting them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show your open changes, changes awaiting your review, and your recently merged changes.
+    ///
+    /// Like the dashboard in the Gerrit web UI.
+    Dashboard {
+        /// Show full subjects, even if they don't fit in the terminal.
+        #[arg(long)]
+        full: bool,
+
+        /// Show the votes on each change, like `CR+2 V+1`.
+        #[arg(long)]
+        votes: bool,
+
+        /// Highlight changes which have waited for review without updates for this long, like
+        /// `36h`, `3d`, or `2w`.
+        #[arg(long, default_value_t, env = "GIT_GR_STALLED_AFTER")]
+        stalled_after: StalledAfter,
+
+        /// How to show the owners of changes.
+        #[arg(long, value_enum, default_value_t, env = "GIT_GR_OWNER_FORMAT")]
+        owner_format: OwnerFormat,
+
+        /// Show changes in all projects, rather than only the current repository's project.
+        #[arg(long)]
+        all_projects: bool,
+    },
+    /// Show every open stack owned by a person or team.
+    ///
+    /// Prints one tree per stack, with a count of how many changes in it are ready to submit.
+    Stacks {
+        /// A username or email whose stacks to show.
+        #[arg(long, default_value = "self")]
+        owner: String,
+
+        /// Treat `--owner` as a group, and show the stacks of all of its members.
+        #[arg(long)]
+        group: bool,
+
+        /// Show stacks in all projects, rather than only the current repository's project.
+        #[arg(long)]
+        all_projects: bool,
+
+        /// Show full subjects, even if they don't fit in the terminal.
+        #[arg(long)]
+        full: bool,
     },
     /// Rebase each CL in a stack, ensuring it's up-to-date with its parent.
     Restack {
+        /// Restack changes owned by other people without asking for confirmation.
+        ///
+        /// Restacking uploads new patchsets to every change in the stack.
+        #[arg(long)]
+        include_others: bool,
+
         #[command(subcommand)]
         command: Option<Restack>,
     },
@@ -91,16 +318,91 @@ pub enum Command {
 
         /// Show changes by others that need review.
         ///
-        /// Adds `is:open -owner:self -is:wip -is:reviewed` to the query.
+        /// Adds `is:open -owner:self -is:wip -is:reviewed` to the query. Changes where you're in
+        /// the attention set are listed first, then the changes which have waited the longest for
+        /// review, then the smallest changes.
         #[arg(long)]
         needs_review: bool,
 
-        /// Query to search for.
+        /// Show full subjects, even if they don't fit in the terminal.
+        #[arg(long)]
+        full: bool,
+
+        /// Show the number of inserted and deleted lines in each change.
+        #[arg(long)]
+        size: bool,
+
+        /// Show the age of each change.
+        #[arg(long)]
+        age: bool,
+
+        /// Show whether each change's latest patchset was a rework or a trivial rebase, so you
+        /// know if you need to read the code again.
+        #[arg(long)]
+        kind: bool,
+
+        /// Show the votes on each change, like `CR+2 V+1`.
+        #[arg(long)]
+        votes: bool,
+
+        /// Show your own votes on each change.
+        ///
+        /// If you haven't voted on the current patchset, your votes on the latest earlier
+        /// patchset are shown dimmed, like `CR+1 (PS2)`. Changes you haven't voted on are
+        /// blank.
+        #[arg(long)]
+        my_vote: bool,
+
+        /// Highlight changes which have waited for review without updates for this long, like
+        /// `36h`, `3d`, or `2w`.
+        #[arg(long, default_value_t, env = "GIT_GR_STALLED_AFTER")]
+        stalled_after: StalledAfter,
+
+        /// How to show the owners of changes.
+        #[arg(long, value_enum, default_value_t, env = "GIT_GR_OWNER_FORMAT")]
+        owner_format: OwnerFormat,
+
+        /// How to print the results.
+        #[arg(long, value_enum, default_value_t)]
+        format: QueryFormat,
+
+        /// Print each change with a template, like `{{ number }} {{ subject }}`.
+        ///
+        /// Templates use minijinja syntax, with the change fields printed by `show --json`.
+        #[arg(long, conflicts_with = "format")]
+        template: Option<String>,
+
+        /// Show changes in all projects.
+        ///
+        /// By default, `project:PROJECT` is added to the query for the current repository's
+        /// project, unless the query already mentions a project.
+        #[arg(long)]
+        all_projects: bool,
+
+        /// Show at most this many changes.
+        ///
+        /// By default, every change the server will return is shown.
+        #[arg(long, conflicts_with = "all")]
+        limit: Option<usize>,
+
+        /// Skip this many changes, to show later pages of results.
+        #[arg(long, default_value_t = 0)]
+        start: usize,
+
+        /// Fetch every page of results.
+        ///
+        /// Gerrit caps how many changes a single query returns; this keeps querying until there
+        /// are no more.
+        #[arg(long)]
+        all: bool,
+
+        /// Query to search for, `@NAME` for a named query from the config file, or `-` to read it
+        /// from stdin.
         ///
         /// Defaults to `status:open -is:wip`.
         ///
         /// See: https://gerrit.lix.systems/Documentation/user-search.html
-        query: Option<String>,
+        query: Option<MaybeStdin<String>>,
     },
     /// Run a `gerrit` command on the remote server.
     Cli {
@@ -121,6 +423,87 @@ pub enum Command {
         ///
         /// Defaults to the `HEAD` commit's change.
         query: Option<String>,
+
+        /// Show full subjects, even if they don't fit in the terminal.
+        #[arg(long)]
+        full: bool,
+
+        /// Show the age of each change, and highlight stalled changes.
+        #[arg(long)]
+        age: bool,
+
+        /// Highlight changes which have waited for review without updates for this long, like
+        /// `36h`, `3d`, or `2w`.
+        #[arg(long, default_value_t, env = "GIT_GR_STALLED_AFTER")]
+        stalled_after: StalledAfter,
+
+        /// Print each change with a template, like `{{ number }} {{ subject }}`, from the bottom
+        /// of the stack up.
+        ///
+        /// Templates use minijinja syntax, with the change fields printed by `show --json`.
+        #[arg(long, conflicts_with_all = ["full", "age"])]
+        template: Option<String>,
+    },
+    /// Set a local "review by" deadline for a change.
+    ///
+    /// Changes past their deadlines are marked as overdue in `query` output. Deadlines are
+    /// stored locally and never sent to Gerrit.
+    Remind {
+        /// The change to set a deadline for.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// When the review is due, like `friday`, `tomorrow`, `3d`, or `2024-05-01`.
+        #[arg(long, required_unless_present_any = ["clear", "list"])]
+        by: Option<String>,
+
+        /// Remove the change's deadline.
+        #[arg(long, conflicts_with = "by")]
+        clear: bool,
+
+        /// List all deadlines.
+        #[arg(long, conflicts_with_all = ["change", "by", "clear"])]
+        list: bool,
+    },
+    /// Keep private notes on changes.
+    ///
+    /// Notes are stored in `refs/notes/git-gr` and are never posted to Gerrit. They're shown in
+    /// `show` and `show-chain` output.
+    Note {
+        #[command(subcommand)]
+        command: Note,
+    },
+    /// Show details about a change, including its submit requirements.
+    Show {
+        /// The change to show.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        query: Option<String>,
+
+        /// Print the change as JSON, including its votes and submit requirements.
+        #[arg(long)]
+        json: bool,
+
+        /// Print the change with a template, like `{{ number }} {{ subject }}`.
+        ///
+        /// Templates use minijinja syntax, with the change fields printed by `--json`.
+        #[arg(long, conflicts_with = "json")]
+        template: Option<String>,
+    },
+    /// Summarize the `HEAD` commit's change: its status, votes, readiness, unresolved
+    /// comments, and whether `HEAD` is the latest patchset.
+    Status,
+    /// Show a chronological feed of messages, votes, and patchset uploads on a change.
+    Activity {
+        /// The change to show activity for.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        query: Option<String>,
+
+        /// Only show activity from this long ago, like `2w` or `36h`.
+        #[arg(long, value_parser = crate::age::parse_duration)]
+        since: Option<time::Duration>,
     },
     /// Open a change in a web browser.
     View {
@@ -128,7 +511,408 @@ pub enum Command {
         ///
         /// Defaults to the `HEAD` commit's change.
         query: Option<String>,
+
+        /// Open every open change in the change's chain, from the bottom of the stack up.
+        ///
+        /// Asks for confirmation before opening more than 5 tabs.
+        #[arg(long)]
+        chain: bool,
+
+        /// Print the URLs instead of opening them.
+        #[arg(long)]
+        print: bool,
+    },
+    /// List Gerrit groups and their members.
+    ///
+    /// Useful for figuring out why a reviewer can't vote on a change.
+    Groups {
+        /// A group to list the members of.
+        ///
+        /// If not given, all visible groups are listed.
+        group: Option<String>,
+
+        /// A username, email, or `self`.
+        ///
+        /// Without a group, lists the groups this user is a member of. With a group, checks if
+        /// this user is a member of the group.
+        #[arg(long)]
+        member: Option<String>,
+    },
+    /// Show the labels and submit requirements for the current project.
+    ///
+    /// Lists each label's values and who can vote on it, to help figure out what approvals a
+    /// change needs.
+    Labels,
+    /// Export a change's full history to a directory.
+    ///
+    /// Writes every patchset as a patch file, along with all messages, inline comments, and
+    /// votes as JSON and Markdown.
+    Archive {
+        /// The change to archive.
+        query: String,
+        /// The directory to write to.
+        dir: Utf8PathBuf,
+    },
+    /// Modify a change on the server without checking it out.
+    ///
+    /// Changes are staged in a change edit until they're published as a new patchset.
+    Edit {
+        /// The change to edit.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(short, long)]
+        change: Option<String>,
+
+        #[command(subcommand)]
+        command: Edit,
+    },
+    /// Vote on and/or comment on changes.
+    ///
+    /// With `--query`, reviews every matching change at once, after listing them and asking for
+    /// confirmation. This is useful for approving a series of mechanical changes or re-triggering
+    /// CI across a topic.
+    Review {
+        /// The change to review, or `-` to read change numbers from stdin, one per line.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(conflicts_with = "query")]
+        change: Option<MaybeStdin<String>>,
+
+        /// Review every change matching this query.
+        ///
+        /// The query is limited to the current project unless it includes a `project:` operator
+        /// or `--all-projects` is given.
+        #[arg(short, long)]
+        query: Option<String>,
+
+        /// Review changes matching `--query` in all projects, rather than only the current
+        /// repository's project.
+        #[arg(long, requires = "query")]
+        all_projects: bool,
+
+        /// Votes to apply, like `Code-Review+1` or `Verified=-1`.
+        #[arg(
+            short,
+            long = "label",
+            required_unless_present_any = ["message", "code_review", "verified"]
+        )]
+        labels: Vec<LabelVote>,
+
+        /// Vote on `Code-Review`, like `+2` or `-1`.
+        #[arg(long, allow_hyphen_values = true)]
+        code_review: Option<i32>,
+
+        /// Vote on `Verified`, like `+1` or `-1`.
+        #[arg(long, allow_hyphen_values = true)]
+        verified: Option<i32>,
+
+        /// A message to post on the changes.
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Don't ask for confirmation before reviewing multiple changes.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Show the inline comments on a change, grouped by file and line.
+    #[command(args_conflicts_with_subcommands = true)]
+    Comments {
+        #[command(subcommand)]
+        command: Option<Comments>,
+
+        /// The change to show comments for.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// Only show unresolved comment threads.
+        #[arg(short, long)]
+        unresolved: bool,
+    },
+    /// Write draft inline comments, and publish them with a vote.
+    Drafts {
+        #[command(subcommand)]
+        command: Drafts,
+    },
+    /// Post a message on a change.
+    Comment {
+        /// The change to comment on.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// The message to post. If not given, an editor is opened to write one.
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Open an editor to write the message, starting with `--message` if given.
+        #[arg(short, long)]
+        edit: bool,
+    },
+    /// Rebase a change on the server, without fetching or pushing it.
+    ///
+    /// Much faster than a local restack for changes which rebase cleanly. Fails without changing
+    /// anything if the rebase has conflicts.
+    Rebase {
+        /// The change to rebase.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// A change number, commit, or ref to rebase onto.
+        ///
+        /// Defaults to the latest patchset of the change this one depends on, or the tip of the
+        /// target branch.
+        #[arg(long)]
+        base: Option<String>,
+    },
+    /// Copy a change to another branch, like a release branch, with Gerrit's cherry-pick.
+    ///
+    /// Prints the new change's number and URL.
+    CherryPickTo {
+        /// The branch to cherry-pick onto.
+        branch: String,
+
+        /// The change to cherry-pick.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(short, long)]
+        change: Option<String>,
+
+        /// The commit message for the new change.
+        ///
+        /// Defaults to the original change's commit message.
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Add the original change's reviewers to the new change.
+        #[arg(long)]
+        keep_reviewers: bool,
+    },
+    /// Abandon a change.
+    Abandon {
+        /// The change to abandon.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// A message explaining why the change is abandoned.
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// Restore an abandoned change.
+    Restore {
+        /// The change to restore.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// A message explaining why the change is restored.
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// Mark a change as work-in-progress.
+    Wip {
+        /// The change to mark as work-in-progress.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// A message to post on the change.
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// Mark a work-in-progress change as ready for review.
+    Ready {
+        /// The change to mark as ready for review.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// A message to post on the change.
+        #[arg(short, long)]
+        message: Option<String>,
     },
+    /// Abandon open changes which haven't been updated in a while.
+    Cleanup {
+        /// Abandon changes which haven't been updated in this long, like `90d` or `12w`.
+        #[arg(long, default_value = "90d")]
+        older_than: String,
+
+        /// Only abandon changes owned by this user.
+        #[arg(long, default_value = "self")]
+        owner: String,
+
+        /// The message to post on abandoned changes.
+        ///
+        /// `{older_than}` is replaced with the `--older-than` duration.
+        #[arg(short, long, default_value = crate::cleanup::DEFAULT_CLEANUP_MESSAGE)]
+        message: String,
+
+        /// List the changes which would be abandoned without abandoning them.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation before abandoning changes.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Resolve a Change-Id to its change number, patchset, and local branch.
+    ///
+    /// Uses a local index updated on every fetch and push, so this works offline and keeps
+    /// track of branches after they're renamed. Prints `NUMBER/PATCHSET`, followed by the
+    /// branch name if one is known.
+    Resolve {
+        /// The Change-Id to resolve.
+        change_id: ChangeId,
+    },
+    /// Show, set, or clear a change's topic.
+    Topic {
+        /// The change to update.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(short, long)]
+        change: Option<String>,
+
+        /// Show or update every open change in the change's chain.
+        #[arg(long, global = true)]
+        chain: bool,
+
+        /// Defaults to `get`.
+        #[command(subcommand)]
+        command: Option<Topic>,
+    },
+    /// List, add, or remove hashtags on a change.
+    Hashtag {
+        /// The change to update.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(short, long)]
+        change: Option<String>,
+
+        /// Update every open change in the change's chain.
+        #[arg(long, global = true)]
+        chain: bool,
+
+        #[command(subcommand)]
+        command: Hashtag,
+    },
+    /// List, add, or remove accounts in a change's attention set.
+    ///
+    /// The attention set tracks whose turn it is to act on a change.
+    Attention {
+        /// The change to update.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(short, long)]
+        change: Option<String>,
+
+        /// Update every open change in the change's chain.
+        #[arg(long, global = true)]
+        chain: bool,
+
+        #[command(subcommand)]
+        command: Attention,
+    },
+    /// List, add, or remove reviewers and CCs on a change.
+    Reviewers {
+        /// The change to update.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(short, long)]
+        change: Option<String>,
+
+        /// Update every open change in the change's chain.
+        #[arg(long, global = true)]
+        chain: bool,
+
+        #[command(subcommand)]
+        command: Reviewers,
+    },
+    /// Update local branches to the latest patchsets of their changes.
+    ///
+    /// Branches are matched to changes by the `Change-Id` of the commit they point to. Branches
+    /// with unpushed commits are left alone.
+    Sync {
+        /// Instead, send actions queued while Gerrit was unreachable, in order.
+        ///
+        /// Actions are queued when Gerrit can't be reached, or when `GIT_GR_OFFLINE` is set.
+        /// Without this flag, queued actions are listed but not sent. Local branches aren't
+        /// updated; run `git gr sync` again afterwards to update them.
+        #[arg(long)]
+        flush: bool,
+
+        /// Reset branches which have diverged from Gerrit without asking.
+        #[arg(short, long, conflicts_with = "flush")]
+        yes: bool,
+    },
+    /// Delete local branches and `refs/changes/*` refs for merged and abandoned changes.
+    ///
+    /// Branches are matched to changes by the `Change-Id` of the commit they point to. Only
+    /// branches with no upstream, or whose tip isn't on a remote yet, are considered.
+    Prune {
+        /// Show what would be deleted without deleting anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation before deleting refs.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Run a mock Gerrit server for testing, printing its URL.
+    #[command(hide = true)]
+    MockServer {
+        /// A JSON file of changes and REST API responses to serve.
+        fixture: Utf8PathBuf,
+
+        /// The port to listen on. Defaults to a random port.
+        #[arg(long, default_value_t = 0)]
+        port: u16,
+    },
+    /// Send a `gerrit` command to a mock server; used in place of `ssh`.
+    #[command(hide = true)]
+    MockSsh {
+        /// The mock server's URL.
+        server: String,
+
+        /// The destination and remote command, like `ssh` takes.
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Run an SSH command and record its output to a directory; used in place of `ssh`.
+    ///
+    /// Set `GIT_GR_RECORD` to a directory to record all SSH and HTTP interactions.
+    #[command(hide = true)]
+    RecordSsh {
+        /// The directory to record to.
+        dir: Utf8PathBuf,
+
+        /// The SSH command to run.
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Print the recorded output of an SSH command; used in place of `ssh`.
+    ///
+    /// Set `GIT_GR_REPLAY` to a directory to replay all SSH and HTTP interactions.
+    #[command(hide = true)]
+    ReplaySsh {
+        /// The directory to replay from.
+        dir: Utf8PathBuf,
+
+        /// The SSH command to replay.
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Set up `git-gr` for this repository.
+    ///
+    /// Finds or adds the Gerrit remote, checks SSH and REST API access, installs the
+    /// `commit-msg` hook, and records the remote in the `git-gr.remote` Git config.
+    Init,
+    /// Check the Gerrit setup for common problems.
+    ///
+    /// Checks the Gerrit remote, SSH and REST API access, the `commit-msg` hook, the cache, and
+    /// pending restacks and pushes, and suggests fixes for any failures.
+    Doctor,
     /// Clear the cache of changes and API responses.
     ClearCache,
 }
@@ -142,7 +926,42 @@ pub enum Restack {
     /// Abort an in-progress restack.
     Abort,
     /// Push changes from a completed restack.
-    Push,
+    ///
+    /// Prints a Markdown summary of the stack, with links, subjects, and old and new patchset
+    /// numbers, for pasting into chat or a tracking issue.
+    Push {
+        /// Also copy the summary to the clipboard, using the terminal's OSC 52 support.
+        #[arg(long)]
+        copy: bool,
+
+        /// Whether to push new patchsets to work-in-progress and private changes.
+        ///
+        /// Skipped changes are left in the push todo, so they can be pushed later. Defaults to the
+        /// `restack.push-wip` setting, or `prompt`.
+        #[arg(long, value_enum)]
+        wip: Option<PushWip>,
+
+        /// Only push changes with a vote like `Code-Review+2` on their current patchset.
+        ///
+        /// Positive votes are satisfied by higher votes. May be given multiple times to require
+        /// several votes. Other changes (and the changes which depend on them) are left in the
+        /// push todo, so they can be updated first.
+        #[arg(long)]
+        require_label: Vec<LabelVote>,
+
+        /// After pushing each change, post a message about the new patchset and add the change's
+        /// reviewers back to the attention set.
+        ///
+        /// The message can be customized with the `restack.review-message` setting. Defaults to
+        /// the `restack.rerequest-review` setting, or off.
+        #[arg(long, overrides_with = "no_rerequest_review")]
+        rerequest_review: bool,
+
+        /// Don't post a message or update the attention set after pushing, even if the
+        /// `restack.rerequest-review` setting is enabled.
+        #[arg(long, overrides_with = "rerequest_review")]
+        no_rerequest_review: bool,
+    },
     /// Write `git-rebase-todo`.
     #[command(hide = true)]
     WriteTodo {
@@ -152,6 +971,111 @@ pub enum Restack {
     },
 }
 
+#[derive(Debug, Clone, Subcommand)]
+pub enum Edit {
+    /// Create or overwrite a file in the change edit.
+    Put {
+        /// The path of the file in the repository.
+        path: String,
+        /// A local file to read the new contents from.
+        ///
+        /// Defaults to reading from stdin.
+        source: Option<Utf8PathBuf>,
+    },
+    /// Delete a file in the change edit.
+    Delete {
+        /// The path of the file in the repository.
+        path: String,
+    },
+    /// Publish the change edit as a new patchset.
+    Publish,
+    /// Rebase the change edit on the latest patchset.
+    Rebase,
+    /// Throw away the change edit.
+    Discard,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Topic {
+    /// Show the topic.
+    Get,
+    /// Set the topic.
+    Set {
+        /// The topic name.
+        name: String,
+    },
+    /// Remove the topic.
+    Clear,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Hashtag {
+    /// List the hashtags.
+    ///
+    /// Prints one hashtag per line; with `--chain`, prints each change's hashtags on one line.
+    List,
+    /// Add hashtags.
+    Add {
+        /// The hashtags to add, without a leading `#`.
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove hashtags.
+    Remove {
+        /// The hashtags to remove, without a leading `#`.
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Attention {
+    /// List the accounts in the attention set, and why they were added.
+    List,
+    /// Add accounts to the attention set.
+    Add {
+        /// Usernames, emails, or `self`.
+        #[arg(required = true)]
+        users: Vec<String>,
+
+        /// Why the accounts are being added.
+        #[arg(short, long, default_value = "Requested with git-gr")]
+        reason: String,
+    },
+    /// Remove accounts from the attention set.
+    Remove {
+        /// Usernames, emails, or `self`.
+        #[arg(required = true)]
+        users: Vec<String>,
+
+        /// Why the accounts are being removed.
+        #[arg(short, long, default_value = "Removed with git-gr")]
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Reviewers {
+    /// List the reviewers and CCs, with their votes.
+    List,
+    /// Add reviewers or CCs.
+    Add {
+        /// Usernames, emails, or group names to add.
+        #[arg(required = true)]
+        reviewers: Vec<String>,
+
+        /// CC the accounts instead of adding them as reviewers.
+        #[arg(long)]
+        cc: bool,
+    },
+    /// Remove reviewers or CCs.
+    Remove {
+        /// Usernames or emails to remove.
+        #[arg(required = true)]
+        reviewers: Vec<String>,
+    },
+}
+
 #[derive(Debug, Clone, Args)]
 pub struct RestackContinue {
     /// If you ran `git rebase --continue` on your own and then checked something else out,
@@ -166,4 +1090,149 @@ pub struct RestackContinue {
     /// may have made.
     #[arg(long)]
     pub restart_in_progress: bool,
+
+    /// Keep going if later steps have conflicts, instead of stopping at the first one.
+    ///
+    /// Conflicting changes (and the changes which depend on them) are skipped, and listed at
+    /// the end so they can be restacked by hand.
+    #[arg(long)]
+    pub all_remaining: bool,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Note {
+    /// Add a paragraph to a change's note.
+    Add {
+        /// The text to add.
+        message: String,
+
+        /// The change to add a note to.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(long)]
+        change: Option<String>,
+    },
+    /// Show a change's note.
+    Show {
+        /// The change to show the note for.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+    },
+    /// Delete a change's note.
+    Remove {
+        /// The change to delete the note for.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Comments {
+    /// Reply to an inline comment thread.
+    Reply {
+        /// The thread to reply to, as `PATH:LINE`, `PATH` for comments on a whole file, or the ID
+        /// of a comment in the thread.
+        thread: String,
+
+        /// The change the thread is on.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(long)]
+        change: Option<String>,
+
+        /// The reply. If not given, an editor is opened to write one.
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Mark the thread as resolved.
+        #[arg(long)]
+        resolve: bool,
+
+        /// Mark the thread as unresolved.
+        #[arg(long, conflicts_with = "resolve")]
+        unresolve: bool,
+    },
+    /// Mark comment threads as resolved.
+    Resolve(ResolveThreads),
+    /// Mark comment threads as unresolved.
+    Unresolve(ResolveThreads),
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Drafts {
+    /// Create a draft inline comment on the current patchset.
+    Add {
+        /// Where to comment, as `PATH:LINE`, or `PATH` for a comment on the whole file.
+        location: String,
+
+        /// The change to comment on.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        #[arg(long)]
+        change: Option<String>,
+
+        /// The comment. If not given, an editor is opened to write one.
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Leave the comment unresolved, so it needs to be addressed.
+        #[arg(short, long)]
+        unresolved: bool,
+    },
+    /// List your draft comments on a change.
+    List {
+        /// The change to list drafts on.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+    },
+    /// Publish your draft comments on a change, optionally with votes and a message.
+    Publish {
+        /// The change to publish drafts on.
+        ///
+        /// Defaults to the `HEAD` commit's change.
+        change: Option<String>,
+
+        /// Votes to apply, like `Code-Review+1` or `Verified=-1`.
+        #[arg(short, long = "label")]
+        labels: Vec<LabelVote>,
+
+        /// Vote on `Code-Review`, like `+2` or `-1`.
+        #[arg(long, allow_hyphen_values = true)]
+        code_review: Option<i32>,
+
+        /// Vote on `Verified`, like `+1` or `-1`.
+        #[arg(long, allow_hyphen_values = true)]
+        verified: Option<i32>,
+
+        /// A message to post on the change.
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ResolveThreads {
+    /// The thread to update, as `PATH:LINE`, `PATH` for comments on a whole file, or the ID of a
+    /// comment in the thread.
+    #[arg(required_unless_present = "all")]
+    pub thread: Option<String>,
+
+    /// Update every comment thread on the change.
+    #[arg(long, conflicts_with = "thread")]
+    pub all: bool,
+
+    /// The change the threads are on.
+    ///
+    /// Defaults to the `HEAD` commit's change.
+    #[arg(long)]
+    pub change: Option<String>,
+
+    /// The reply to post on each thread.
+    ///
+    /// Defaults to `Done` when resolving and `Reopened` when unresolving.
+    #[arg(short, long)]
+    pub message: Option<String>,
 }
@@ -0,0 +1,71 @@
+use fs_err as fs;
+use miette::IntoDiagnostic;
+
+use crate::change::Change;
+use crate::git::Git;
+
+/// Open the user's editor to compose a message on a change.
+///
+/// `initial` is used as the starting text. Returns `None` if the message is left empty.
+pub fn compose_message(
+    git: &Git,
+    change: &Change,
+    initial: Option<&str>,
+) -> miette::Result<Option<String>> {
+    let path = git.git_path("GIT_GR_COMMENT_EDITMSG")?;
+    fs::write(
+        &path,
+        format!(
+            "{}\n# Commenting on {}: {}\n#\n# Lines starting with '#' will be ignored, and an empty message aborts.\n",
+            initial.unwrap_or_default(),
+            change.number,
+            change.subject.as_deref().unwrap_or_default(),
+        ),
+    )
+    .into_diagnostic()?;
+
+    git.edit_file(&path)?;
+
+    let message = strip_comments(&fs::read_to_string(&path).into_diagnostic()?);
+    Ok(if message.is_empty() {
+        None
+    } else {
+        Some(message)
+    })
+}
+
+/// Remove `#` comment lines and surrounding whitespace from an edited message.
+fn strip_comments(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(
+            strip_comments(indoc!(
+                "
+
+                Looks good!
+
+                Just one question.
+                # Commenting on 123: Add a mock Gerrit server
+                #
+                "
+            )),
+            "Looks good!\n\nJust one question."
+        );
+        assert_eq!(strip_comments("# Only comments\n\n"), "");
+    }
+}
@@ -0,0 +1,25 @@
+use crate::account_info::AccountInfo;
+use crate::patchset::Patchset;
+
+/// An inline comment on a change, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#comment-info>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct CommentInfo {
+    pub id: String,
+    /// The file path. Unset when comments are returned as a map keyed by path.
+    pub path: Option<String>,
+    /// The patchset the comment was made on.
+    pub patch_set: Option<Patchset>,
+    /// The line the comment is on. If unset, the comment is on the whole file.
+    pub line: Option<u32>,
+    /// The ID of the comment this comment is a reply to.
+    pub in_reply_to: Option<String>,
+    pub message: Option<String>,
+    /// When the comment was last updated, like `2013-02-01 09:59:32.126000000`.
+    pub updated: String,
+    pub author: Option<AccountInfo>,
+    /// Is this comment thread unresolved?
+    pub unresolved: Option<bool>,
+}
@@ -0,0 +1,17 @@
+/// A new inline comment, or a change to a draft comment.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#comment-input>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct CommentInput {
+    pub path: String,
+    /// The line to comment on. If unset, the comment is on the whole file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// The ID of the comment this comment is a reply to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<String>,
+    pub message: String,
+    /// Should this comment leave the thread unresolved?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unresolved: Option<bool>,
+}
@@ -0,0 +1,443 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use miette::miette;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+
+use crate::change_number::ChangeNumber;
+use crate::cli::ResolveThreads;
+use crate::comment_info::CommentInfo;
+use crate::comment_input::CommentInput;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+use crate::format_bulleted_list;
+use crate::gerrit::Gerrit;
+use crate::git::Git;
+
+/// An inline comment and its replies.
+#[derive(Debug, Clone)]
+pub struct CommentThread {
+    /// The comments in the thread, oldest first.
+    pub comments: Vec<CommentInfo>,
+}
+
+impl CommentThread {
+    fn root(&self) -> &CommentInfo {
+        &self.comments[0]
+    }
+
+    fn latest(&self) -> &CommentInfo {
+        self.comments
+            .last()
+            .expect("Threads have at least one comment")
+    }
+
+    /// A short description of the thread, like `src/main.rs:12 (abc123)`.
+    fn describe(&self) -> String {
+        let root = self.root();
+        format!(
+            "{}{} ({})",
+            root.path.as_deref().unwrap_or_default(),
+            root.line.map(|line| format!(":{line}")).unwrap_or_default(),
+            root.id
+        )
+    }
+
+    /// A thread is resolved if its latest comment resolved it.
+    pub fn is_resolved(&self) -> bool {
+        self.comments
+            .last()
+            .map(|comment| comment.unresolved != Some(true))
+            .unwrap_or(true)
+    }
+}
+
+/// Group a file's comments into threads, ordered by line.
+pub fn comment_threads(comments: &[CommentInfo]) -> Vec<CommentThread> {
+    let parents = comments
+        .iter()
+        .filter_map(|comment| {
+            comment
+                .in_reply_to
+                .as_deref()
+                .map(|parent| (comment.id.as_str(), parent))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let root_of = |id: &str| {
+        let mut id = id.to_owned();
+        // Bounded, in case of a cycle.
+        for _ in 0..comments.len() {
+            match parents.get(id.as_str()) {
+                Some(parent) => id = (*parent).to_owned(),
+                None => break,
+            }
+        }
+        id
+    };
+
+    let mut threads = BTreeMap::<String, Vec<CommentInfo>>::new();
+    for comment in comments {
+        threads
+            .entry(root_of(&comment.id))
+            .or_default()
+            .push(comment.clone());
+    }
+
+    let mut threads = threads
+        .into_values()
+        .map(|mut comments| {
+            // Timestamps are formatted so that they sort chronologically.
+            comments.sort_by(|a, b| a.updated.cmp(&b.updated));
+            CommentThread { comments }
+        })
+        .collect::<Vec<_>>();
+    threads.sort_by(|a, b| {
+        (a.root().line, &a.root().updated).cmp(&(b.root().line, &b.root().updated))
+    });
+    threads
+}
+
+/// Format inline comments grouped by file and line, with resolved threads dimmed.
+///
+/// If `unresolved_only` is set, resolved threads are skipped.
+pub fn format_comment_threads(
+    comments: &BTreeMap<String, Vec<CommentInfo>>,
+    unresolved_only: bool,
+) -> String {
+    let mut output = String::new();
+    for (path, comments) in comments {
+        let threads = comment_threads(comments)
+            .into_iter()
+            .filter(|thread| !unresolved_only || !thread.is_resolved())
+            .collect::<Vec<_>>();
+        if threads.is_empty() {
+            continue;
+        }
+
+        let path = match path.as_str() {
+            "/PATCHSET_LEVEL" => "Patchset comments",
+            "/COMMIT_MSG" => "Commit message",
+            path => path,
+        };
+        let _ = writeln!(
+            output,
+            "{}",
+            path.if_supports_color(Stdout, |path| path.bold())
+        );
+
+        for thread in threads {
+            let root = thread.root();
+            let mut thread_output = String::new();
+            let _ = writeln!(
+                thread_output,
+                "  {}, patchset {}{} [{}]:",
+                root.line
+                    .map(|line| format!("Line {line}"))
+                    .unwrap_or_else(|| "File".to_owned()),
+                root.patch_set
+                    .map(|patchset| patchset.to_string())
+                    .unwrap_or_else(|| "?".to_owned()),
+                if thread.is_resolved() {
+                    " (resolved)"
+                } else {
+                    ""
+                },
+                root.id,
+            );
+            for comment in &thread.comments {
+                let author = comment
+                    .author
+                    .as_ref()
+                    .map(|author| author.to_string())
+                    .unwrap_or_else(|| "Gerrit".to_owned());
+                let mut lines = comment.message.as_deref().unwrap_or_default().lines();
+                let _ = writeln!(
+                    thread_output,
+                    "    {author}: {}",
+                    lines.next().unwrap_or_default()
+                );
+                for line in lines {
+                    let _ = writeln!(thread_output, "      {line}");
+                }
+            }
+
+            if thread.is_resolved() {
+                let _ = write!(
+                    output,
+                    "{}",
+                    thread_output.if_supports_color(Stdout, |thread| thread.dimmed())
+                );
+            } else {
+                output.push_str(&thread_output);
+            }
+        }
+    }
+    output
+}
+
+/// Group a change's comments, keyed by path, into threads.
+///
+/// Unlike the comments Gerrit returns, each comment has its `path` filled in.
+pub fn all_threads(comments: &BTreeMap<String, Vec<CommentInfo>>) -> Vec<CommentThread> {
+    comments
+        .iter()
+        .flat_map(|(path, comments)| {
+            comment_threads(comments).into_iter().map(|mut thread| {
+                for comment in &mut thread.comments {
+                    comment.path.get_or_insert_with(|| path.clone());
+                }
+                thread
+            })
+        })
+        .collect()
+}
+
+/// Find a comment thread by `PATH:LINE`, `PATH` (for file comments), or the ID of any comment in
+/// the thread.
+///
+/// If several threads are on the same line, the unresolved one is picked; it's an error if
+/// that's still ambiguous.
+pub fn find_thread(
+    comments: &BTreeMap<String, Vec<CommentInfo>>,
+    target: &str,
+) -> miette::Result<CommentThread> {
+    let threads = all_threads(comments);
+
+    if let Some(thread) = threads
+        .iter()
+        .find(|thread| thread.comments.iter().any(|comment| comment.id == target))
+    {
+        return Ok(thread.clone());
+    }
+
+    let (path, line) = parse_location(target);
+    let mut matching = threads
+        .into_iter()
+        .filter(|thread| thread.root().path.as_deref() == Some(path) && thread.root().line == line)
+        .collect::<Vec<_>>();
+    if matching.len() > 1 {
+        matching.retain(|thread| !thread.is_resolved());
+    }
+
+    match matching.len() {
+        1 => Ok(matching.pop().expect("Length was checked")),
+        0 => Err(miette!("No comment thread found for `{target}`").classify(ErrorKind::NotFound)),
+        _ => Err(miette!(
+            help = "Reply to a thread by its ID instead",
+            "Found {} unresolved comment threads for `{target}`:\n{}",
+            matching.len(),
+            format_bulleted_list(matching.iter().map(|thread| thread.describe()))
+        )),
+    }
+}
+
+/// Parse a location like `PATH:LINE`, or `PATH` for the whole file.
+pub fn parse_location(location: &str) -> (&str, Option<u32>) {
+    match location.rsplit_once(':') {
+        Some((path, line)) => match line.parse::<u32>() {
+            Ok(line) => (path, Some(line)),
+            Err(_) => (location, None),
+        },
+        None => (location, None),
+    }
+}
+
+/// Reply to a comment thread, by creating a draft comment and publishing it.
+///
+/// If `unresolved` is `None`, the thread stays resolved or unresolved.
+pub fn reply_to_thread(
+    gerrit: &mut Gerrit,
+    change: ChangeNumber,
+    thread: &CommentThread,
+    message: String,
+    unresolved: Option<bool>,
+) -> miette::Result<()> {
+    draft_reply(gerrit, change, thread, message, unresolved)?;
+    gerrit.publish_drafts(change)?;
+    tracing::info!("Replied to {}", thread.describe());
+    Ok(())
+}
+
+/// Run `git-gr comments resolve` or `git-gr comments unresolve`.
+pub fn resolve_threads(
+    git: &Git,
+    gerrit: &mut Gerrit,
+    args: ResolveThreads,
+    resolved: bool,
+) -> miette::Result<()> {
+    let change = git.change_key_or_head(args.change)?;
+    let change = gerrit.get_change(change)?.number;
+    let comments = gerrit.comments(change)?;
+    let threads = match args.thread {
+        Some(thread) => vec![find_thread(&comments, &thread)?],
+        None => all_threads(&comments),
+    };
+    let message = args
+        .message
+        .unwrap_or_else(|| if resolved { "Done" } else { "Reopened" }.to_owned());
+    set_resolved(gerrit, change, &threads, resolved, &message)
+}
+
+/// Mark comment threads as resolved or unresolved by replying to each of them, publishing the
+/// replies together.
+///
+/// Threads which are already resolved or unresolved are skipped.
+pub fn set_resolved(
+    gerrit: &mut Gerrit,
+    change: ChangeNumber,
+    threads: &[CommentThread],
+    resolved: bool,
+    message: &str,
+) -> miette::Result<()> {
+    let threads = threads
+        .iter()
+        .filter(|thread| thread.is_resolved() != resolved)
+        .collect::<Vec<_>>();
+    let state = if resolved { "resolved" } else { "unresolved" };
+    if threads.is_empty() {
+        tracing::info!("No comment threads to mark as {state}");
+        return Ok(());
+    }
+
+    for thread in &threads {
+        draft_reply(gerrit, change, thread, message.to_owned(), Some(!resolved))?;
+    }
+    gerrit.publish_drafts(change)?;
+    tracing::info!(
+        "Marked as {state}:\n{}",
+        format_bulleted_list(threads.iter().map(|thread| thread.describe()))
+    );
+    Ok(())
+}
+
+/// Create a draft reply to a comment thread.
+fn draft_reply(
+    gerrit: &mut Gerrit,
+    change: ChangeNumber,
+    thread: &CommentThread,
+    message: String,
+    unresolved: Option<bool>,
+) -> miette::Result<()> {
+    let root = thread.root();
+    let latest = thread.latest();
+    let patchset = root
+        .patch_set
+        .ok_or_else(|| miette!("Comment {} doesn't have a patchset", root.id))?;
+    gerrit.create_draft(
+        change,
+        patchset,
+        &CommentInput {
+            path: root
+                .path
+                .clone()
+                .ok_or_else(|| miette!("Comment {} doesn't have a path", root.id))?,
+            line: root.line,
+            in_reply_to: Some(latest.id.clone()),
+            message,
+            unresolved: Some(unresolved.unwrap_or(!thread.is_resolved())),
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_comment_threads() {
+        let comments = serde_json::from_str::<BTreeMap<String, Vec<CommentInfo>>>(indoc!(
+            r#"
+            {
+                "src/main.rs": [
+                    {
+                        "id": "b",
+                        "patch_set": 2,
+                        "line": 12,
+                        "in_reply_to": "a",
+                        "message": "Because.",
+                        "updated": "2024-04-02 10:00:00.000000000",
+                        "author": { "username": "rbt" },
+                        "unresolved": false
+                    },
+                    {
+                        "id": "a",
+                        "patch_set": 2,
+                        "line": 12,
+                        "message": "Why?",
+                        "updated": "2024-04-01 10:00:00.000000000",
+                        "author": { "username": "jade" },
+                        "unresolved": true
+                    },
+                    {
+                        "id": "c",
+                        "patch_set": 1,
+                        "line": 3,
+                        "message": "Typo.\nAlso this.",
+                        "updated": "2024-04-03 10:00:00.000000000",
+                        "author": { "username": "jade" },
+                        "unresolved": true
+                    }
+                ],
+                "/PATCHSET_LEVEL": [
+                    {
+                        "id": "d",
+                        "patch_set": 2,
+                        "message": "Nice!",
+                        "updated": "2024-04-01 10:00:00.000000000",
+                        "author": { "username": "jade" }
+                    }
+                ]
+            }
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(
+            format_comment_threads(&comments, false),
+            indoc!(
+                "
+                Patchset comments
+                  File, patchset 2 (resolved) [d]:
+                    jade: Nice!
+                src/main.rs
+                  Line 3, patchset 1 [c]:
+                    jade: Typo.
+                      Also this.
+                  Line 12, patchset 2 (resolved) [a]:
+                    jade: Why?
+                    rbt: Because.
+                "
+            )
+        );
+
+        assert_eq!(
+            format_comment_threads(&comments, true),
+            indoc!(
+                "
+                src/main.rs
+                  Line 3, patchset 1 [c]:
+                    jade: Typo.
+                      Also this.
+                "
+            )
+        );
+
+        let thread_ids = |target: &str| {
+            find_thread(&comments, target).map(|thread| {
+                thread
+                    .comments
+                    .into_iter()
+                    .map(|comment| comment.id)
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(thread_ids("src/main.rs:12").unwrap(), vec!["a", "b"]);
+        assert_eq!(thread_ids("b").unwrap(), vec!["a", "b"]);
+        assert_eq!(thread_ids("src/main.rs:3").unwrap(), vec!["c"]);
+        assert!(thread_ids("src/main.rs:4").is_err());
+    }
+}
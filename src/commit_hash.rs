@@ -23,7 +23,24 @@ pub struct CommitHash(String);
 
 impl CommitHash {
     /// Get an abbreviated 8-character Git hash.
+    ///
+    /// Hashes which are already shorter than that are returned as-is.
     pub fn abbrev(&self) -> &str {
-        &self.0[..8]
+        self.0.get(..8).unwrap_or(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_abbrev() {
+        assert_eq!(
+            CommitHash::new("0123456789abcdef".to_owned()).abbrev(),
+            "01234567"
+        );
+        assert_eq!(CommitHash::new("0123".to_owned()).abbrev(), "0123");
     }
 }
@@ -0,0 +1,542 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use camino::Utf8PathBuf;
+use clap::ValueEnum;
+use fs_err as fs;
+use miette::miette;
+use miette::Context;
+use miette::IntoDiagnostic;
+
+use crate::age::parse_duration;
+use crate::cache::CACHE_LIFESPAN;
+use crate::change_number::ChangeNumber;
+use crate::date_format::parse_format;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+use crate::format_bulleted_list;
+use crate::git::Git;
+use crate::push_wip::PushWip;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The query for `git gr query` and `git gr pick`, if the `default-query` setting isn't set.
+const DEFAULT_QUERY: &str = "status:open -is:wip";
+
+/// How long to wait for Gerrit to respond, if the `timeout` setting isn't set.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Defaults read from `~/.config/git-gr/config.toml`, the repository's `.git-gr.toml`, and
+/// `git-gr.*` Git config keys.
+///
+/// Later sources take precedence, so teams can share settings in `.git-gr.toml` and users can
+/// override them per-clone with `git config`. Environment variables take precedence over all of
+/// them.
+///
+/// ```toml
+/// remote = "gerrit"
+/// default-query = "status:open owner:self"
+/// 24-hour-time = true
+/// date-format = "[day].[month]."
+/// full-date-format = "[day].[month].[year]"
+/// timezone = "Europe/Berlin"
+/// change-url = "https://review.example.com/c/{project}/+/{number}"
+/// cache-ttl = "30m"
+/// timeout = "90s"
+/// query-deadline = "45s"
+///
+/// [queries]
+/// incoming = "is:open reviewer:self -owner:self"
+///
+/// [restack]
+/// push = true
+/// hashtag = "git-gr-restacking"
+/// push-wip = "skip"
+/// rerequest-review = true
+/// review-message = "Rebased onto {{ branch }}."
+/// ```
+#[derive(serde::Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// The Git remote for Gerrit, if the `git-gr.remote` Git config isn't set.
+    pub remote: Option<String>,
+    /// The query for `git gr query` with no arguments.
+    pub default_query: Option<String>,
+    /// Show times in 24-hour format, like `GIT_GR_24_HOUR_TIME`.
+    #[serde(rename = "24-hour-time")]
+    pub twenty_four_hour_time: Option<bool>,
+    /// The format for dates in the current year, like `GIT_GR_DATE_FORMAT`.
+    pub date_format: Option<String>,
+    /// The format for dates in other years, like `GIT_GR_FULL_DATE_FORMAT`.
+    pub full_date_format: Option<String>,
+    /// The time zone to show times in, like `GIT_GR_TIMEZONE`.
+    pub timezone: Option<String>,
+    /// A template for change URLs, for deployments where the URLs Gerrit reports are wrong.
+    ///
+    /// `{number}`, `{project}`, and `{branch}` are replaced with the change's details.
+    pub change_url: Option<String>,
+    /// How long to cache Gerrit API responses for, like `30m` or `2h`.
+    pub cache_ttl: Option<String>,
+    /// How long to wait for Gerrit to respond over SSH or HTTP, like `30s` or `2m`.
+    pub timeout: Option<String>,
+    /// How long Gerrit should spend on a `gerrit query` before giving up, like `45s`.
+    ///
+    /// Only supported by newer Gerrit servers, so it isn't sent unless it's set.
+    pub query_deadline: Option<String>,
+    /// Named queries, run with `git gr query @NAME`.
+    ///
+    /// Names are case-insensitive, because Git lowercases the names in `git-gr.query.NAME` keys.
+    pub queries: BTreeMap<String, String>,
+    pub restack: RestackConfig,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RestackConfig {
+    /// Push changes as soon as a restack completes, like `git gr restack push`.
+    pub push: Option<bool>,
+    /// A hashtag to add to changes while they're being restacked, like `git-gr-restacking`.
+    pub hashtag: Option<String>,
+    /// Whether to push new patchsets to work-in-progress and private changes.
+    pub push_wip: Option<PushWip>,
+    /// After pushing, post a message on each restacked change and add its reviewers back to the
+    /// attention set.
+    pub rerequest_review: Option<bool>,
+    /// A template for the message posted by `rerequest-review`.
+    pub review_message: Option<String>,
+}
+
+impl Config {
+    /// Load the configuration files, making them available from [`Config::global`].
+    pub fn init(git: &Git) -> miette::Result<()> {
+        let mut config = Self::default();
+        if let Some(path) = user_config_path() {
+            config = config.merge(Self::read(&path)?);
+        }
+        if let Some(toplevel) = git.toplevel()? {
+            config = config.merge(Self::read(&toplevel.join(".git-gr.toml"))?);
+        }
+        config = config.merge(Self::from_git_config(git)?);
+        if let Some(cache_ttl) = &config.cache_ttl {
+            parse_duration(cache_ttl)
+                .map_err(|error| miette!("Invalid `cache-ttl` setting: {error}"))?;
+        }
+        if let Some(timeout) = &config.timeout {
+            parse_duration(timeout)
+                .map_err(|error| miette!("Invalid `timeout` setting: {error}"))?;
+        }
+        for (setting, format) in [
+            ("date-format", &config.date_format),
+            ("full-date-format", &config.full_date_format),
+        ] {
+            if let Some(format) = format {
+                parse_format(format)
+                    .map_err(|error| miette!("Invalid `{setting}` setting: {error}"))?;
+            }
+        }
+        if let Some(timezone) = &config.timezone {
+            tz::TimeZone::from_posix_tz(timezone)
+                .map_err(|error| miette!("Invalid `timezone` setting: {error}"))?;
+        }
+        if let Some(query_deadline) = &config.query_deadline {
+            parse_duration(query_deadline)
+                .map_err(|error| miette!("Invalid `query-deadline` setting: {error}"))?;
+        }
+        tracing::debug!(?config, "Loaded configuration");
+        let _ = CONFIG.set(config);
+        Ok(())
+    }
+
+    /// The loaded configuration, or the defaults if it hasn't been loaded.
+    pub fn global() -> &'static Self {
+        CONFIG.get_or_init(Self::default)
+    }
+
+    /// How long to cache Gerrit API responses for.
+    pub fn cache_lifespan(&self) -> Duration {
+        self.cache_ttl
+            .as_deref()
+            .and_then(|cache_ttl| parse_duration(cache_ttl).ok())
+            .and_then(|cache_ttl| cache_ttl.try_into().ok())
+            .unwrap_or(CACHE_LIFESPAN)
+    }
+
+    /// The query to run when none is given.
+    pub fn default_query(&self) -> &str {
+        self.default_query.as_deref().unwrap_or(DEFAULT_QUERY)
+    }
+
+    /// How long to wait for Gerrit to respond.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+            .as_deref()
+            .and_then(|timeout| parse_duration(timeout).ok())
+            .and_then(|timeout| timeout.try_into().ok())
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    /// How long Gerrit should spend on a query, if the `query-deadline` setting is set.
+    ///
+    /// There's no default, because older Gerrit servers reject `gerrit query --deadline`.
+    pub fn query_deadline(&self) -> Option<Duration> {
+        self.query_deadline
+            .as_deref()
+            .and_then(|query_deadline| parse_duration(query_deadline).ok())
+            .and_then(|query_deadline| query_deadline.try_into().ok())
+    }
+
+    /// Expand a named query like `@incoming`, leaving other queries alone.
+    pub fn expand_query(&self, query: String) -> miette::Result<String> {
+        let name = match query.strip_prefix('@') {
+            Some(name) => name,
+            None => return Ok(query),
+        };
+        let query = self
+            .queries
+            .iter()
+            .find(|(query_name, _)| query_name.eq_ignore_ascii_case(name));
+        match query {
+            Some((_, query)) => Ok(query.clone()),
+            None if self.queries.is_empty() => Err(miette!(
+                help = "Define named queries in the `[queries]` section of the config file, or with `git config git-gr.query.NAME QUERY`",
+                "No query named `{name}`"
+            )
+            .classify(ErrorKind::Usage)),
+            None => Err(miette!(
+                help = format!(
+                    "Named queries are:\n{}",
+                    format_bulleted_list(self.queries.keys().map(|name| format!("@{name}")))
+                ),
+                "No query named `{name}`"
+            )
+            .classify(ErrorKind::Usage)),
+        }
+    }
+
+    /// Read settings from `git-gr.*` Git config keys.
+    ///
+    /// This runs at the start of every `git-gr` process, so it reads every key with one
+    /// `git config` call.
+    fn from_git_config(git: &Git) -> miette::Result<Self> {
+        Self::from_git_config_entries(git.config_get_regexp(r"^git-gr\.")?)
+    }
+
+    /// Read settings from `git config --get-regexp` output, which has lowercase keys.
+    fn from_git_config_entries(entries: Vec<(String, Option<String>)>) -> miette::Result<Self> {
+        let mut settings = BTreeMap::new();
+        let mut queries = BTreeMap::new();
+        for (key, value) in entries {
+            match key.strip_prefix("git-gr.query.") {
+                Some(name) => {
+                    queries.insert(name.to_owned(), value.unwrap_or_default());
+                }
+                None => {
+                    // Later values override earlier ones, like `git config --get`.
+                    settings.insert(key, value);
+                }
+            }
+        }
+
+        let get = |key: &str| -> Option<String> {
+            settings
+                .get(&key.to_ascii_lowercase())
+                .map(|value| value.clone().unwrap_or_default())
+        };
+        let get_bool = |key: &str| -> miette::Result<Option<bool>> {
+            settings
+                .get(&key.to_ascii_lowercase())
+                .map(|value| {
+                    parse_git_bool(value.as_deref())
+                        .ok_or_else(|| miette!("Invalid `{key}` setting: expected a boolean"))
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            remote: get("git-gr.remote"),
+            default_query: get("git-gr.defaultQuery"),
+            twenty_four_hour_time: get_bool("git-gr.twentyFourHourTime")?,
+            date_format: get("git-gr.dateFormat"),
+            full_date_format: get("git-gr.fullDateFormat"),
+            timezone: get("git-gr.timezone"),
+            change_url: get("git-gr.changeUrl"),
+            cache_ttl: get("git-gr.cacheTtl"),
+            timeout: get("git-gr.timeout"),
+            query_deadline: get("git-gr.queryDeadline"),
+            queries,
+            restack: RestackConfig {
+                push: get_bool("git-gr.restackPush")?,
+                hashtag: get("git-gr.restackHashtag"),
+                push_wip: get("git-gr.restackPushWip")
+                    .map(|push_wip| {
+                        PushWip::from_str(&push_wip, true).map_err(|error| {
+                            miette!("Invalid `git-gr.restackPushWip` setting: {error}")
+                        })
+                    })
+                    .transpose()?,
+                rerequest_review: get_bool("git-gr.restackRerequestReview")?,
+                review_message: get("git-gr.restackReviewMessage"),
+            },
+        })
+    }
+
+    fn read(path: &Utf8PathBuf) -> miette::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).into_diagnostic()?;
+        Self::parse(&contents).wrap_err_with(|| format!("Failed to parse config file `{path}`"))
+    }
+
+    fn parse(contents: &str) -> miette::Result<Self> {
+        toml::from_str(contents).into_diagnostic()
+    }
+
+    /// Combine two configurations, preferring settings from `other`.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            remote: other.remote.or(self.remote),
+            default_query: other.default_query.or(self.default_query),
+            twenty_four_hour_time: other.twenty_four_hour_time.or(self.twenty_four_hour_time),
+            date_format: other.date_format.or(self.date_format),
+            full_date_format: other.full_date_format.or(self.full_date_format),
+            timezone: other.timezone.or(self.timezone),
+            change_url: other.change_url.or(self.change_url),
+            cache_ttl: other.cache_ttl.or(self.cache_ttl),
+            timeout: other.timeout.or(self.timeout),
+            query_deadline: other.query_deadline.or(self.query_deadline),
+            queries: {
+                let mut queries = self.queries;
+                queries.extend(other.queries);
+                queries
+            },
+            restack: RestackConfig {
+                push: other.restack.push.or(self.restack.push),
+                hashtag: other.restack.hashtag.or(self.restack.hashtag),
+                push_wip: other.restack.push_wip.or(self.restack.push_wip),
+                rerequest_review: other
+                    .restack
+                    .rerequest_review
+                    .or(self.restack.rerequest_review),
+                review_message: other.restack.review_message.or(self.restack.review_message),
+            },
+        }
+    }
+}
+
+/// Parse a Git config boolean like `git config --type=bool` does.
+///
+/// A key with no value at all (`None`), like `[git-gr] restackPush`, is true.
+fn parse_git_bool(value: Option<&str>) -> Option<bool> {
+    let value = match value {
+        Some(value) => value.to_ascii_lowercase(),
+        None => return Some(true),
+    };
+    match value.as_str() {
+        "true" | "yes" | "on" => Some(true),
+        "false" | "no" | "off" | "" => Some(false),
+        number => number.parse::<i64>().ok().map(|number| number != 0),
+    }
+}
+
+/// Fill in a `change-url` template.
+pub fn expand_change_url(
+    template: &str,
+    number: ChangeNumber,
+    project: &str,
+    branch: &str,
+) -> String {
+    template
+        .replace("{number}", &number.to_string())
+        .replace("{project}", project)
+        .replace("{branch}", branch)
+}
+
+/// `$XDG_CONFIG_HOME/git-gr/config.toml`, or `~/.config/git-gr/config.toml`.
+fn user_config_path() -> Option<Utf8PathBuf> {
+    let config_home = std::env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(Utf8PathBuf::from)
+        .or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .filter(|dir| !dir.is_empty())
+                .map(|home| Utf8PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join("git-gr").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_config_parse() {
+        assert_eq!(
+            Config::parse(indoc!(
+                r#"
+                remote = "gerrit"
+                default-query = "status:open owner:self"
+                24-hour-time = true
+                date-format = "[day].[month]."
+                full-date-format = "[day].[month].[year]"
+                timezone = "Europe/Berlin"
+                change-url = "https://review.example.com/c/{project}/+/{number}"
+                cache-ttl = "30m"
+                timeout = "90s"
+                query-deadline = "45s"
+
+                [queries]
+                incoming = "is:open reviewer:self -owner:self"
+
+                [restack]
+                push = true
+                hashtag = "git-gr-restacking"
+                push-wip = "skip"
+                rerequest-review = true
+                review-message = "Rebased onto {{ branch }}."
+                "#
+            ))
+            .unwrap(),
+            Config {
+                remote: Some("gerrit".to_owned()),
+                default_query: Some("status:open owner:self".to_owned()),
+                twenty_four_hour_time: Some(true),
+                date_format: Some("[day].[month].".to_owned()),
+                full_date_format: Some("[day].[month].[year]".to_owned()),
+                timezone: Some("Europe/Berlin".to_owned()),
+                change_url: Some("https://review.example.com/c/{project}/+/{number}".to_owned()),
+                cache_ttl: Some("30m".to_owned()),
+                timeout: Some("90s".to_owned()),
+                query_deadline: Some("45s".to_owned()),
+                queries: BTreeMap::from([(
+                    "incoming".to_owned(),
+                    "is:open reviewer:self -owner:self".to_owned()
+                )]),
+                restack: RestackConfig {
+                    push: Some(true),
+                    hashtag: Some("git-gr-restacking".to_owned()),
+                    push_wip: Some(PushWip::Skip),
+                    rerequest_review: Some(true),
+                    review_message: Some("Rebased onto {{ branch }}.".to_owned()),
+                },
+            }
+        );
+
+        assert!(Config::parse("remtoe = \"gerrit\"").is_err());
+    }
+
+    #[test]
+    fn test_config_merge() {
+        let user = Config {
+            remote: Some("gerrit".to_owned()),
+            twenty_four_hour_time: Some(true),
+            ..Default::default()
+        };
+        let repo = Config {
+            remote: Some("origin".to_owned()),
+            restack: RestackConfig {
+                push: Some(false),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            user.merge(repo),
+            Config {
+                remote: Some("origin".to_owned()),
+                default_query: None,
+                twenty_four_hour_time: Some(true),
+                date_format: None,
+                full_date_format: None,
+                timezone: None,
+                change_url: None,
+                cache_ttl: None,
+                timeout: None,
+                query_deadline: None,
+                queries: Default::default(),
+                restack: RestackConfig {
+                    push: Some(false),
+                    ..Default::default()
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_expand_query() {
+        let config = Config {
+            queries: BTreeMap::from([("incoming".to_owned(), "reviewer:self".to_owned())]),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.expand_query("@incoming".to_owned()).unwrap(),
+            "reviewer:self"
+        );
+        assert_eq!(
+            config.expand_query("owner:self".to_owned()).unwrap(),
+            "owner:self"
+        );
+        assert_eq!(
+            config.expand_query("@Incoming".to_owned()).unwrap(),
+            "reviewer:self"
+        );
+        assert!(config.expand_query("@outgoing".to_owned()).is_err());
+    }
+
+    #[test]
+    fn test_config_from_git_config_entries() {
+        let config = Config::from_git_config_entries(vec![
+            ("git-gr.remote".to_owned(), Some("origin".to_owned())),
+            ("git-gr.remote".to_owned(), Some("gerrit".to_owned())),
+            (
+                "git-gr.defaultquery".to_owned(),
+                Some("owner:self".to_owned()),
+            ),
+            ("git-gr.twentyfourhourtime".to_owned(), None),
+            ("git-gr.restackpush".to_owned(), Some("off".to_owned())),
+            (
+                "git-gr.query.incoming".to_owned(),
+                Some("reviewer:self".to_owned()),
+            ),
+        ])
+        .unwrap();
+        assert_eq!(config.remote.as_deref(), Some("gerrit"));
+        assert_eq!(config.default_query.as_deref(), Some("owner:self"));
+        assert_eq!(config.twenty_four_hour_time, Some(true));
+        assert_eq!(config.restack.push, Some(false));
+        assert_eq!(config.queries["incoming"], "reviewer:self");
+
+        assert!(Config::from_git_config_entries(vec![(
+            "git-gr.restackpush".to_owned(),
+            Some("maybe".to_owned())
+        )])
+        .is_err());
+    }
+
+    #[test]
+    fn test_expand_change_url() {
+        assert_eq!(
+            expand_change_url(
+                "https://review.example.com/c/{project}/+/{number}",
+                "1234".parse().unwrap(),
+                "team/project",
+                "main"
+            ),
+            "https://review.example.com/c/team/project/+/1234"
+        );
+        assert_eq!(
+            expand_change_url(
+                "https://go.example.com/cl/{number}?branch={branch}",
+                "5".parse().unwrap(),
+                "project",
+                "main"
+            ),
+            "https://go.example.com/cl/5?branch=main"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! A tiny evaluator for Gerrit label copy conditions.
+//!
+//! Copy conditions are queries like `changekind:TRIVIAL_REBASE OR is:MIN` which determine if a
+//! vote is copied to a new patchset. We can only evaluate a small subset of the query language
+//! locally; anything else evaluates to `None` (unknown).
+//!
+//! See: <https://gerrit-review.googlesource.com/Documentation/config-labels.html#label_copyCondition>
+
+use crate::change_kind::ChangeKind;
+
+/// The information needed to determine if a vote is copied to a new patchset.
+#[derive(Debug, Clone, Copy)]
+pub struct VoteContext {
+    /// The kind of the new patchset.
+    pub kind: ChangeKind,
+    /// The value of the vote.
+    pub value: i32,
+    /// The minimum value for the label.
+    pub min: i32,
+    /// The maximum value for the label.
+    pub max: i32,
+}
+
+/// Evaluate a copy condition.
+///
+/// Returns `Some(true)` if the vote will be copied, `Some(false)` if it won't, and `None` if
+/// we can't tell.
+pub fn evaluate(condition: &str, context: VoteContext) -> Option<bool> {
+    let tokens = tokenize(condition);
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        context,
+    };
+    let result = parser.or()?;
+    if parser.position == tokens.len() {
+        result
+    } else {
+        // Trailing garbage, probably unbalanced parentheses.
+        None
+    }
+}
+
+fn tokenize(condition: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in condition.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+            }
+            '(' | ')' if !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                tokens.push(c.to_string());
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    position: usize,
+    context: VoteContext,
+}
+
+/// The outer `Option` is for parse errors; the inner `Option` is for unknown atoms.
+type ParseResult = Option<Option<bool>>;
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|token| token.as_str())
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> ParseResult {
+        let mut result = self.and()?;
+        while self.peek() == Some("OR") {
+            self.next();
+            let rhs = self.and()?;
+            result = match (result, rhs) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            };
+        }
+        Some(result)
+    }
+
+    fn and(&mut self) -> ParseResult {
+        let mut result = self.not()?;
+        loop {
+            match self.peek() {
+                Some("AND") => {
+                    self.next();
+                }
+                Some("OR") | Some(")") | None => {
+                    break;
+                }
+                // Implicit `AND`.
+                Some(_) => {}
+            }
+            let rhs = self.not()?;
+            result = match (result, rhs) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            };
+        }
+        Some(result)
+    }
+
+    fn not(&mut self) -> ParseResult {
+        match self.peek()? {
+            "NOT" => {
+                self.next();
+                Some(self.not()?.map(|value| !value))
+            }
+            token if token.starts_with('-') && token.len() > 1 => {
+                self.next();
+                Some(self.atom(&token[1..]).map(|value| !value))
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> ParseResult {
+        match self.next()? {
+            "(" => {
+                let result = self.or()?;
+                match self.next()? {
+                    ")" => Some(result),
+                    _ => None,
+                }
+            }
+            ")" => None,
+            atom => Some(self.atom(atom)),
+        }
+    }
+
+    fn atom(&self, atom: &str) -> Option<bool> {
+        let (key, value) = atom.split_once(':')?;
+        match key {
+            "changekind" => Some(value.eq_ignore_ascii_case(self.context.kind.as_str())),
+            "is" => match value.to_ascii_uppercase().as_str() {
+                "ANY" => Some(true),
+                "MIN" => Some(self.context.value == self.context.min),
+                "MAX" => Some(self.context.value == self.context.max),
+                value => value
+                    .trim_start_matches('+')
+                    .parse::<i32>()
+                    .ok()
+                    .map(|value| value == self.context.value),
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn context(kind: ChangeKind, value: i32) -> VoteContext {
+        VoteContext {
+            kind,
+            value,
+            min: -2,
+            max: 2,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_change_kind() {
+        let condition = "changekind:NO_CHANGE OR changekind:TRIVIAL_REBASE OR is:MIN";
+        assert_eq!(
+            evaluate(condition, context(ChangeKind::TrivialRebase, 2)),
+            Some(true)
+        );
+        assert_eq!(
+            evaluate(condition, context(ChangeKind::Rework, 2)),
+            Some(false)
+        );
+        assert_eq!(
+            evaluate(condition, context(ChangeKind::Rework, -2)),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_grouping() {
+        let condition = "is:MAX AND (changekind:NO_CODE_CHANGE OR changekind:TRIVIAL_REBASE)";
+        assert_eq!(
+            evaluate(condition, context(ChangeKind::NoCodeChange, 2)),
+            Some(true)
+        );
+        assert_eq!(
+            evaluate(condition, context(ChangeKind::NoCodeChange, 1)),
+            Some(false)
+        );
+        assert_eq!(
+            evaluate("-is:MIN", context(ChangeKind::Rework, 1)),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_unknown() {
+        // We can't tell who the approver is locally.
+        assert_eq!(
+            evaluate(
+                "approverin:\"Core Maintainers\" OR changekind:NO_CHANGE",
+                context(ChangeKind::Rework, 2)
+            ),
+            None
+        );
+        // But if another branch of the `OR` is true, it doesn't matter.
+        assert_eq!(
+            evaluate(
+                "approverin:\"Core Maintainers\" OR changekind:NO_CHANGE",
+                context(ChangeKind::NoChange, 2)
+            ),
+            Some(true)
+        );
+        assert_eq!(evaluate("(is:MAX", context(ChangeKind::Rework, 2)), None);
+    }
+}
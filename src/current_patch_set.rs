@@ -1,7 +1,19 @@
+use miette::IntoDiagnostic;
+use serde_with::serde_as;
+use serde_with::TimestampSeconds;
+use time::OffsetDateTime;
+
+use crate::age::format_age;
 use crate::approval::Approval;
 use crate::author::Author;
+use crate::change_kind::ChangeKind;
+use crate::date_format::DateFormat;
+use crate::timezone::local_time_zone;
 
-/// The current patch set in a Gerrit change.
+/// A patch set in a Gerrit change.
+///
+/// Used for both the current patch set and the list of all patch sets.
+#[serde_as]
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
@@ -20,11 +32,10 @@ pub struct CurrentPatchSet {
     /// The change's author.
     pub author: Author,
     /// Created timestamp.
-    ///
-    /// Unix epoch.
-    created_on: u64,
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    pub created_on: OffsetDateTime,
     /// Patch kind, e.g. `TRIVIAL_REBASE`.
-    kind: String,
+    pub kind: ChangeKind,
     /// The approvals for this patchset.
     #[serde(default)]
     pub approvals: Vec<Approval>,
@@ -33,3 +44,18 @@ pub struct CurrentPatchSet {
     /// The number of deleted lines in the patchset.
     pub size_deletions: u64,
 }
+
+impl CurrentPatchSet {
+    /// A one-line summary, like `2: trivial rebase by Rebecca Turner on 2024-04-01 (3d ago)`.
+    pub fn summary(&self) -> miette::Result<String> {
+        let age = format_age(OffsetDateTime::now_utc() - self.created_on);
+        let created_on = local_time_zone()
+            .to_local(self.created_on)
+            .format(&DateFormat::configured().full)
+            .into_diagnostic()?;
+        Ok(format!(
+            "{}: {} by {} on {created_on} ({age} ago)",
+            self.number, self.kind, self.uploader.name
+        ))
+    }
+}
@@ -0,0 +1,68 @@
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+
+use crate::gerrit::Gerrit;
+use crate::query::in_project;
+use crate::query::QueryTableOptions;
+
+/// A section of the dashboard.
+struct Section {
+    title: &'static str,
+    query: &'static str,
+    /// Sort the changes by how actionable they are for a reviewer.
+    review_priority: bool,
+}
+
+/// The sections of the dashboard, like the default dashboard in the Gerrit web UI.
+const SECTIONS: [Section; 3] = [
+    Section {
+        title: "Your open changes",
+        query: "is:open owner:self",
+        review_priority: false,
+    },
+    Section {
+        title: "Awaiting your review",
+        query: "is:open -owner:self -is:wip reviewer:self",
+        review_priority: true,
+    },
+    Section {
+        title: "Recently merged",
+        query: "status:merged owner:self -age:1w",
+        review_priority: false,
+    },
+];
+
+/// Format the dashboard: a query table for each section, with a heading.
+pub fn format_dashboard(
+    gerrit: &Gerrit,
+    all_projects: bool,
+    options: QueryTableOptions,
+) -> miette::Result<String> {
+    let mut sections = Vec::new();
+    for section in SECTIONS {
+        let query = if all_projects {
+            section.query.to_owned()
+        } else {
+            in_project(section.query, gerrit.project())
+        };
+        let options = QueryTableOptions {
+            review_priority: section.review_priority,
+            ..options
+        };
+        let results = gerrit.query_for_table(&query, options)?;
+
+        let title = section
+            .title
+            .if_supports_color(Stdout, |title| title.bold())
+            .to_string();
+        let body = if results.changes.is_empty() {
+            "No changes"
+                .if_supports_color(Stdout, |text| text.dimmed())
+                .to_string()
+        } else {
+            gerrit.format_changes_table(&results.changes, options)?
+        };
+        sections.push(format!("{title}\n{body}"));
+    }
+    Ok(sections.join("\n\n"))
+}
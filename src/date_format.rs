@@ -0,0 +1,125 @@
+use time::format_description::OwnedFormatItem;
+
+use crate::config::Config;
+
+/// How to format dates, for timestamps that aren't from today.
+#[derive(Debug, Clone)]
+pub struct DateFormat {
+    /// The format for dates in the current year.
+    pub month_day: OwnedFormatItem,
+    /// The format for dates in other years.
+    pub full: OwnedFormatItem,
+}
+
+impl DateFormat {
+    /// Get the date format from the environment or the configuration.
+    ///
+    /// `GIT_GR_DATE_FORMAT` and `GIT_GR_FULL_DATE_FORMAT` (or the `date-format` and
+    /// `full-date-format` settings) can be set to [`time` format descriptions][format] like
+    /// `[day].[month].` for dates in the current year and in other years, respectively.
+    /// Otherwise, the format is picked based on the locale in `LC_ALL`, `LC_TIME`, or `LANG`.
+    ///
+    /// [format]: https://time-rs.github.io/book/api/format-description.html
+    pub fn configured() -> Self {
+        let locale = ["LC_ALL", "LC_TIME", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        let (month_day, full) = locale_date_formats(&locale);
+        let config = Config::global();
+
+        Self {
+            month_day: configured_format(
+                "GIT_GR_DATE_FORMAT",
+                config.date_format.as_deref(),
+                month_day,
+            ),
+            full: configured_format(
+                "GIT_GR_FULL_DATE_FORMAT",
+                config.full_date_format.as_deref(),
+                full,
+            ),
+        }
+    }
+}
+
+/// Parse a format description from an environment variable or a config setting, falling back
+/// to a default.
+///
+/// Config settings are checked when the config is loaded, so only the environment variable can
+/// fail to parse here.
+fn configured_format(var: &str, setting: Option<&str>, default: &str) -> OwnedFormatItem {
+    if let Ok(format) = std::env::var(var) {
+        if !format.is_empty() {
+            match parse_format(&format) {
+                Ok(format) => {
+                    return format;
+                }
+                Err(error) => {
+                    tracing::warn!("Failed to parse date format `{format}` from `{var}`: {error}");
+                }
+            }
+        }
+    }
+
+    setting
+        .and_then(|format| parse_format(format).ok())
+        .unwrap_or_else(|| parse_format(default).expect("Default date format parses"))
+}
+
+/// Parse a [`time` format description][format].
+///
+/// [format]: https://time-rs.github.io/book/api/format-description.html
+pub fn parse_format(
+    format: &str,
+) -> Result<OwnedFormatItem, time::error::InvalidFormatDescription> {
+    time::format_description::parse_owned::<2>(format)
+}
+
+/// Get the date formats (for the current year and other years) conventionally used in a locale
+/// like `de_DE.UTF-8`.
+fn locale_date_formats(locale: &str) -> (&'static str, &'static str) {
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let (language, country) = locale.split_once('_').unwrap_or((locale, ""));
+
+    match language {
+        "en" if country.is_empty() || country == "US" => ("[month]-[day]", "[year]-[month]-[day]"),
+        "en" | "fr" | "es" | "it" | "pt" | "el" => ("[day]/[month]", "[day]/[month]/[year]"),
+        "de" | "da" | "fi" | "nb" | "nn" | "no" | "pl" | "cs" | "sk" | "ru" | "uk" | "tr" => {
+            ("[day].[month].", "[day].[month].[year]")
+        }
+        "nl" => ("[day]-[month]", "[day]-[month]-[year]"),
+        _ => ("[month]-[day]", "[year]-[month]-[day]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_locale_date_formats() {
+        assert_eq!(
+            locale_date_formats("en_US.UTF-8"),
+            ("[month]-[day]", "[year]-[month]-[day]")
+        );
+        assert_eq!(
+            locale_date_formats("de_DE.UTF-8"),
+            ("[day].[month].", "[day].[month].[year]")
+        );
+        assert_eq!(
+            locale_date_formats("en_GB"),
+            ("[day]/[month]", "[day]/[month]/[year]")
+        );
+        assert_eq!(
+            locale_date_formats("C.UTF-8"),
+            ("[month]-[day]", "[year]-[month]-[day]")
+        );
+        assert_eq!(
+            locale_date_formats("ja_JP.UTF-8"),
+            ("[month]-[day]", "[year]-[month]-[day]")
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use miette::IntoDiagnostic;
+use time::format_description::well_known::Rfc3339;
+
+use crate::change::Change;
+use crate::query::QueryTableOptions;
+
+/// Format changes as delimited text, like CSV or TSV, with a header row.
+///
+/// The columns match [`Gerrit::format_query_results`], but values aren't truncated or colored,
+/// and times are RFC 3339 timestamps.
+///
+/// [`Gerrit::format_query_results`]: crate::gerrit::Gerrit::format_query_results
+pub fn format_delimited(
+    changes: &[Change],
+    options: QueryTableOptions,
+    username: &str,
+    delimiter: char,
+) -> miette::Result<String> {
+    let mut header = vec!["number", "subject", "updated", "owner", "status"];
+    if options.size {
+        header.extend(["insertions", "deletions"]);
+    }
+    if options.age {
+        header.push("created");
+    }
+    if options.kind {
+        header.push("kind");
+    }
+    if options.votes {
+        header.push("votes");
+    }
+    if options.my_vote {
+        header.push("mine");
+    }
+    header.push("url");
+
+    let mut output = format_row(header, delimiter);
+    for change in changes {
+        let mut row = vec![
+            change.number.to_string(),
+            change.subject.clone().unwrap_or_default(),
+            change.last_updated.format(&Rfc3339).into_diagnostic()?,
+            options.owner_format.format(&change.owner).to_owned(),
+            change.status_cell().content(),
+        ];
+        if options.size {
+            row.push(change.current_patch_set.size_insertions.to_string());
+            row.push(change.current_patch_set.size_deletions.to_string());
+        }
+        if options.age {
+            row.push(change.created_on.format(&Rfc3339).into_diagnostic()?);
+        }
+        if options.kind {
+            row.push(change.kind_cell().content());
+        }
+        if options.votes {
+            row.push(change.votes_cell().content());
+        }
+        if options.my_vote {
+            row.push(change.my_votes_cell(username).content());
+        }
+        row.push(change.web_url());
+        output.push_str(&format_row(row, delimiter));
+    }
+    Ok(output)
+}
+
+/// Format a row of fields, ending with a newline.
+fn format_row(fields: impl IntoIterator<Item = impl AsRef<str>>, delimiter: char) -> String {
+    let mut row = fields
+        .into_iter()
+        .map(|field| escape_field(field.as_ref(), delimiter))
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string());
+    row.push('\n');
+    row
+}
+
+/// Escape a field so it can't break the row apart.
+///
+/// TSV fields are never quoted; tabs, newlines, and backslashes are written as `\t`, `\n`, and
+/// `\\` instead, like the `text/tab-separated-values` convention most tools read.
+///
+/// Other delimiters follow CSV, quoting a field if it contains the delimiter, a quote, or a
+/// newline, and doubling any quotes. See: <https://www.rfc-editor.org/rfc/rfc4180#section-2>
+fn escape_field(field: &str, delimiter: char) -> String {
+    if delimiter == '\t' {
+        field
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    } else if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_escape_field() {
+        assert_eq!(escape_field("Add a feature", ','), "Add a feature");
+        assert_eq!(escape_field("Fix a, b", ','), "\"Fix a, b\"");
+        assert_eq!(escape_field(r#"Use "quotes""#, ','), r#""Use ""quotes""""#);
+        assert_eq!(escape_field("Two\nlines", ','), "\"Two\nlines\"");
+    }
+
+    #[test]
+    fn test_escape_field_tsv() {
+        assert_eq!(escape_field("Fix a, b", '\t'), "Fix a, b");
+        assert_eq!(escape_field(r#"Use "quotes""#, '\t'), r#"Use "quotes""#);
+        assert_eq!(escape_field("Fix\ta", '\t'), r"Fix\ta");
+        assert_eq!(escape_field("Two\r\nlines", '\t'), r"Two\r\nlines");
+        assert_eq!(escape_field(r"C:\path", '\t'), r"C:\\path");
+    }
+
+    #[test]
+    fn test_format_row() {
+        assert_eq!(
+            format_row(["123", "Fix a, b", ""], ','),
+            "123,\"Fix a, b\",\n"
+        );
+    }
+}
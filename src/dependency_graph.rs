@@ -28,6 +28,12 @@ pub struct DependencyGraph {
     pub root: ChangeNumber,
     pub(crate) dependencies: BTreeMap<ChangeNumber, ChangeNumber>,
     pub(crate) reverse_dependencies: BTreeMap<ChangeNumber, BTreeSet<ChangeNumber>>,
+    /// Changes whose reverse dependencies couldn't be checked against the REST API.
+    ///
+    /// Changes which depend on an outdated patchset of these changes may be missing from the
+    /// graph.
+    #[serde(default)]
+    pub(crate) unverified: BTreeSet<ChangeNumber>,
 }
 
 impl DependencyGraph {
@@ -36,6 +42,7 @@ impl DependencyGraph {
             root,
             dependencies: Default::default(),
             reverse_dependencies: Default::default(),
+            unverified: Default::default(),
         }
     }
 
@@ -71,6 +78,14 @@ impl DependencyGraph {
         self.reverse_dependencies.entry(change).or_default()
     }
 
+    /// Are the reverse dependencies of `change` possibly out of date?
+    ///
+    /// This happens when related changes can't be fetched from the REST API, so changes which
+    /// depend on an outdated patchset of `change` may be missing.
+    pub fn is_unverified(&self, change: ChangeNumber) -> bool {
+        self.unverified.contains(&change)
+    }
+
     /// Get the root dependency changes in the graph.
     ///
     /// These are the changes that do not depend on any other changes.
@@ -111,11 +126,69 @@ impl DependencyGraph {
         }
     }
 
+    /// All the changes in the graph.
+    pub fn changes(&self) -> BTreeSet<ChangeNumber> {
+        std::iter::once(self.root)
+            .chain(self.dependencies.keys().copied())
+            .chain(self.dependencies.values().copied())
+            .collect()
+    }
+
+    /// All the changes in the graph, from the bottom of the stack up.
+    ///
+    /// Each change comes after the change it depends on.
+    pub fn changes_bottom_up(&mut self) -> miette::Result<Vec<ChangeNumber>> {
+        let root = self.dependency_root()?;
+        let mut changes = Vec::new();
+        let mut seen = BTreeSet::new();
+        seen.insert(root);
+        let mut queue = VecDeque::new();
+        queue.push_front(root);
+
+        while let Some(change) = queue.pop_back() {
+            changes.push(change);
+            for reverse_dependency in self.needed_by(change) {
+                if !seen.contains(reverse_dependency) {
+                    seen.insert(*reverse_dependency);
+                    queue.push_front(*reverse_dependency);
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Format the graph as a tree, truncating lines to fit in `width` columns.
     pub fn format_tree(
         &mut self,
         gerrit: &Gerrit,
+        width: Option<usize>,
+        extra_label: impl FnMut(ChangeNumber) -> miette::Result<Vec<String>>,
+    ) -> miette::Result<String> {
+        self.format_tree_with_leaves(gerrit, width, extra_label, |_| Ok(Vec::new()))
+    }
+
+    /// Format the graph as a tree, with extra leaf nodes (like dependencies outside the graph)
+    /// under each change.
+    pub fn format_tree_with_leaves(
+        &mut self,
+        gerrit: &Gerrit,
+        width: Option<usize>,
         mut extra_label: impl FnMut(ChangeNumber) -> miette::Result<Vec<String>>,
+        mut extra_leaves: impl FnMut(ChangeNumber) -> miette::Result<Vec<String>>,
     ) -> miette::Result<String> {
+        let mut node = |change: ChangeNumber| -> miette::Result<Arc<Mutex<Tree>>> {
+            let mut label = vec![change.pretty(gerrit)?];
+            label.extend(extra_label(change)?);
+            let mut tree = Tree::leaf(label);
+            tree.children.extend(
+                extra_leaves(change)?
+                    .into_iter()
+                    .map(|leaf| Arc::new(Mutex::new(Tree::leaf(vec![leaf])))),
+            );
+            Ok(Arc::new(Mutex::new(tree)))
+        };
+
         let mut trees = BTreeMap::<ChangeNumber, Arc<Mutex<Tree>>>::new();
         let root = self.dependency_root()?;
 
@@ -126,22 +199,14 @@ impl DependencyGraph {
 
         while let Some(change) = queue.pop_back() {
             let tree = Arc::clone(match trees.entry(change) {
-                Entry::Vacant(entry) => {
-                    let mut label = vec![change.pretty(gerrit)?];
-                    label.extend(extra_label(change)?);
-                    entry.insert(Arc::new(Mutex::new(Tree::leaf(label))))
-                }
+                Entry::Vacant(entry) => entry.insert(node(change)?),
                 Entry::Occupied(entry) => entry.into_mut(),
             });
 
             let needed_by = self.needed_by(change);
             for reverse_dependency in needed_by {
                 let reverse_dependency_tree = Arc::clone(match trees.entry(*reverse_dependency) {
-                    Entry::Vacant(entry) => {
-                        let mut label = vec![reverse_dependency.pretty(gerrit)?];
-                        label.extend(extra_label(*reverse_dependency)?);
-                        entry.insert(Arc::new(Mutex::new(Tree::leaf(label))))
-                    }
+                    Entry::Vacant(entry) => entry.insert(node(*reverse_dependency)?),
                     Entry::Occupied(entry) => entry.into_mut(),
                 });
                 tree.lock().children.push(reverse_dependency_tree);
@@ -155,6 +220,6 @@ impl DependencyGraph {
 
         let tree = trees.get(&root).expect("Root should have a tree").lock();
 
-        Ok(tree.to_string())
+        Ok(tree.to_string_with_width(width))
     }
 }
@@ -7,6 +7,8 @@ use crate::change::Change;
 use crate::change_number::ChangeNumber;
 use crate::dependency_graph::DependencyGraph;
 use crate::dependency_graph::DependsOnRelation;
+use crate::error_kind::error_kind;
+use crate::error_kind::ErrorKind;
 use crate::gerrit::Gerrit;
 use crate::related_changes_info::RelatedChangesInfo;
 
@@ -14,6 +16,10 @@ pub struct DependencyGraphBuilder<'a> {
     inner: DependencyGraph,
 
     gerrit: &'a mut Gerrit,
+
+    /// Set if the REST API couldn't be used to find related changes, in which case we fall back to
+    /// the (possibly out-of-date) dependencies reported over SSH.
+    rest_unavailable: bool,
 }
 
 impl<'a> DependencyGraphBuilder<'a> {
@@ -21,6 +27,7 @@ impl<'a> DependencyGraphBuilder<'a> {
         Self {
             inner: DependencyGraph::new(root),
             gerrit,
+            rest_unavailable: false,
         }
     }
 
@@ -35,16 +42,32 @@ impl<'a> DependencyGraphBuilder<'a> {
             .filter_unmerged(self.gerrit)
     }
 
-    fn related(&mut self, change: ChangeNumber) -> miette::Result<RelatedChangesInfo> {
-        self.gerrit
-            .related_changes(change, None)
-            .wrap_err("Failed to get related changes")
+    /// Get the changes related to a change, or `None` if we can't authenticate to the REST API.
+    fn related(&mut self, change: ChangeNumber) -> miette::Result<Option<RelatedChangesInfo>> {
+        if self.rest_unavailable {
+            return Ok(None);
+        }
+
+        match self.gerrit.related_changes(change, None) {
+            Ok(related) => Ok(Some(related)),
+            Err(error) if error_kind(&error) == Some(ErrorKind::Auth) => {
+                tracing::warn!(
+                    "Failed to get related changes from the REST API; falling back to dependencies from SSH, which may be out of date: {error}"
+                );
+                self.rest_unavailable = true;
+                Ok(None)
+            }
+            Err(error) => Err(error.wrap_err("Failed to get related changes")),
+        }
     }
 
+    /// Get the changes which depend on an outdated patchset of `change`.
+    ///
+    /// Returns `None` if these can't be determined because the REST API is unavailable.
     fn indirect_reverse_dependencies(
         &mut self,
         change: ChangeNumber,
-    ) -> miette::Result<BTreeSet<ChangeNumber>> {
+    ) -> miette::Result<Option<BTreeSet<ChangeNumber>>> {
         // If a change B depends on a change A, and A has a commit that B doesn't, in the web UI
         // you see this. On the page for A:
         //
@@ -79,7 +102,10 @@ impl<'a> DependencyGraphBuilder<'a> {
         //     if related(A) includes B
         //     and B depends on A
         //     then B is out of date with A
-        let related_changes = self.related(change)?.change_numbers();
+        let related_changes = match self.related(change)? {
+            Some(related) => related.change_numbers(),
+            None => return Ok(None),
+        };
 
         let mut indirect = BTreeSet::new();
         for related in related_changes {
@@ -91,7 +117,7 @@ impl<'a> DependencyGraphBuilder<'a> {
                 indirect.insert(related);
             }
         }
-        Ok(indirect)
+        Ok(Some(indirect))
     }
 
     pub fn traverse(gerrit: &'a mut Gerrit, root: ChangeNumber) -> miette::Result<Self> {
@@ -102,7 +128,13 @@ impl<'a> DependencyGraphBuilder<'a> {
         queue.push_front(root);
 
         while let Some(change) = queue.pop_back() {
-            let needed_by_indirect_numbers = builder.indirect_reverse_dependencies(change)?;
+            let needed_by_indirect_numbers = match builder.indirect_reverse_dependencies(change)? {
+                Some(indirect) => indirect,
+                None => {
+                    builder.inner.unverified.insert(change);
+                    BTreeSet::new()
+                }
+            };
             let dependencies = builder.dependencies(change)?;
             let depends_on_numbers = dependencies.depends_on_numbers();
             let needed_by_numbers = dependencies.needed_by_numbers();
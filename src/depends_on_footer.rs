@@ -0,0 +1,155 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::change_id::ChangeId;
+
+/// A `Depends-On:` trailer in a commit message.
+///
+/// These are used by Zuul to declare dependencies on changes in other repositories, which
+/// Gerrit's own dependency tracking doesn't know about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependsOnFooter {
+    /// A Change-Id, which may match changes in several projects.
+    ChangeId(ChangeId),
+    /// A URL for a change, like `https://gerrit.example.com/c/project/+/1234`.
+    Url(String),
+}
+
+impl DependsOnFooter {
+    /// Find the `Depends-On:` trailers in a commit message.
+    pub fn from_commit_message(commit_message: &str) -> Vec<Self> {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        RE.get_or_init(|| {
+            Regex::new(r"(?mi)^Depends-On:[[:blank:]]*(?P<value>\S+)[[:blank:]]*$")
+                .expect("Regex parses")
+        })
+        .captures_iter(commit_message)
+        .map(|captures| {
+            let value = &captures["value"];
+            if is_change_id(value) {
+                Self::ChangeId(ChangeId::new(value.to_owned()))
+            } else {
+                Self::Url(value.to_owned())
+            }
+        })
+        .collect()
+    }
+
+    /// A query to find the changes this refers to on the given Gerrit host.
+    ///
+    /// Returns `None` for URLs pointing to other hosts.
+    pub fn query(&self, host: &str) -> Option<String> {
+        match self {
+            DependsOnFooter::ChangeId(change_id) => Some(format!("change:{change_id}")),
+            DependsOnFooter::Url(url) => {
+                let (url_host, number) = parse_change_url(url)?;
+                if url_host == host {
+                    Some(format!("change:{number}"))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// The Gerrit host this refers to, for URLs.
+    ///
+    /// Returns `None` for Change-Ids, which refer to the current host.
+    pub fn host(&self) -> Option<&str> {
+        match self {
+            DependsOnFooter::ChangeId(_) => None,
+            DependsOnFooter::Url(url) => parse_change_url(url).map(|(host, _number)| host),
+        }
+    }
+}
+
+impl Display for DependsOnFooter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependsOnFooter::ChangeId(change_id) => change_id.fmt(f),
+            DependsOnFooter::Url(url) => url.fmt(f),
+        }
+    }
+}
+
+/// Parse a change URL into a host and change number.
+///
+/// Matches `https://HOST/c/PROJECT/+/1234`, `https://HOST/#/c/1234/`, and `https://HOST/1234`.
+fn parse_change_url(url: &str) -> Option<(&str, &str)> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let captures = RE
+        .get_or_init(|| {
+            Regex::new(
+                r"(?x)
+                ^
+                https?://
+                (?P<host>[^/:]+)
+                (:[0-9]+)?
+                /
+                .*?
+                (?P<number>[0-9]+)
+                /?
+                $
+                ",
+            )
+            .expect("Regex parses")
+        })
+        .captures(url)?;
+    Some((
+        captures.name("host")?.as_str(),
+        captures.name("number")?.as_str(),
+    ))
+}
+
+fn is_change_id(value: &str) -> bool {
+    value.len() == 41 && value.starts_with('I') && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_from_commit_message() {
+        let footers = DependsOnFooter::from_commit_message(indoc!(
+            "
+            Use the new API
+
+            Depends-On: https://gerrit.example.com/c/other/+/1234
+            depends-on: I0123456789abcdef0123456789abcdef01234567
+            Change-Id: I1111111111111111111111111111111111111111
+            "
+        ));
+        assert_eq!(
+            footers,
+            vec![
+                DependsOnFooter::Url("https://gerrit.example.com/c/other/+/1234".to_owned()),
+                DependsOnFooter::ChangeId(ChangeId::new(
+                    "I0123456789abcdef0123456789abcdef01234567".to_owned()
+                )),
+            ]
+        );
+
+        assert_eq!(
+            footers[0].query("gerrit.example.com").as_deref(),
+            Some("change:1234")
+        );
+        assert_eq!(footers[0].query("review.example.org"), None);
+        assert_eq!(footers[0].host(), Some("gerrit.example.com"));
+        assert_eq!(footers[1].host(), None);
+        assert_eq!(
+            footers[1].query("gerrit.example.com").as_deref(),
+            Some("change:I0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(
+            DependsOnFooter::Url("https://gerrit.example.com/#/c/5678/".to_owned())
+                .query("gerrit.example.com")
+                .as_deref(),
+            Some("change:5678")
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Display;
+
+use miette::miette;
+use miette::Diagnostic;
+
+use crate::change::Change;
+use crate::change_key::ChangeKey;
+use crate::error_kind::find_error;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+use crate::format_bulleted_list::format_bulleted_list;
+use crate::gerrit::Gerrit;
+use crate::query::QueryOptions;
+
+/// How many similar changes to suggest.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A change lookup which found nothing.
+#[derive(Debug)]
+pub struct ChangeNotFound {
+    pub key: ChangeKey,
+}
+
+impl Display for ChangeNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Didn't find change {}", self.key)
+    }
+}
+
+impl std::error::Error for ChangeNotFound {}
+
+impl Diagnostic for ChangeNotFound {}
+
+/// An error for a change that wasn't found.
+///
+/// This doesn't look for similar changes, because many lookups are expected to miss; see
+/// [`suggest_similar_changes`].
+pub fn change_not_found(key: &ChangeKey) -> miette::Report {
+    let error =
+        miette::Report::new(ChangeNotFound { key: key.clone() }).classify(ErrorKind::NotFound);
+    match key {
+        ChangeKey::Number(change) => error.for_change(*change),
+        _ => error,
+    }
+}
+
+/// Add suggestions to an error for a change that wasn't found, if the user typed the change on
+/// the command line.
+///
+/// `args` are the command-line arguments. Lookups `git-gr` makes on its own, like for the
+/// Change-Id of `HEAD`, are left alone.
+pub fn suggest_similar_changes(
+    gerrit: &Gerrit,
+    error: miette::Report,
+    args: &[String],
+) -> miette::Report {
+    let key = match find_error::<ChangeNotFound>(&error) {
+        Some(not_found) => not_found.key.clone(),
+        None => return error,
+    };
+    let needle = key.to_string();
+    if !args
+        .iter()
+        .any(|arg| arg == &needle || arg.split('/').any(|part| part == needle))
+    {
+        return error;
+    }
+
+    let suggestions = match similar_changes(gerrit, &key) {
+        Ok(suggestions) if !suggestions.is_empty() => suggestions,
+        Ok(_) => return error,
+        Err(error_finding) => {
+            tracing::debug!("Failed to find similar changes: {error_finding}");
+            return error;
+        }
+    };
+
+    let error = miette!(
+        help = format!(
+            "Did you mean:\n{}",
+            format_bulleted_list(suggestions.iter().map(|change| format!(
+                "{} {}",
+                change.number,
+                change.subject.as_deref().unwrap_or_default()
+            )))
+        ),
+        "{error}"
+    )
+    .classify(ErrorKind::NotFound);
+    match key {
+        ChangeKey::Number(change) => error.for_change(change),
+        _ => error,
+    }
+}
+
+/// Find changes the user may have meant when looking up `key`.
+///
+/// Free text is searched for in commit messages. Change numbers and IDs are compared against the
+/// user's open changes and recently-viewed changes, to catch typos.
+fn similar_changes(gerrit: &Gerrit, key: &ChangeKey) -> miette::Result<Vec<Change>> {
+    let needle = key.to_string();
+
+    if let ChangeKey::Query(query) = key {
+        if !query.contains(':') && !is_change_number(query) && !is_change_id_prefix(query) {
+            let text = query.replace('"', "");
+            return Ok(gerrit
+                .query(
+                    QueryOptions::new(format!(
+                        "message:\"{text}\" is:open limit:{MAX_SUGGESTIONS}"
+                    ))
+                    .current_patch_set(),
+                )?
+                .changes);
+        }
+    }
+
+    let mut candidates = gerrit
+        .query(QueryOptions::new("owner:self is:open".to_owned()).current_patch_set())?
+        .changes;
+    candidates.extend(gerrit.recent_changes()?);
+
+    let mut suggestions: Vec<Change> = Vec::new();
+    for candidate in candidates {
+        if suggestions.len() >= MAX_SUGGESTIONS {
+            break;
+        }
+        if suggestions
+            .iter()
+            .any(|change| change.number == candidate.number)
+        {
+            continue;
+        }
+        if is_similar(&needle, &candidate.number.to_string()) || is_similar(&needle, &candidate.id)
+        {
+            suggestions.push(candidate);
+        }
+    }
+    Ok(suggestions)
+}
+
+fn is_change_number(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_change_id_prefix(text: &str) -> bool {
+    text.len() > 1 && text.starts_with('I') && text[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Is `candidate` a likely typo of `needle`?
+///
+/// Change numbers may differ by one digit (or a swapped pair of digits). Change ID prefixes may
+/// differ by two characters.
+fn is_similar(needle: &str, candidate: &str) -> bool {
+    if is_change_number(needle) && is_change_number(candidate) {
+        // Every short number is "similar" to every other short number.
+        needle.len() >= 3 && strsim::damerau_levenshtein(needle, candidate) == 1
+    } else if is_change_id_prefix(needle) && is_change_id_prefix(candidate) {
+        // Gerrit matches change ID prefixes, so only compare that much.
+        let prefix = candidate.get(..needle.len()).unwrap_or(candidate);
+        needle.len() >= 7 && (1..=2).contains(&strsim::damerau_levenshtein(needle, prefix))
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_similar() {
+        assert!(is_similar("12345", "12346"));
+        assert!(is_similar("12345", "12354"));
+        assert!(is_similar("12345", "1234"));
+        assert!(!is_similar("12345", "12345"));
+        assert!(!is_similar("12345", "12367"));
+        assert!(!is_similar("12", "13"));
+
+        let id = "I0123456789abcdef0123456789abcdef01234567";
+        assert!(is_similar("I0123465", id));
+        assert!(is_similar("I0123456789abcdef0123456789abcdef01234576", id));
+        assert!(!is_similar("I0123456", id));
+        assert!(!is_similar("I01", "I02"));
+        assert!(!is_similar("I0123465", "12345"));
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt::Display;
+
+use calm_io::stdoutln;
+use itertools::Itertools;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+use reqwest::Method;
+
+use crate::account_info::AccountInfo;
+use crate::endpoint::Endpoint;
+use crate::gerrit::GerritGitRemote;
+use crate::git::Git;
+use crate::offline_queue;
+use crate::restack;
+use crate::restack_push;
+
+/// Collects and prints the results of diagnostic checks.
+#[derive(Default)]
+struct Report {
+    failures: usize,
+}
+
+impl Report {
+    fn pass(&self, check: &str, detail: impl Display) {
+        let _ = stdoutln!(
+            "{} {check}: {detail}",
+            "✔".if_supports_color(Stdout, |text| text.green())
+        );
+    }
+
+    fn fail(&mut self, check: &str, error: impl Display, fix: impl Display) {
+        self.failures += 1;
+        let _ = stdoutln!(
+            "{} {check}: {error}\n  {} {fix}",
+            "✗".if_supports_color(Stdout, |text| text.red()),
+            "Fix:".if_supports_color(Stdout, |text| text.bold()),
+        );
+    }
+
+    fn skip(&self, check: &str, reason: &str) {
+        let _ = stdoutln!(
+            "{}",
+            format!("- {check}: skipped ({reason})")
+                .if_supports_color(Stdout, |text| text.dimmed())
+        );
+    }
+}
+
+/// Format an error and its causes on one line.
+fn format_error(error: &miette::Report) -> String {
+    error.chain().join(": ")
+}
+
+/// Check the Gerrit setup for common problems, printing fixes for each failure.
+pub fn doctor(git: &Git, remote: Option<&str>) -> miette::Result<()> {
+    let mut report = Report::default();
+
+    let mut gerrit = match git.gerrit(remote) {
+        Ok(gerrit) => {
+            report.pass("Remote", format!("{} ({})", gerrit.remote, gerrit.host()));
+            Some(gerrit)
+        }
+        Err(error) => {
+            report.fail(
+                "Remote",
+                format_error(&error),
+                "Add a Gerrit remote like `git remote add gerrit ssh://USER@HOST:PORT/PROJECT`",
+            );
+            None
+        }
+    };
+
+    match &mut gerrit {
+        Some(gerrit) => check_gerrit(&mut report, gerrit),
+        None => {
+            for check in ["SSH", "REST API", "Cache"] {
+                report.skip(check, "no Gerrit remote");
+            }
+        }
+    }
+
+    check_commit_msg_hook(&mut report, git, gerrit.as_ref());
+    check_todos(&mut report, git);
+
+    if report.failures > 0 {
+        Err(miette::miette!(
+            "{} {} failed",
+            report.failures,
+            if report.failures == 1 {
+                "check"
+            } else {
+                "checks"
+            }
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_gerrit(report: &mut Report, gerrit: &mut GerritGitRemote) {
+    let ssh_ok = match gerrit.version() {
+        Ok(version) => {
+            report.pass("SSH", format!("Gerrit {version}"));
+            true
+        }
+        Err(error) => {
+            report.fail(
+                "SSH",
+                format_error(&error),
+                format!(
+                    "Check that `ssh -p {} {}@{} gerrit version` works and that your SSH key is added to Gerrit",
+                    gerrit.host().port,
+                    gerrit.host().username,
+                    gerrit.host().host,
+                ),
+            );
+            false
+        }
+    };
+
+    if ssh_ok {
+        // Don't report a cached response as a successful request.
+        gerrit.deattach_cache();
+        match gerrit.http_json::<AccountInfo>(Method::GET, &Endpoint::new("accounts/self")) {
+            Ok(account) => {
+                report.pass("REST API", format!("authenticated as {account}"));
+            }
+            Err(error) => {
+                report.fail(
+                    "REST API",
+                    format_error(&error),
+                    format!(
+                        "Check that https://{}/ is reachable and that your account can generate HTTP passwords",
+                        gerrit.host().host
+                    ),
+                );
+            }
+        }
+    } else {
+        report.skip("REST API", "SSH failed");
+    }
+
+    match gerrit.attach_cache().and_then(|()| gerrit.check_cache()) {
+        Ok(()) => {
+            report.pass("Cache", "writable");
+        }
+        Err(error) => {
+            report.fail(
+                "Cache",
+                format_error(&error),
+                "Check the permissions of your cache directory, or run `git-gr clear-cache`",
+            );
+        }
+    }
+}
+
+fn check_commit_msg_hook(report: &mut Report, git: &Git, gerrit: Option<&GerritGitRemote>) {
+    let hook = match git.git_path("hooks/commit-msg") {
+        Ok(hook) => hook,
+        Err(error) => {
+            report.fail(
+                "commit-msg hook",
+                format_error(&error),
+                "Run `git-gr doctor` inside a Git repository",
+            );
+            return;
+        }
+    };
+
+    if hook.exists() {
+        report.pass("commit-msg hook", format!("installed at `{hook}`"));
+    } else {
+        let host = gerrit
+            .map(|gerrit| gerrit.host().host.clone())
+            .unwrap_or_else(|| "GERRIT_HOST".to_owned());
+        report.fail(
+            "commit-msg hook",
+            format!("`{hook}` does not exist"),
+            format!("Run `git-gr init`, or: curl -Lo {hook} https://{host}/tools/hooks/commit-msg && chmod +x {hook}"),
+        );
+    }
+}
+
+fn check_todos(report: &mut Report, git: &Git) {
+    let checks = [
+        (
+            "Restack",
+            restack::todo_path(git),
+            "A restack is in progress; run `git-gr restack continue` or `git-gr restack abort`",
+        ),
+        (
+            "Restack push",
+            restack_push::push_path(git),
+            "Restacked changes haven't been pushed; run `git-gr restack push`",
+        ),
+        (
+            "Offline queue",
+            offline_queue::queue_path(git),
+            "Actions were queued while offline; run `git-gr sync --flush`",
+        ),
+    ];
+
+    for (check, path, fix) in checks {
+        match path {
+            Ok(path) => {
+                if path.exists() {
+                    report.fail(check, format!("pending todo at `{path}`"), fix);
+                } else {
+                    report.pass(check, "nothing pending");
+                }
+            }
+            Err(error) => {
+                report.fail(
+                    check,
+                    format_error(&error),
+                    "Run `git-gr doctor` inside a Git repository",
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+
+use crate::change::Change;
+use crate::comment_info::CommentInfo;
+use crate::comment_input::CommentInput;
+use crate::comment_threads::parse_location;
+use crate::gerrit::Gerrit;
+
+/// Create a draft inline comment on the current patchset of a change.
+///
+/// `location` is `PATH:LINE`, or `PATH` for a comment on the whole file.
+pub fn add_draft(
+    gerrit: &mut Gerrit,
+    change: &Change,
+    location: &str,
+    message: String,
+    unresolved: bool,
+) -> miette::Result<CommentInfo> {
+    let (path, line) = parse_location(location);
+    let patchset = change.patchset().patchset;
+    let draft = gerrit.create_draft(
+        change.number,
+        patchset,
+        &CommentInput {
+            path: path.to_owned(),
+            line,
+            in_reply_to: None,
+            message,
+            unresolved: Some(unresolved),
+        },
+    )?;
+    tracing::info!(
+        "Drafted a comment on {location}, patchset {patchset} of {}",
+        change.number.pretty(gerrit)?
+    );
+    Ok(draft)
+}
+
+/// Format draft comments, grouped by file and ordered by line.
+pub fn format_drafts(drafts: &BTreeMap<String, Vec<CommentInfo>>) -> String {
+    let mut output = String::new();
+    for (path, drafts) in drafts {
+        if drafts.is_empty() {
+            continue;
+        }
+        let _ = writeln!(
+            output,
+            "{}",
+            path.if_supports_color(Stdout, |path| path.bold())
+        );
+
+        let mut drafts = drafts.iter().collect::<Vec<_>>();
+        drafts.sort_by_key(|draft| (draft.line, &draft.updated));
+        for draft in drafts {
+            let _ = writeln!(
+                output,
+                "  {}, patchset {}{} [{}]:",
+                draft
+                    .line
+                    .map(|line| format!("Line {line}"))
+                    .unwrap_or_else(|| "File".to_owned()),
+                draft
+                    .patch_set
+                    .map(|patchset| patchset.to_string())
+                    .unwrap_or_else(|| "?".to_owned()),
+                match &draft.in_reply_to {
+                    Some(parent) => format!(" (reply to {parent})"),
+                    None => String::new(),
+                },
+                draft.id,
+            );
+            for line in draft.message.as_deref().unwrap_or_default().lines() {
+                let _ = writeln!(output, "    {line}");
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_format_drafts() {
+        let drafts = serde_json::from_str::<BTreeMap<String, Vec<CommentInfo>>>(indoc!(
+            r#"
+            {
+                "src/main.rs": [
+                    {
+                        "id": "d2",
+                        "patch_set": 2,
+                        "line": 12,
+                        "in_reply_to": "a",
+                        "message": "Done.",
+                        "updated": "2024-04-02 10:00:00.000000000"
+                    },
+                    {
+                        "id": "d1",
+                        "patch_set": 2,
+                        "line": 3,
+                        "message": "Typo here.\nAlso here.",
+                        "updated": "2024-04-02 11:00:00.000000000"
+                    }
+                ]
+            }
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(
+            format_drafts(&drafts),
+            indoc!(
+                "
+                src/main.rs
+                  Line 3, patchset 2 [d1]:
+                    Typo here.
+                    Also here.
+                  Line 12, patchset 2 (reply to a) [d2]:
+                    Done.
+                "
+            )
+        );
+    }
+}
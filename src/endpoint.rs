@@ -2,6 +2,22 @@ use clap::builder::StringValueParser;
 use clap::builder::TypedValueParser;
 use clap::builder::ValueParserFactory;
 use derive_more::{AsRef, Deref, DerefMut, Display, Into};
+use percent_encoding::utf8_percent_encode;
+use percent_encoding::AsciiSet;
+use percent_encoding::NON_ALPHANUMERIC;
+
+/// Characters to percent-encode in a URL path segment: everything except unreserved
+/// characters.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Percent-encode a string for use as a single URL path segment, including any `/`s.
+pub fn encode_path_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
 
 /// An API endpoint, with no leading `/`.
 #[derive(
@@ -0,0 +1,234 @@
+use std::fmt::Debug;
+use std::fmt::Display;
+
+use miette::Diagnostic;
+
+use crate::change_number::ChangeNumber;
+
+/// Broad categories of errors, which determine `git-gr`'s exit code.
+///
+/// | Code | Meaning                                         |
+/// | ---- | ----------------------------------------------- |
+/// | 0    | Success                                         |
+/// | 1    | Any other error                                 |
+/// | 2    | Invalid command-line arguments                  |
+/// | 3    | Authentication or permission failure            |
+/// | 4    | A change, comment, or other object wasn't found |
+/// | 5    | A conflict, like a restack stopped by conflicts |
+/// | 6    | Gerrit didn't respond in time                   |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Usage,
+    Auth,
+    NotFound,
+    Conflict,
+    Timeout,
+}
+
+impl ErrorKind {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorKind::Usage => 2,
+            ErrorKind::Auth => 3,
+            ErrorKind::NotFound => 4,
+            ErrorKind::Conflict => 5,
+            ErrorKind::Timeout => 6,
+        }
+    }
+
+    /// A short name for this kind of error, used in JSON error output.
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Usage => "usage",
+            ErrorKind::Auth => "auth",
+            ErrorKind::NotFound => "not-found",
+            ErrorKind::Conflict => "conflict",
+            ErrorKind::Timeout => "timeout",
+        }
+    }
+
+    /// Classify an HTTP error status.
+    pub fn from_http_status(status: u16) -> Option<Self> {
+        match status {
+            400 => Some(ErrorKind::Usage),
+            401 | 403 => Some(ErrorKind::Auth),
+            404 => Some(ErrorKind::NotFound),
+            409 => Some(ErrorKind::Conflict),
+            _ => None,
+        }
+    }
+}
+
+/// An error tagged with an [`ErrorKind`] or the changes it relates to.
+///
+/// Displays exactly like the wrapped error.
+pub struct ClassifiedError {
+    kind: Option<ErrorKind>,
+    changes: Vec<ChangeNumber>,
+    report: miette::Report,
+}
+
+impl Debug for ClassifiedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.report, f)
+    }
+}
+
+impl Display for ClassifiedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.report, f)
+    }
+}
+
+impl std::error::Error for ClassifiedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.report.source()
+    }
+}
+
+impl Diagnostic for ClassifiedError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.code()
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        self.report.severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.report.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        self.report.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.report.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.report.diagnostic_source()
+    }
+}
+
+/// Tag errors with an [`ErrorKind`] or related changes.
+pub trait Classify {
+    fn classify(self, kind: ErrorKind) -> Self;
+
+    fn for_change(self, change: ChangeNumber) -> Self;
+}
+
+impl Classify for miette::Report {
+    fn classify(self, kind: ErrorKind) -> Self {
+        tag(self, |error| error.kind = Some(kind))
+    }
+
+    fn for_change(self, change: ChangeNumber) -> Self {
+        tag(self, |error| {
+            if !error.changes.contains(&change) {
+                error.changes.push(change);
+            }
+        })
+    }
+}
+
+/// Update the tags on an error, reusing its [`ClassifiedError`] if it already has one.
+///
+/// Nested `ClassifiedError`s would hide each other from [`miette::Report::chain`], because
+/// their sources skip the errors they wrap.
+fn tag(mut report: miette::Report, f: impl FnOnce(&mut ClassifiedError)) -> miette::Report {
+    match report.downcast_mut::<ClassifiedError>() {
+        Some(error) => f(error),
+        None => {
+            let mut error = ClassifiedError {
+                kind: None,
+                changes: Vec::new(),
+                report,
+            };
+            f(&mut error);
+            report = miette::Report::new(error);
+        }
+    }
+    report
+}
+
+impl<T> Classify for miette::Result<T> {
+    fn classify(self, kind: ErrorKind) -> Self {
+        self.map_err(|error| error.classify(kind))
+    }
+
+    fn for_change(self, change: ChangeNumber) -> Self {
+        self.map_err(|error| error.for_change(change))
+    }
+}
+
+fn classified(error: &miette::Report) -> impl Iterator<Item = &ClassifiedError> {
+    error
+        .chain()
+        .filter_map(|error| error.downcast_ref::<ClassifiedError>())
+}
+
+/// Find an error of type `E` in an error's chain, including errors wrapped by [`Classify`].
+pub fn find_error<E: std::error::Error + 'static>(error: &miette::Report) -> Option<&E> {
+    error.chain().find_map(|cause| {
+        cause.downcast_ref::<E>().or_else(|| {
+            cause
+                .downcast_ref::<ClassifiedError>()
+                .and_then(|classified| find_error(&classified.report))
+        })
+    })
+}
+
+/// Find the outermost [`ErrorKind`] an error was tagged with.
+pub fn error_kind(error: &miette::Report) -> Option<ErrorKind> {
+    classified(error).find_map(|error| error.kind)
+}
+
+/// The changes an error was tagged with, outermost first.
+pub fn related_changes(error: &miette::Report) -> Vec<ChangeNumber> {
+    let mut changes = Vec::new();
+    for change in classified(error).flat_map(|error| &error.changes) {
+        if !changes.contains(change) {
+            changes.push(*change);
+        }
+    }
+    changes
+}
+
+/// The exit code for an error.
+pub fn exit_code(error: &miette::Report) -> u8 {
+    error_kind(error).map(ErrorKind::exit_code).unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use miette::miette;
+    use miette::Context;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code(&miette!("Oops")), 1);
+
+        let error = miette!(help = "Log in", "Unauthorized").classify(ErrorKind::Auth);
+        assert_eq!(exit_code(&error), 3);
+        assert_eq!(error.to_string(), "Unauthorized");
+        assert_eq!(error.help().unwrap().to_string(), "Log in");
+
+        let error = Err::<(), _>(miette!("Didn't find change 123"))
+            .classify(ErrorKind::NotFound)
+            .wrap_err("Failed to check out change")
+            .unwrap_err();
+        assert_eq!(exit_code(&error), 4);
+    }
+}
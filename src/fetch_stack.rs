@@ -0,0 +1,24 @@
+use calm_io::stdoutln;
+
+use crate::dependency_graph::DependencyGraph;
+use crate::gerrit::GerritGitRemote;
+
+/// Fetch every change in the `HEAD` commit's stack to its `refs/changes/*` ref.
+///
+/// Prints each change number and its local ref, from the bottom of the stack up. The working
+/// tree isn't touched.
+pub fn fetch_stack(gerrit: &mut GerritGitRemote) -> miette::Result<()> {
+    let git = gerrit.git();
+    let head = gerrit.get_change(git.change_id("HEAD")?)?;
+    let mut graph = DependencyGraph::traverse(gerrit, head.number)?;
+
+    for change in graph.changes_bottom_up()? {
+        let patchset = gerrit.get_change(change)?.patchset();
+        let commit = gerrit.fetch_cl(patchset)?;
+        let git_ref = patchset.git_ref();
+        git.set_ref(&git_ref, &commit)?;
+        let _ = stdoutln!("{change} {git_ref}");
+    }
+
+    Ok(())
+}
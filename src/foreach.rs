@@ -0,0 +1,143 @@
+use std::process::Command;
+
+use camino::Utf8PathBuf;
+use command_error::CommandExt;
+use fs_err as fs;
+use miette::miette;
+use miette::IntoDiagnostic;
+
+use crate::change_number::ChangeNumber;
+use crate::dependency_graph::DependencyGraph;
+use crate::gerrit::GerritGitRemote;
+use crate::git::Git;
+
+/// Run a command in a temporary worktree checked out at each change in the stack.
+///
+/// Changes are visited in dependency order, and every change is tried even if some fail.
+pub fn foreach(
+    gerrit: &mut GerritGitRemote,
+    whole_stack: bool,
+    command: &[String],
+) -> miette::Result<()> {
+    let changes = stack_changes(gerrit, whole_stack)?;
+    let mut worktree = StackWorktree::new(gerrit.git())?;
+
+    let mut results = Vec::<(ChangeNumber, bool)>::new();
+    for change in &changes {
+        let passed = worktree.run(gerrit, *change, command)?;
+        results.push((*change, passed));
+    }
+
+    worktree.remove()?;
+
+    let mut failed = 0;
+    for (change, passed) in &results {
+        if *passed {
+            tracing::info!("✔ {}", change.pretty(gerrit)?);
+        } else {
+            failed += 1;
+            tracing::error!("✗ {}", change.pretty(gerrit)?);
+        }
+    }
+
+    if failed > 0 {
+        Err(miette!(
+            "`{}` failed on {failed} of {} changes",
+            command.join(" "),
+            results.len()
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// The changes in the `HEAD` commit's stack, in dependency order.
+///
+/// Without `whole_stack`, these are the changes from the bottom of the stack up to `HEAD`'s
+/// change. With it, the changes that depend on `HEAD`'s change are included too.
+pub fn stack_changes(
+    gerrit: &mut GerritGitRemote,
+    whole_stack: bool,
+) -> miette::Result<Vec<ChangeNumber>> {
+    let head = gerrit.get_change(gerrit.git().change_id("HEAD")?)?.number;
+    let mut graph = DependencyGraph::traverse(gerrit, head)?;
+    if whole_stack {
+        return graph.changes_bottom_up();
+    }
+
+    let mut changes = vec![head];
+    let mut change = head;
+    while let Some(depends_on) = graph.depends_on(change) {
+        changes.push(depends_on);
+        change = depends_on;
+    }
+    changes.reverse();
+    Ok(changes)
+}
+
+/// A temporary worktree for checking out changes without disturbing the user's checkout.
+pub struct StackWorktree {
+    git: Git,
+    path: Utf8PathBuf,
+    added: bool,
+}
+
+impl StackWorktree {
+    pub fn new(git: Git) -> miette::Result<Self> {
+        let path = git.get_git_dir()?.join("git-gr-foreach");
+        if path.exists() {
+            // Left over from an interrupted run.
+            if let Err(error) = git.worktree_remove(&path) {
+                tracing::debug!("{error}");
+                fs::remove_dir_all(&path).into_diagnostic()?;
+                git.worktree_prune()?;
+            }
+        }
+        Ok(Self {
+            git,
+            path,
+            added: false,
+        })
+    }
+
+    /// Check out a change and run a command on it, returning whether the command succeeded.
+    pub fn run(
+        &mut self,
+        gerrit: &GerritGitRemote,
+        change: ChangeNumber,
+        command: &[String],
+    ) -> miette::Result<bool> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| miette!("No command given"))?;
+
+        let commit = gerrit.fetch_cl(gerrit.get_change(change)?.patchset())?;
+        if self.added {
+            self.git.worktree_checkout(&self.path, &commit)?;
+        } else {
+            self.git.worktree_add(&self.path, &commit)?;
+            self.added = true;
+        }
+
+        tracing::info!(
+            "Running `{}` on {}",
+            command.join(" "),
+            change.pretty(gerrit)?
+        );
+        let result = Command::new(program)
+            .args(args)
+            .current_dir(&self.path)
+            .status_checked();
+        if let Err(error) = &result {
+            tracing::debug!("{error}");
+        }
+        Ok(result.is_ok())
+    }
+
+    pub fn remove(self) -> miette::Result<()> {
+        if self.added {
+            self.git.worktree_remove(&self.path)?;
+        }
+        Ok(())
+    }
+}
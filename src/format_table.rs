@@ -0,0 +1,17 @@
+use comfy_table::Attribute;
+use comfy_table::Cell;
+use comfy_table::Table;
+
+/// Construct a borderless table with a bold, underlined header row.
+pub fn new_table<'a>(header: impl IntoIterator<Item = &'a str>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(comfy_table::presets::NOTHING)
+        .set_content_arrangement(comfy_table::ContentArrangement::Dynamic)
+        .set_header(header.into_iter().map(|cell| {
+            Cell::new(cell)
+                .add_attribute(Attribute::Bold)
+                .add_attribute(Attribute::Underlined)
+        }));
+    table
+}
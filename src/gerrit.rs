@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::io::BufWriter;
 use std::io::Write;
@@ -5,53 +7,138 @@ use std::ops::Deref;
 use std::ops::DerefMut;
 use std::process::Command;
 use std::sync::OnceLock;
+use std::time::Duration;
 
 use cached::IOCached;
 use camino::Utf8Path;
 use comfy_table::Attribute;
 use comfy_table::Cell;
+use comfy_table::Color;
+use comfy_table::Row;
+use comfy_table::Table;
 use command_error::CommandExt;
 use command_error::OutputContext;
 use fs_err::File;
 use miette::miette;
 use miette::Context;
 use miette::IntoDiagnostic;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+use parking_lot::Mutex;
 use regex::Regex;
 use reqwest::Method;
+use reqwest::StatusCode;
 use secrecy::ExposeSecret;
 use secrecy::SecretString;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
+use time::OffsetDateTime;
 use tracing::instrument;
 use utf8_command::Utf8Output;
 
+use crate::account_info::AccountInfo;
+use crate::age::StalledAfter;
+use crate::atom_feed::format_atom_feed;
+use crate::attention_set_info::AttentionSetInfo;
+use crate::attention_set_input::AttentionSetInput;
 use crate::cache::CacheKey;
 use crate::cache::CacheValue;
 use crate::cache::GerritCache;
 use crate::change::Change;
 use crate::change::TimestampFormat;
+use crate::change_id::ChangeId;
+use crate::change_index::ChangeIndex;
+use crate::change_info::ChangeInfo;
 use crate::change_key::ChangeKey;
 use crate::change_number::ChangeNumber;
+use crate::change_status::ChangeStatus;
+use crate::cherry_pick_input::CherryPickInput;
 use crate::cli::RestackContinue;
+use crate::comment_info::CommentInfo;
+use crate::comment_input::CommentInput;
 use crate::commit_hash::CommitHash;
+use crate::config::Config;
 use crate::current_exe::current_exe;
+use crate::current_patch_set::CurrentPatchSet;
+use crate::date_format::DateFormat;
+use crate::delimited::format_delimited;
 use crate::dependency_graph::DependencyGraph;
+use crate::depends_on_footer::DependsOnFooter;
+use crate::did_you_mean::change_not_found;
+use crate::endpoint::encode_path_segment;
 use crate::endpoint::Endpoint;
+use crate::error_kind::error_kind;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
 use crate::format_bulleted_list;
+use crate::format_table::new_table;
+use crate::gerrit_host::GerritHost;
+use crate::gerrit_pool::GerritPool;
 use crate::gerrit_project::GerritProject;
 use crate::git::Git;
+use crate::group_info::GroupInfo;
+use crate::hashtags_input::HashtagsInput;
+use crate::hyperlink::link_table_rows;
+use crate::hyperlink::Stream;
+use crate::label_definition_info::LabelDefinitionInfo;
+use crate::label_vote::LabelVote;
+use crate::notes::note_label;
+use crate::output_template::OutputTemplate;
 use crate::patchset::ChangePatchset;
+use crate::patchset::Patchset;
+use crate::permission_error::permission_help;
+use crate::project_access_info::ProjectAccessInfo;
+use crate::project_id::ProjectId;
+use crate::push_options::PushOptions;
+use crate::push_wip::PushWip;
 use crate::query::QueryOptions;
+use crate::query::QueryTableOptions;
 use crate::query_result::QueryResult;
+use crate::rate_limit::backoff_delay;
+use crate::rate_limit::is_rate_limit_message;
+use crate::rate_limit::RateLimiter;
+use crate::rate_limit::MAX_RETRIES;
+use crate::recording;
+use crate::recording::Interaction;
+use crate::recording::Recording;
 use crate::related_changes_info::RelatedChangesInfo;
+use crate::reminders::Reminders;
 use crate::restack::format_git_rebase_todo;
 use crate::restack::restack;
 use crate::restack::restack_abort;
 use crate::restack_push::restack_push;
-use crate::tmpdir::ssh_control_path;
+use crate::restack_summary::RestackSummary;
+use crate::review_input::ReviewInput;
+use crate::reviewer_info::AddReviewerResult;
+use crate::reviewer_info::ReviewerInfo;
+use crate::reviewer_input::ReviewerInput;
+use crate::ssh::SshCommand;
+use crate::submit_requirement_info::SubmitRequirementInfo;
+use crate::terminal_width::terminal_width;
+use crate::timeout::deadline_error;
+use crate::timeout::is_deadline_message;
+use crate::timeout::is_timeout_message;
+use crate::timeout::output_checked_with_timeout;
+use crate::timeout::succeeded_utf8;
+use crate::timeout::timeout_error;
+use crate::timezone::local_time_zone;
+use crate::transport::Transport;
+
+/// How many recently-fetched change numbers to remember, for "did you mean" suggestions.
+const MAX_RECENT_CHANGES: usize = 50;
 
 /// Gerrit SSH client wrapper.
 pub struct Gerrit {
     host: GerritProject,
+    ssh: SshCommand,
+    /// A server to send requests to instead of `host`, like a
+    /// [`MockServer`][crate::mock_server::MockServer].
+    server_override: Option<String>,
+    /// Whether SSH and HTTP interactions are being recorded or replayed.
+    recording: Option<Recording>,
+    rate_limiter: Mutex<RateLimiter>,
+    /// How to fetch patchsets.
+    transport: Transport,
 
     /// Password for the REST API.
     ///
@@ -72,9 +159,35 @@ impl Debug for Gerrit {
 
 impl Gerrit {
     pub fn new(host: GerritProject) -> miette::Result<Self> {
-        let cache = GerritCache::new(&host)?;
+        let server_override = std::env::var("GIT_GR_SERVER_OVERRIDE")
+            .ok()
+            .filter(|server| !server.is_empty());
+        let recording = Recording::from_env();
+        let mut ssh = match &server_override {
+            Some(server) => {
+                tracing::debug!(server, "Using server override");
+                SshCommand::mock(server)?
+            }
+            None => SshCommand::from_env()?.with_timeout(Config::global().timeout()),
+        };
+        if let Some(recording) = &recording {
+            tracing::debug!(?recording, "Recording or replaying interactions");
+            ssh = ssh.wrap(recording)?;
+        }
+        // Don't mix cached responses from the real server with the override, and make every
+        // request when recording or replaying.
+        let cache = if server_override.is_some() || recording.is_some() {
+            GerritCache::None
+        } else {
+            GerritCache::new(&host)?
+        };
         Ok(Self {
             host,
+            ssh,
+            server_override,
+            recording,
+            rate_limiter: Mutex::new(RateLimiter::from_env()),
+            transport: Transport::from_env()?,
             http_password: None,
             http_client: None,
             cache,
@@ -90,6 +203,9 @@ impl Gerrit {
     }
 
     pub fn attach_cache(&mut self) -> miette::Result<()> {
+        if self.server_override.is_some() || self.recording.is_some() {
+            return Ok(());
+        }
         self.cache.attach_cache(&self.host)?;
         Ok(())
     }
@@ -98,26 +214,53 @@ impl Gerrit {
         Git {}
     }
 
+    /// The Gerrit host and project.
+    pub fn host(&self) -> &GerritProject {
+        &self.host
+    }
+
+    /// The name of the Gerrit project.
+    pub fn project(&self) -> &str {
+        &self.host.project
+    }
+
+    /// Our username on the Gerrit server.
+    pub fn username(&self) -> &str {
+        &self.host.username
+    }
+
+    /// The name of the Gerrit project, encoded for REST API paths.
+    pub fn project_id(&self) -> ProjectId<'_> {
+        ProjectId::new(&self.host.project)
+    }
+
+    /// Get the Gerrit server version over SSH.
+    pub fn version(&self) -> miette::Result<String> {
+        let output = self
+            .run(["version"], "`gerrit version`", succeeded_utf8)?
+            .stdout;
+        Ok(output
+            .trim()
+            .strip_prefix("gerrit version ")
+            .unwrap_or(output.trim())
+            .to_owned())
+    }
+
+    /// Check that the disk cache can be written to.
+    pub fn check_cache(&self) -> miette::Result<()> {
+        let key = CacheKey::Api(Endpoint::new("git-gr-doctor"));
+        self.cache
+            .cache_set(key.clone(), CacheValue::Api(String::new()))
+            .into_diagnostic()?;
+        self.cache.cache_remove(&key).into_diagnostic()?;
+        Ok(())
+    }
+
     /// A `gerrit` command to run on the remote.
     pub fn command(&self, args: impl IntoIterator<Item = impl AsRef<str>>) -> Command {
-        let mut cmd = Command::new("ssh");
-        cmd.args([
-            // Persist sessions in the background to speed up subsequent `ssh` calls.
-            "-o",
-            "ControlMaster=auto",
-            "-o",
-            &format!(
-                "ControlPath={}",
-                ssh_control_path(&format!(
-                    "git-gr-ssh-{}-{}-{}",
-                    self.host.username, self.host.host, self.host.port
-                ))
-            ),
-            "-o",
-            "ControlPersist=120",
-            &self.host.connect_to(),
-            "gerrit",
-        ]);
+        self.rate_limiter.lock().acquire();
+        let mut cmd = self.ssh.command(&self.host);
+        cmd.arg("gerrit");
         cmd.args(
             args.into_iter()
                 .map(|arg| shell_words::quote(arg.as_ref()).into_owned()),
@@ -125,18 +268,56 @@ impl Gerrit {
         cmd
     }
 
+    /// Run a `gerrit` command on the remote, killing it if it runs past the `timeout` setting.
+    fn run<R>(
+        &self,
+        args: impl IntoIterator<Item = impl AsRef<str>>,
+        operation: &str,
+        succeeded: impl Fn(OutputContext<Utf8Output>) -> Result<R, command_error::Error>,
+    ) -> miette::Result<R> {
+        output_checked_with_timeout(self.command(args), self.ssh.timeout(), operation, succeeded)
+    }
+
+    /// Run a query, fetching more pages of results until there are no more.
+    ///
+    /// Gerrit caps how many changes a single query returns, even with `--no-limit`.
+    pub fn query_all_pages(&self, query: QueryOptions) -> miette::Result<QueryResult<Change>> {
+        let mut results = self.query(query.clone())?;
+        while results.stats.is_some_and(|stats| stats.more_changes) {
+            let page = self.query(query.next_page(results.changes.len()))?;
+            if page.changes.is_empty() {
+                break;
+            }
+            results.changes.extend(page.changes);
+            results.stats = page.stats;
+        }
+        Ok(results)
+    }
+
+    #[instrument(skip_all, fields(query = query.query_string()))]
     pub fn query(&self, query: QueryOptions) -> miette::Result<QueryResult<Change>> {
-        let key = CacheKey::Query(query.query_string().to_owned());
+        let key = CacheKey::Query(query.cache_key());
         if let Some(value) = self.cache.cache_get(&key).into_diagnostic()? {
-            return match value {
-                CacheValue::Query(result) => Ok(result),
-                _ => Err(miette!("Cached value isn't a set of changes: {value:?}")),
-            };
+            match value {
+                CacheValue::Query(result) => {
+                    if !self.any_stale(&result.changes)? {
+                        return Ok(result);
+                    }
+                    tracing::debug!("Cached query results include modified changes; refreshing");
+                }
+                _ => return Err(miette!("Cached value isn't a set of changes: {value:?}")),
+            }
         }
 
-        let result = self
-            .command(query.into_args())
-            .output_checked_as(|context: OutputContext<Utf8Output>| {
+        let deadline = Config::global().query_deadline();
+        let query = match deadline {
+            Some(deadline) => query.default_deadline(deadline),
+            None => query,
+        };
+        let args = query.into_args();
+        let mut attempt = 0;
+        let result = loop {
+            let result = self.run(&args, "`gerrit query`", |context| {
                 if context.status().success() {
                     match QueryResult::from_stdout(&context.output().stdout) {
                         Ok(value) => Ok(value),
@@ -145,8 +326,33 @@ impl Gerrit {
                 } else {
                     Err(context.error())
                 }
-            })
-            .into_diagnostic()?;
+            });
+            match result {
+                Err(error)
+                    if attempt < MAX_RETRIES && is_rate_limit_message(&error.to_string()) =>
+                {
+                    let delay = backoff_delay(attempt, None);
+                    tracing::warn!("Gerrit is rate limiting us; retrying in {delay:?}");
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => {
+                    break result.map_err(|error| {
+                        if let Some(deadline) =
+                            deadline.filter(|_| is_deadline_message(&error.to_string()))
+                        {
+                            tracing::debug!("Query exceeded its deadline: {error:?}");
+                            deadline_error(deadline)
+                        } else if is_timeout_message(&error.to_string()) {
+                            tracing::debug!("Query timed out: {error:?}");
+                            timeout_error("`gerrit query`", Config::global().timeout())
+                        } else {
+                            self.with_permission_help(error, None)
+                        }
+                    })?
+                }
+            }
+        };
 
         self.cache
             .cache_set(key, CacheValue::Query(result.clone()))
@@ -166,9 +372,43 @@ impl Gerrit {
             .cache_set(CacheKey::ChangeId(id), value)
             .into_diagnostic()?;
 
+        let mut recent = self.recent_change_numbers()?;
+        recent.retain(|recent| *recent != number);
+        recent.insert(0, number);
+        recent.truncate(MAX_RECENT_CHANGES);
+        self.cache
+            .cache_set(CacheKey::RecentChanges, CacheValue::RecentChanges(recent))
+            .into_diagnostic()?;
+
         Ok(())
     }
 
+    fn recent_change_numbers(&self) -> miette::Result<Vec<ChangeNumber>> {
+        match self
+            .cache
+            .cache_get(&CacheKey::RecentChanges)
+            .into_diagnostic()?
+        {
+            Some(CacheValue::RecentChanges(recent)) => Ok(recent),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Recently-fetched changes which are still cached, most recent first.
+    pub fn recent_changes(&self) -> miette::Result<Vec<Change>> {
+        let mut changes = Vec::new();
+        for number in self.recent_change_numbers()? {
+            if let Some(CacheValue::Change(change)) = self
+                .cache
+                .cache_get(&CacheKey::Change(number))
+                .into_diagnostic()?
+            {
+                changes.push(*change);
+            }
+        }
+        Ok(changes)
+    }
+
     pub fn get_change(&self, change: impl Into<ChangeKey>) -> miette::Result<Change> {
         let change: ChangeKey = change.into();
         if let Some(value) = self
@@ -177,39 +417,167 @@ impl Gerrit {
             .into_diagnostic()?
         {
             return match value {
-                CacheValue::Change(change) => Ok(*change),
+                CacheValue::Change(cached) => {
+                    if let ChangeKey::Id(id) = &change {
+                        self.check_project(id, &cached)?;
+                    }
+                    Ok(*cached)
+                }
                 _ => Err(miette!("Cached value isn't a change: {value:?}")),
             };
         }
 
         let query = change.to_string();
-        let result = self
+        let mut changes = self
             .query(
                 QueryOptions::new(query.clone())
                     .current_patch_set()
+                    .commit_message()
                     .dependencies()
                     .submit_records(),
             )?
-            .changes
-            .pop()
-            .ok_or_else(|| miette!("Didn't find change {query}"))?;
+            .changes;
+        if let ChangeKey::Id(_) = &change {
+            // Change-Ids are only unique within a project, so prefer changes in this one.
+            if let Some(index) = changes
+                .iter()
+                .position(|change| change.project == self.host.project)
+            {
+                let ours = changes.remove(index);
+                changes.push(ours);
+            }
+        }
+        let result = changes.pop().ok_or_else(|| change_not_found(&change))?;
+        if let ChangeKey::Id(id) = &change {
+            self.check_project(id, &result)?;
+        }
         self.cache_change(result.clone())?;
         Ok(result)
     }
 
+    /// Check that a change found by its Change-Id is in this project.
+    ///
+    /// Change-Ids are copied when commits are cherry-picked between repositories, so the
+    /// Change-Id in a local commit may belong to a change somewhere else on the same host.
+    fn check_project(&self, id: &ChangeId, change: &Change) -> miette::Result<()> {
+        if change.project == self.host.project {
+            return Ok(());
+        }
+        Err(miette!(
+            help = "This commit was probably cherry-picked from another repository. Remove its \
+                `Change-Id` trailer and run `git commit --amend --no-edit` so the `commit-msg` \
+                hook generates a new one.",
+            "Change-Id {id} belongs to change {} in project `{}`, not `{}`",
+            change.number,
+            change.project,
+            self.host.project,
+        ))
+    }
+
+    /// Get all of a change's patch sets, oldest first.
+    pub fn get_patch_sets(&self, change: ChangeNumber) -> miette::Result<Vec<CurrentPatchSet>> {
+        let change = self
+            .query(
+                QueryOptions::new(format!("change:{change}"))
+                    .current_patch_set()
+                    .patch_sets(),
+            )?
+            .changes
+            .pop()
+            .ok_or_else(|| change_not_found(&change.into()))?;
+        Ok(change.patch_sets)
+    }
+
+    /// Get a change, bypassing the cache so the status and current patchset are up to date.
+    pub fn get_change_fresh(&self, change: impl Into<ChangeKey>) -> miette::Result<Change> {
+        // Resolving a Change-Id to a number from the cache is fine; the number never changes.
+        let number = self.get_change(change)?.number;
+        self.forget_change(number)?;
+        self.get_change(number)
+    }
+
+    /// Remove a change from the cache, e.g. after modifying it.
+    pub fn forget_change(&self, change: ChangeNumber) -> miette::Result<()> {
+        if let Some(CacheValue::Change(cached)) = self
+            .cache
+            .cache_remove(&CacheKey::Change(change))
+            .into_diagnostic()?
+        {
+            self.cache
+                .cache_remove(&CacheKey::Query(cached.id.to_string()))
+                .into_diagnostic()?;
+            self.cache
+                .cache_remove(&CacheKey::ChangeId(cached.id))
+                .into_diagnostic()?;
+        }
+        // `get_change` also caches the query it makes.
+        self.cache
+            .cache_remove(&CacheKey::Query(change.to_string()))
+            .into_diagnostic()?;
+        // Other cached queries, like the ones for `git gr query`, may include the change too.
+        self.cache
+            .cache_set(CacheKey::Stale(change), CacheValue::Stale)
+            .into_diagnostic()?;
+        Ok(())
+    }
+
+    /// Have any of these changes been modified since they were cached?
+    fn any_stale(&self, changes: &[Change]) -> miette::Result<bool> {
+        for change in changes {
+            if self
+                .cache
+                .cache_get(&CacheKey::Stale(change.number))
+                .into_diagnostic()?
+                .is_some()
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Forget cached information about a change and the changes in its relation chain, whose
+    /// cached relation chains include the change's status.
+    pub fn forget_chain(&mut self, change: ChangeNumber) -> miette::Result<()> {
+        let mut changes = match self.related_changes(change, None) {
+            Ok(related) => related.change_numbers(),
+            Err(error) => {
+                tracing::debug!("Failed to get related changes for {change}: {error}");
+                Default::default()
+            }
+        };
+        changes.insert(change);
+        for change in changes {
+            self.forget_change(change)?;
+            self.cache
+                .cache_remove(&CacheKey::Api(
+                    self.related_changes_endpoint(change, "current"),
+                ))
+                .into_diagnostic()?;
+        }
+        Ok(())
+    }
+
     pub fn dependency_graph(&mut self, root: ChangeNumber) -> miette::Result<DependencyGraph> {
         DependencyGraph::traverse(self, root)
     }
 
     pub fn git_sequence_editor(&self) -> miette::Result<String> {
         let exe = current_exe()?;
-        let exe = shell_words::quote(exe.as_str());
+        // Git for Windows runs the sequence editor with `sh`, which wants forward slashes.
+        let exe = if cfg!(windows) {
+            exe.as_str().replace('\\', "/")
+        } else {
+            exe.into_string()
+        };
+        let exe = shell_words::quote(&exe);
         Ok(format!("{exe} restack write-todo"))
     }
 
     /// Fetch a CL.
     ///
     /// Returns the Git ref of the fetched patchset.
+    #[instrument(skip(self))]
     pub fn fetch_cl(&self, change: ChangePatchset) -> miette::Result<CommitHash> {
         if let Some(value) = self
             .cache
@@ -224,15 +592,32 @@ impl Gerrit {
 
         let git = self.git();
         git.command()
-            .args(["fetch", &self.host.remote_url(), &change.git_ref()])
+            .args(["fetch", &self.fetch_url(), &change.git_ref()])
             .output_checked_utf8()
             .into_diagnostic()?;
 
         // Seriously, `git fetch` doesn't write the fetched ref anywhere but `FETCH_HEAD`?
-        git.rev_parse("FETCH_HEAD")
+        let hash = git.rev_parse("FETCH_HEAD")?;
+
+        if let Err(error) = self.get_change(change.change).and_then(|info| {
+            ChangeIndex::update(&git, info.id, change.change, change.patchset, None)
+        }) {
+            tracing::debug!("Failed to update Change-Id index: {error}");
+        }
+
+        Ok(hash)
+    }
+
+    /// The URL to fetch patchsets from, depending on the configured [`Transport`].
+    fn fetch_url(&self) -> String {
+        match self.transport {
+            Transport::Ssh => self.host.remote_url(),
+            Transport::Http => self.host.http_remote_url(),
+        }
     }
 
     /// Checkout a CL.
+    #[instrument(skip(self))]
     pub fn checkout_cl(&self, change: ChangePatchset) -> miette::Result<()> {
         let git = self.git();
         git.command()
@@ -242,10 +627,6 @@ impl Gerrit {
         Ok(())
     }
 
-    pub fn restack_abort(&self) -> miette::Result<()> {
-        restack_abort(&self.git())
-    }
-
     pub fn up(&self) -> miette::Result<()> {
         let git = self.git();
         let change_id = git
@@ -339,52 +720,115 @@ impl Gerrit {
         Ok(())
     }
 
-    pub fn format_query_results(&self, query: String) -> miette::Result<comfy_table::Table> {
-        let results = self.query(
-            QueryOptions::new(query)
-                .current_patch_set()
-                .dependencies()
-                .submit_records()
-                .no_limit(),
-        )?;
+    /// Format the results of a query as a table.
+    ///
+    /// Subjects are truncated to fit the table in the terminal width unless `options.full` is set.
+    /// Change numbers link to the changes on terminals that support it.
+    pub fn format_query_results(
+        &self,
+        query: String,
+        options: QueryTableOptions,
+    ) -> miette::Result<String> {
+        let results = self.query_for_table(&query, options)?;
+        self.format_changes_table(&results.changes, options)
+    }
 
-        // TODO: Make this configurable.
-        let timestamp_format = if std::env::var("GIT_GR_24_HOUR_TIME")
-            .map(|value| !value.is_empty())
-            .unwrap_or(false)
-        {
-            TimestampFormat::TwentyFourHour
-        } else {
-            TimestampFormat::TwelveHour
-        };
+    /// Format changes as a table, like [`Self::format_query_results`].
+    pub fn format_changes_table(
+        &self,
+        changes: &[Change],
+        options: QueryTableOptions,
+    ) -> miette::Result<String> {
+        let timestamp_format = TimestampFormat::from_env();
+        let date_format = DateFormat::configured();
+        let time_zone = local_time_zone();
+        let now = OffsetDateTime::now_utc();
+        let reminders = Reminders::read(&self.git()).unwrap_or_else(|error| {
+            tracing::debug!("Failed to read reminders: {error:?}");
+            Default::default()
+        });
+
+        let mut header = vec![
+            "#", "Subject",
+            // 5-letter abbreviation doesn't make the column too wide for short
+            // timestamps like `21:30` or `04-30`.
+            "Updat", "Owner", "Status", "",
+        ];
+        if options.size {
+            header.push("Size");
+        }
+        if options.age {
+            header.push("Age");
+        }
+        if options.kind {
+            header.push("Kind");
+        }
+        if options.votes {
+            header.push("Votes");
+        }
+        if options.my_vote {
+            header.push("Mine");
+        }
+        let mut table = new_table(header);
 
-        let mut table = comfy_table::Table::new();
-        table
-            .load_preset(comfy_table::presets::NOTHING)
-            .set_content_arrangement(comfy_table::ContentArrangement::Dynamic)
-            .set_header(
-                [
-                    "#", "Subject",
-                    // 5-letter abbreviation doesn't make the column too wide for short
-                    // timestamps like `21:30` or `04-30`.
-                    "Updat", "Owner", "Status", "",
-                ]
-                .map(|cell| {
-                    Cell::new(cell)
-                        .add_attribute(Attribute::Bold)
-                        .add_attribute(Attribute::Underlined)
-                }),
-            );
+        if options.full {
+            table.set_content_arrangement(comfy_table::ContentArrangement::Disabled);
+        } else if let Some(width) = terminal_width() {
+            table.set_width(width.try_into().unwrap_or(u16::MAX));
+        }
 
-        for change in &results.changes {
-            table.add_row([
+        for change in changes {
+            let mine = change.owner.username == self.host.username;
+            let mut owner_cell = Cell::new(options.owner_format.format(&change.owner));
+            if mine {
+                // Highlight our own changes.
+                owner_cell = owner_cell.fg(Color::Cyan);
+            }
+
+            let mut last_updated_cell =
+                change.last_updated_cell(timestamp_format, &date_format, time_zone)?;
+            if change.is_stalled(now, options.stalled_after) {
+                last_updated_cell = last_updated_cell.fg(Color::Yellow);
+            }
+
+            let subject = change.subject.clone().unwrap_or_default();
+            let subject_cell = match reminders.changes.get(&change.number) {
+                // Mark changes we're late to review.
+                Some(reminder) if change.open && reminder.is_overdue(now) => {
+                    Cell::new(format!("⏰ {subject}")).fg(Color::Red)
+                }
+                _ => Cell::new(subject),
+            };
+
+            let mut cells = vec![
                 Cell::new(change.number).add_attribute(Attribute::Bold),
-                Cell::new(change.subject.clone().unwrap_or_default()),
-                change.last_updated_cell(timestamp_format)?,
-                Cell::new(change.owner.username.clone()),
+                subject_cell,
+                last_updated_cell,
+                owner_cell,
                 change.status_cell(),
                 change.ready_cell(),
-            ]);
+            ];
+            if options.size {
+                cells.push(change.size_cell());
+            }
+            if options.age {
+                cells.push(change.age_cell(now, options.stalled_after));
+            }
+            if options.kind {
+                cells.push(change.kind_cell());
+            }
+            if options.votes {
+                cells.push(change.votes_cell());
+            }
+            if options.my_vote {
+                cells.push(change.my_votes_cell(&self.host.username));
+            }
+            let mut row = Row::from(cells);
+            if !options.full {
+                // Truncate long subjects rather than wrapping them.
+                row.max_height(1);
+            }
+            table.add_row(row);
         }
 
         // Change numbers.
@@ -399,7 +843,131 @@ impl Gerrit {
             .expect("Third column exists")
             .set_cell_alignment(comfy_table::CellAlignment::Right);
 
-        Ok(table)
+        // Sizes and ages. Kinds and votes are left-aligned.
+        for column in 6..6 + usize::from(options.size) + usize::from(options.age) {
+            table
+                .column_mut(column)
+                .expect("Column exists")
+                .set_cell_alignment(comfy_table::CellAlignment::Right);
+        }
+
+        let urls = changes
+            .iter()
+            .map(|change| change.web_url())
+            .collect::<Vec<_>>();
+        let urls = urls.iter().map(|url| url.as_str()).collect::<Vec<_>>();
+        Ok(link_table_rows(table.to_string(), &urls, Stream::Stdout))
+    }
+
+    /// Format the results of a query with a user-supplied template, one change per line, with the
+    /// same changes in the same order as [`Self::format_query_results`].
+    pub fn format_query_template(
+        &self,
+        query: String,
+        options: QueryTableOptions,
+        template: &OutputTemplate,
+    ) -> miette::Result<String> {
+        let results = self.query_for_table(&query, options)?;
+        template.render_changes(&results.changes)
+    }
+
+    /// Format the results of a query as an Atom feed, with the same changes in the same order as
+    /// [`Self::format_query_results`].
+    pub fn format_query_feed(
+        &self,
+        query: String,
+        options: QueryTableOptions,
+    ) -> miette::Result<String> {
+        let results = self.query_for_table(&query, options)?;
+        let id = format!(
+            "https://{}/q/{}",
+            self.host.host,
+            encode_path_segment(&query)
+        );
+        format_atom_feed(
+            &format!("Gerrit: {query}"),
+            &id,
+            &results.changes,
+            OffsetDateTime::now_utc(),
+        )
+    }
+
+    /// Format the results of a query as delimited text, like CSV or TSV, with the same changes in
+    /// the same order as [`Self::format_query_results`].
+    pub fn format_query_delimited(
+        &self,
+        query: String,
+        options: QueryTableOptions,
+        delimiter: char,
+    ) -> miette::Result<String> {
+        let results = self.query_for_table(&query, options)?;
+        format_delimited(&results.changes, options, self.username(), delimiter)
+    }
+
+    /// Run a query for [`Self::format_query_results`].
+    pub fn query_for_table(
+        &self,
+        query: &str,
+        options: QueryTableOptions,
+    ) -> miette::Result<QueryResult<Change>> {
+        let mut query_options = QueryOptions::new(query.to_owned())
+            .current_patch_set()
+            .dependencies()
+            .submit_records()
+            .start(options.start);
+        query_options = match options.limit {
+            Some(limit) => query_options.limit(limit),
+            None => query_options.no_limit(),
+        };
+        if options.my_vote {
+            // Find our votes on earlier patchsets too.
+            query_options = query_options.all_approvals();
+        }
+        let mut results = if options.all {
+            self.query_all_pages(query_options)?
+        } else {
+            self.query(query_options)?
+        };
+
+        if results.stats.is_some_and(|stats| stats.more_changes) {
+            tracing::info!(
+                "More changes are available; use `--start {}` or `--all` to see them",
+                options.start + results.changes.len()
+            );
+        }
+
+        if options.review_priority {
+            self.sort_by_review_priority(query, &mut results.changes)?;
+        }
+
+        Ok(results)
+    }
+
+    /// Sort changes so the most actionable ones for a reviewer come first.
+    ///
+    /// Changes where we're in the attention set come first, then changes which have been waiting
+    /// the longest, then the smallest changes.
+    fn sort_by_review_priority(&self, query: &str, changes: &mut [Change]) -> miette::Result<()> {
+        // `gerrit query` doesn't include the attention set, so search for it separately.
+        let attention = self
+            .query(
+                QueryOptions::new(format!("({query}) attention:self"))
+                    .current_patch_set()
+                    .no_limit(),
+            )?
+            .changes
+            .into_iter()
+            .map(|change| change.number)
+            .collect::<BTreeSet<_>>();
+
+        changes.sort_by_key(|change| {
+            (
+                !attention.contains(&change.number),
+                change.last_updated,
+                change.current_patch_set.size_insertions + change.current_patch_set.size_deletions,
+            )
+        });
+        Ok(())
     }
 
     pub fn rebase_interactive(&mut self, onto: &str) -> miette::Result<()> {
@@ -417,13 +985,21 @@ impl Gerrit {
         }
 
         let output = self
-            .command([
-                "set-account",
-                &self.host.username,
-                "--generate-http-password",
-            ])
-            .output_checked_utf8()
-            .into_diagnostic()?
+            .run(
+                [
+                    "set-account",
+                    &self.host.username,
+                    "--generate-http-password",
+                ],
+                "`gerrit set-account`",
+                succeeded_utf8,
+            )
+            .map_err(|error| match error_kind(&error) {
+                Some(ErrorKind::Timeout) => error,
+                _ => error
+                    .wrap_err("Failed to generate Gerrit HTTP password")
+                    .classify(ErrorKind::Auth),
+            })?
             .stdout;
 
         static RE: OnceLock<Regex> = OnceLock::new();
@@ -454,68 +1030,204 @@ impl Gerrit {
         self.generate_http_password()?;
 
         if self.http_client.is_none() {
-            self.http_client = Some(reqwest::blocking::Client::new());
+            self.http_client = Some(
+                reqwest::blocking::Client::builder()
+                    .timeout(Config::global().timeout())
+                    .build()
+                    .into_diagnostic()?,
+            );
         }
 
         Ok(())
     }
 
+    /// Make a request to the REST API.
+    ///
+    /// `GET` responses are cached.
     #[instrument()]
     pub fn http_request(&mut self, method: Method, endpoint: &Endpoint) -> miette::Result<String> {
         let key = CacheKey::Api(endpoint.to_owned());
-        if let Some(value) = self.cache.cache_get(&key).into_diagnostic()? {
-            return match value {
-                CacheValue::Api(response) => Ok(response),
-                _ => Err(miette!("Cached value isn't an API response: {value:?}")),
-            };
+        if method == Method::GET {
+            if let Some(value) = self.cache.cache_get(&key).into_diagnostic()? {
+                return match value {
+                    CacheValue::Api(response) => Ok(response),
+                    _ => Err(miette!("Cached value isn't an API response: {value:?}")),
+                };
+            }
+        }
+
+        let body = self.http_send(method.clone(), endpoint, None)?;
+
+        if method == Method::GET {
+            self.cache
+                .cache_set(key, CacheValue::Api(body.clone()))
+                .into_diagnostic()?;
+        }
+
+        Ok(body)
+    }
+
+    /// Make a request to the REST API with a JSON body.
+    ///
+    /// Responses are not cached.
+    pub fn http_send_json<B: Serialize + Debug>(
+        &mut self,
+        method: Method,
+        endpoint: &Endpoint,
+        body: &B,
+    ) -> miette::Result<String> {
+        tracing::debug!(%method, %endpoint, ?body, "Sending request");
+        let body = serde_json::to_vec(body).into_diagnostic()?;
+        self.http_send(method, endpoint, Some(("application/json", body)))
+    }
+
+    /// Make a request to the REST API with a raw body.
+    ///
+    /// Responses are not cached.
+    pub fn http_send_raw(
+        &mut self,
+        method: Method,
+        endpoint: &Endpoint,
+        body: Vec<u8>,
+    ) -> miette::Result<String> {
+        tracing::debug!(%method, %endpoint, "Sending request");
+        self.http_send(method, endpoint, Some(("application/octet-stream", body)))
+    }
+
+    /// Send a request to the REST API, with an optional content type and body.
+    #[instrument(skip_all, fields(%method, %endpoint))]
+    fn http_send(
+        &mut self,
+        method: Method,
+        endpoint: &Endpoint,
+        body: Option<(&str, Vec<u8>)>,
+    ) -> miette::Result<String> {
+        let request = recording::http_request(
+            &method,
+            endpoint,
+            body.as_ref().map(|(_, bytes)| bytes.as_slice()),
+        );
+        if let Some(recording) = &self.recording {
+            if let Some(interaction) = recording.replay(&request)? {
+                return self.http_result(
+                    &method,
+                    endpoint,
+                    interaction.status as u16,
+                    interaction.stdout,
+                );
+            }
         }
 
         self.http_ensure()?;
 
-        let url = self.host.endpoint(endpoint);
+        let url = match &self.server_override {
+            Some(server) => format!("{}/a/{endpoint}", server.trim_end_matches('/')),
+            None => self.host.endpoint(endpoint),
+        };
 
-        let response = self
-            .http_client
-            .as_ref()
-            .expect("http_ensure should construct an HTTP client")
-            .request(method.clone(), &url)
-            .basic_auth(
-                &self.host.username,
-                self.http_password
-                    .as_ref()
-                    .map(|password| password.expose_secret()),
-            )
-            .send()
+        let mut attempt = 0;
+        let response = loop {
+            self.rate_limiter.lock().acquire();
+
+            let request = self
+                .http_client
+                .as_ref()
+                .expect("http_ensure should construct an HTTP client")
+                .request(method.clone(), &url)
+                .basic_auth(
+                    &self.host.username,
+                    self.http_password
+                        .as_ref()
+                        .map(|password| password.expose_secret()),
+                );
+
+            let request = match &body {
+                Some((content_type, bytes)) => request
+                    .header(reqwest::header::CONTENT_TYPE, *content_type)
+                    .body(bytes.clone()),
+                None => request,
+            };
+
+            let response = match request.send() {
+                Err(error) if error.is_timeout() => {
+                    return Err(timeout_error(
+                        &format!("`{method} {endpoint}`"),
+                        Config::global().timeout(),
+                    ));
+                }
+                response => response
+                    .into_diagnostic()
+                    .wrap_err_with(|| format!("Failed to {method} {url}"))?,
+            };
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .map(Duration::from_secs);
+                let delay = backoff_delay(attempt, retry_after);
+                tracing::warn!("Gerrit is rate limiting us; retrying in {delay:?}");
+                std::thread::sleep(delay);
+                attempt += 1;
+            } else {
+                break response;
+            }
+        };
+
+        let status = response.status().as_u16();
+        let body = response
+            .text()
             .into_diagnostic()
-            .wrap_err_with(|| format!("Failed to {method} {url}"))?;
+            .wrap_err_with(|| format!("Failed to get response body for {url}"))?;
+
+        if let Some(recording) = &self.recording {
+            recording.record(Interaction {
+                request,
+                status: status.into(),
+                stdout: body.clone(),
+                stderr: String::new(),
+            })?;
+        }
 
-        if response.status().is_success() {
-            let body = response
-                .text()
-                .into_diagnostic()
-                .wrap_err_with(|| format!("Failed to get response body for {url}"))?;
+        self.http_result(&method, endpoint, status, body)
+    }
 
-            let body = body
+    /// Convert an HTTP response into a result.
+    fn http_result(
+        &self,
+        method: &Method,
+        endpoint: &Endpoint,
+        status: u16,
+        body: String,
+    ) -> miette::Result<String> {
+        if (200..300).contains(&status) {
+            Ok(body
                 .strip_prefix(")]}'\n")
                 .map(|body| body.to_owned())
-                .unwrap_or(body);
-
-            self.cache
-                .cache_set(key, CacheValue::Api(body.clone()))
-                .into_diagnostic()?;
-
-            Ok(body)
+                .unwrap_or(body))
         } else {
-            Err(miette!(
-                "{method} {url} failed with status {}:\n{}",
-                response.status(),
-                response
-                    .text()
-                    .unwrap_or_else(|error| { format!("Failed to get response body: {error}") })
+            Err(self.with_permission_help(
+                miette!("{method} {endpoint} failed with status {status}:\n{body}"),
+                Some(status),
             ))
         }
     }
 
+    /// Add guidance to an error if it was caused by missing permissions, and classify it by
+    /// its HTTP status.
+    fn with_permission_help(&self, error: miette::Report, status: Option<u16>) -> miette::Report {
+        let kind = status.and_then(ErrorKind::from_http_status);
+        match permission_help(self.project(), &error.to_string(), status) {
+            Some(help) => miette!(help = help, "{error}").classify(ErrorKind::Auth),
+            None => match kind {
+                Some(kind) => error.classify(kind),
+                None => error,
+            },
+        }
+    }
+
     pub fn http_json<T: DeserializeOwned>(
         &mut self,
         method: Method,
@@ -527,6 +1239,100 @@ impl Gerrit {
             .wrap_err_with(|| format!("Failed to deserialize JSON from HTTP request to {endpoint}"))
     }
 
+    /// List the groups visible to the current user.
+    ///
+    /// If `member` is given, list the groups that user is a member of instead.
+    pub fn groups(&mut self, member: Option<&str>) -> miette::Result<Vec<GroupInfo>> {
+        match member {
+            Some(member) => self.http_json::<Vec<GroupInfo>>(
+                Method::GET,
+                &Endpoint::new(&format!("accounts/{}/groups", encode_path_segment(member))),
+            ),
+            None => Ok(self
+                .http_json::<BTreeMap<String, GroupInfo>>(Method::GET, &Endpoint::new("groups/"))?
+                .into_iter()
+                .map(|(name, mut group)| {
+                    group.name.get_or_insert(name);
+                    group
+                })
+                .collect()),
+        }
+    }
+
+    /// List the members of a group, including members of included groups.
+    pub fn group_members(&mut self, group: &str) -> miette::Result<Vec<AccountInfo>> {
+        self.http_json::<Vec<AccountInfo>>(
+            Method::GET,
+            &Endpoint::new(&format!(
+                "groups/{}/members/?recursive",
+                encode_path_segment(group)
+            )),
+        )
+        .wrap_err_with(|| format!("Failed to list members of group {group}"))
+    }
+
+    pub fn format_groups(&mut self, member: Option<&str>) -> miette::Result<Table> {
+        let groups = self.groups(member)?;
+
+        let mut table = new_table(["Group", "Owner", "Description"]);
+        for group in groups {
+            table.add_row([
+                Cell::new(group.name.unwrap_or(group.id)).add_attribute(Attribute::Bold),
+                Cell::new(group.owner.unwrap_or_default()),
+                Cell::new(group.description.unwrap_or_default()),
+            ]);
+        }
+
+        Ok(table)
+    }
+
+    pub fn format_group_members(&mut self, group: &str) -> miette::Result<Table> {
+        let members = self.group_members(group)?;
+
+        let mut table = new_table(["Username", "Name", "Email"]);
+        for member in members {
+            table.add_row([
+                Cell::new(member.username.unwrap_or_default()).add_attribute(Attribute::Bold),
+                Cell::new(member.name.unwrap_or_default()),
+                Cell::new(member.email.unwrap_or_default()),
+            ]);
+        }
+
+        Ok(table)
+    }
+
+    /// Get the label definitions for this project, including inherited labels.
+    pub fn project_labels(&mut self) -> miette::Result<Vec<LabelDefinitionInfo>> {
+        self.http_json::<Vec<LabelDefinitionInfo>>(
+            Method::GET,
+            &Endpoint::new(&format!("projects/{}/labels/?inherited", self.project_id())),
+        )
+    }
+
+    /// Get the access rights defined in a project.
+    pub fn project_access(&mut self, project: &str) -> miette::Result<ProjectAccessInfo> {
+        self.http_json::<ProjectAccessInfo>(
+            Method::GET,
+            &Endpoint::new(&format!("projects/{}/access", ProjectId::new(project))),
+        )
+    }
+
+    /// Get the submit requirements for this project, including inherited requirements.
+    pub fn project_submit_requirements(&mut self) -> miette::Result<Vec<SubmitRequirementInfo>> {
+        self.http_json::<Vec<SubmitRequirementInfo>>(
+            Method::GET,
+            &Endpoint::new(&format!(
+                "projects/{}/submit_requirements?inherited",
+                self.project_id()
+            )),
+        )
+    }
+
+    /// The REST API endpoint for a change, like `changes/PROJECT~123{path}`.
+    pub fn change_endpoint(&self, change: ChangeNumber, path: &str) -> Endpoint {
+        Endpoint::new(&format!("changes/{}~{change}{path}", self.project_id()))
+    }
+
     pub fn related_changes(
         &mut self,
         change_number: ChangeNumber,
@@ -537,12 +1343,319 @@ impl Gerrit {
             .unwrap_or_else(|| "current".to_owned());
         self.http_json::<RelatedChangesInfo>(
             Method::GET,
-            &Endpoint::new(&format!(
-                "changes/{}~{change_number}/revisions/{revision}/related?o=SUBMITTABLE",
-                self.host.project
-            )),
+            &self.related_changes_endpoint(change_number, &revision),
         )
     }
+
+    fn related_changes_endpoint(&self, change: ChangeNumber, revision: &str) -> Endpoint {
+        self.change_endpoint(
+            change,
+            &format!("/revisions/{revision}/related?o=SUBMITTABLE"),
+        )
+    }
+
+    /// Get a change from the REST API.
+    ///
+    /// `options` are passed as `o=` query parameters, like `MESSAGES` or `ALL_REVISIONS`.
+    pub fn change_info(
+        &mut self,
+        change: ChangeNumber,
+        options: &[&str],
+    ) -> miette::Result<ChangeInfo> {
+        let query = options
+            .iter()
+            .map(|option| format!("o={option}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        self.http_json::<ChangeInfo>(
+            Method::GET,
+            &self.change_endpoint(change, &format!("?{query}")),
+        )
+    }
+
+    /// Post votes and/or a message on the current patchset of a change.
+    pub fn set_review(&mut self, change: ChangeNumber, review: &ReviewInput) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/revisions/current/review");
+        self.http_send_json(Method::POST, &endpoint, review)?;
+        Ok(())
+    }
+
+    /// Create a draft inline comment on a patchset.
+    pub fn create_draft(
+        &mut self,
+        change: ChangeNumber,
+        patchset: Patchset,
+        comment: &CommentInput,
+    ) -> miette::Result<CommentInfo> {
+        let endpoint = self.change_endpoint(change, &format!("/revisions/{patchset}/drafts"));
+        let response = self.http_send_json(Method::PUT, &endpoint, comment)?;
+        serde_json::from_str(&response)
+            .into_diagnostic()
+            .wrap_err("Failed to deserialize draft comment")
+    }
+
+    /// Get our draft comments on a change, keyed by path.
+    pub fn drafts(
+        &mut self,
+        change: ChangeNumber,
+    ) -> miette::Result<BTreeMap<String, Vec<CommentInfo>>> {
+        self.http_json(Method::GET, &self.change_endpoint(change, "/drafts"))
+    }
+
+    /// Publish all of our draft comments on a change, on every patchset.
+    pub fn publish_drafts(&mut self, change: ChangeNumber) -> miette::Result<()> {
+        self.set_review(
+            change,
+            &ReviewInput {
+                drafts: Some("PUBLISH_ALL_REVISIONS".to_owned()),
+                ..Default::default()
+            },
+        )?;
+        self.forget_change(change)
+    }
+
+    /// Set or clear the topic on a change.
+    pub fn set_topic(&mut self, change: ChangeNumber, topic: Option<&str>) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/topic");
+        match topic {
+            Some(topic) => {
+                self.http_send_json(
+                    Method::PUT,
+                    &endpoint,
+                    &serde_json::json!({ "topic": topic }),
+                )?;
+            }
+            None => {
+                self.http_send(Method::DELETE, &endpoint, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Abandon a change.
+    pub fn abandon(&mut self, change: ChangeNumber, message: Option<&str>) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/abandon");
+        self.http_send_json(
+            Method::POST,
+            &endpoint,
+            &serde_json::json!({ "message": message }),
+        )?;
+        self.forget_chain(change)
+    }
+
+    /// Rebase a change on the server, creating a new patchset.
+    ///
+    /// Without a `base`, the change is rebased on the latest patchset of the change it depends
+    /// on, or on the tip of its branch. A `base` can be a change number, a commit, or a ref.
+    pub fn rebase(&mut self, change: ChangeNumber, base: Option<&str>) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/rebase");
+        self.http_send_json(
+            Method::POST,
+            &endpoint,
+            &serde_json::json!({ "base": base }),
+        )
+        .for_change(change)
+        .wrap_err_with(|| format!("Failed to rebase {change}"))?;
+        self.forget_chain(change)
+    }
+
+    /// Cherry-pick the current patchset of a change onto another branch, creating a new change.
+    ///
+    /// Returns the new change.
+    pub fn cherry_pick(
+        &mut self,
+        change: ChangeNumber,
+        input: &CherryPickInput,
+    ) -> miette::Result<ChangeInfo> {
+        let endpoint = self.change_endpoint(change, "/revisions/current/cherrypick");
+        let response = self
+            .http_send_json(Method::POST, &endpoint, input)
+            .for_change(change)
+            .wrap_err_with(|| format!("Failed to cherry-pick {change} to {}", input.destination))?;
+        serde_json::from_str(&response)
+            .into_diagnostic()
+            .wrap_err("Failed to deserialize cherry-picked change")
+    }
+
+    /// Mark a change as work-in-progress, or as ready for review.
+    pub fn set_wip(
+        &mut self,
+        change: ChangeNumber,
+        wip: bool,
+        message: Option<&str>,
+    ) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, if wip { "/wip" } else { "/ready" });
+        self.http_send_json(
+            Method::POST,
+            &endpoint,
+            &serde_json::json!({ "message": message }),
+        )?;
+        self.forget_change(change)
+    }
+
+    /// Restore an abandoned change.
+    pub fn restore(&mut self, change: ChangeNumber, message: Option<&str>) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/restore");
+        self.http_send_json(
+            Method::POST,
+            &endpoint,
+            &serde_json::json!({ "message": message }),
+        )?;
+        self.forget_chain(change)
+    }
+
+    /// Add and remove hashtags on a change.
+    pub fn set_hashtags(
+        &mut self,
+        change: ChangeNumber,
+        hashtags: &HashtagsInput,
+    ) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/hashtags");
+        self.http_send_json(Method::POST, &endpoint, hashtags)?;
+        Ok(())
+    }
+
+    /// Get the reviewers and CCs on a change.
+    pub fn reviewers(&mut self, change: ChangeNumber) -> miette::Result<Vec<ReviewerInfo>> {
+        self.http_json(Method::GET, &self.change_endpoint(change, "/reviewers/"))
+    }
+
+    /// Add a reviewer or CC to a change, by username, email, or group name.
+    pub fn add_reviewer(
+        &mut self,
+        change: ChangeNumber,
+        reviewer: &ReviewerInput,
+    ) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/reviewers");
+        let response = self.http_send_json(Method::POST, &endpoint, reviewer)?;
+        let result: AddReviewerResult = serde_json::from_str(&response)
+            .into_diagnostic()
+            .wrap_err("Failed to deserialize add reviewer result")?;
+        if let Some(error) = result.error {
+            return Err(miette!(
+                "Failed to add {} to {change}: {error}",
+                reviewer.reviewer
+            ));
+        }
+        self.forget_reviewers(change)
+    }
+
+    /// Remove a reviewer or CC from a change.
+    pub fn remove_reviewer(&mut self, change: ChangeNumber, account: &str) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(
+            change,
+            &format!("/reviewers/{}", encode_path_segment(account)),
+        );
+        self.http_send(Method::DELETE, &endpoint, None)?;
+        self.forget_reviewers(change)
+    }
+
+    /// Get the accounts in a change's attention set.
+    pub fn attention_set(&mut self, change: ChangeNumber) -> miette::Result<Vec<AttentionSetInfo>> {
+        self.http_json(Method::GET, &self.change_endpoint(change, "/attention"))
+    }
+
+    /// Add an account to a change's attention set.
+    pub fn add_to_attention_set(
+        &mut self,
+        change: ChangeNumber,
+        input: &AttentionSetInput,
+    ) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/attention");
+        self.http_send_json(Method::POST, &endpoint, input)?;
+        self.forget_attention_set(change)
+    }
+
+    /// Remove an account from a change's attention set.
+    pub fn remove_from_attention_set(
+        &mut self,
+        change: ChangeNumber,
+        input: &AttentionSetInput,
+    ) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(
+            change,
+            &format!("/attention/{}/delete", encode_path_segment(&input.user)),
+        );
+        self.http_send_json(
+            Method::POST,
+            &endpoint,
+            &serde_json::json!({ "reason": input.reason }),
+        )?;
+        self.forget_attention_set(change)
+    }
+
+    fn forget_attention_set(&mut self, change: ChangeNumber) -> miette::Result<()> {
+        self.cache
+            .cache_remove(&CacheKey::Api(self.change_endpoint(change, "/attention")))
+            .into_diagnostic()?;
+        self.forget_change(change)
+    }
+
+    fn forget_reviewers(&mut self, change: ChangeNumber) -> miette::Result<()> {
+        self.cache
+            .cache_remove(&CacheKey::Api(self.change_endpoint(change, "/reviewers/")))
+            .into_diagnostic()?;
+        self.forget_change(change)
+    }
+
+    /// Create or update a file in a change edit.
+    pub fn edit_put_file(
+        &mut self,
+        change: ChangeNumber,
+        path: &str,
+        contents: Vec<u8>,
+    ) -> miette::Result<()> {
+        let endpoint =
+            self.change_endpoint(change, &format!("/edit/{}", encode_path_segment(path)));
+        self.http_send_raw(Method::PUT, &endpoint, contents)?;
+        Ok(())
+    }
+
+    /// Delete a file in a change edit.
+    pub fn edit_delete_file(&mut self, change: ChangeNumber, path: &str) -> miette::Result<()> {
+        let endpoint =
+            self.change_endpoint(change, &format!("/edit/{}", encode_path_segment(path)));
+        self.http_send(Method::DELETE, &endpoint, None)?;
+        Ok(())
+    }
+
+    /// Publish a change edit as a new patchset.
+    pub fn edit_publish(&mut self, change: ChangeNumber) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/edit:publish");
+        self.http_send_json(Method::POST, &endpoint, &serde_json::json!({}))?;
+        self.forget_change(change)
+    }
+
+    /// Rebase a change edit on the latest patchset.
+    pub fn edit_rebase(&mut self, change: ChangeNumber) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/edit:rebase");
+        self.http_send(Method::POST, &endpoint, None)?;
+        Ok(())
+    }
+
+    /// Delete a change edit.
+    pub fn edit_discard(&mut self, change: ChangeNumber) -> miette::Result<()> {
+        let endpoint = self.change_endpoint(change, "/edit");
+        self.http_send(Method::DELETE, &endpoint, None)?;
+        Ok(())
+    }
+
+    /// Get the published inline comments on a change, keyed by file path.
+    pub fn comments(
+        &mut self,
+        change: ChangeNumber,
+    ) -> miette::Result<BTreeMap<String, Vec<CommentInfo>>> {
+        let mut comments = self.http_json::<BTreeMap<String, Vec<CommentInfo>>>(
+            Method::GET,
+            &self.change_endpoint(change, "/comments"),
+        )?;
+        for (path, comments) in comments.iter_mut() {
+            for comment in comments {
+                comment.path.get_or_insert_with(|| path.clone());
+            }
+        }
+        Ok(comments)
+    }
 }
 
 /// A [`Gerrit`] client tied to a specific Git remote.
@@ -550,6 +1663,8 @@ impl Gerrit {
 pub struct GerritGitRemote {
     pub remote: String,
     inner: Gerrit,
+    /// Clients for other Gerrit hosts.
+    others: GerritPool,
 }
 
 impl GerritGitRemote {
@@ -557,6 +1672,7 @@ impl GerritGitRemote {
         Ok(Self {
             remote: remote.to_owned(),
             inner: GerritProject::parse_from_remote_url(url).and_then(Gerrit::new)?,
+            others: Default::default(),
         })
     }
 
@@ -597,7 +1713,12 @@ impl GerritGitRemote {
         Ok(())
     }
 
-    pub fn push(&self, branch: Option<String>, target: Option<String>) -> miette::Result<()> {
+    pub fn push(
+        &mut self,
+        branch: Option<String>,
+        target: Option<String>,
+        options: &PushOptions,
+    ) -> miette::Result<()> {
         let git = self.git();
         let target = match target {
             Some(target) => target,
@@ -607,7 +1728,11 @@ impl GerritGitRemote {
             Some(branch) => branch,
             None => "HEAD".to_owned(),
         };
-        git.gerrit_push(&self.remote, &branch, &target)?;
+        if let Err(error) = self.log_sticky_votes(&branch) {
+            tracing::debug!("Failed to determine sticky votes: {error}");
+        }
+        git.gerrit_push(&self.remote, &branch, &target, options)
+            .map_err(|error| self.with_permission_help(error, None))?;
         let change_id = git.change_id(&branch)?;
         match self.get_change(change_id) {
             Ok(change) => {
@@ -622,23 +1747,81 @@ impl GerritGitRemote {
                 tracing::debug!("Ignoring error from fetching change before pushing: {error}");
             }
         }
+        if let Err(error) = self.index_pushed_change(&branch) {
+            tracing::debug!("Failed to update Change-Id index: {error}");
+        }
         Ok(())
     }
 
+    /// Record a just-pushed change and the local branch it was pushed from in the Change-Id index.
+    fn index_pushed_change(&self, branch: &str) -> miette::Result<()> {
+        let git = self.git();
+        let change = self.get_change(git.change_id(branch)?)?;
+        let local_branch = if branch == "HEAD" {
+            git.current_branch()?
+        } else if git.refs(&format!("refs/heads/{branch}"))?.is_empty() {
+            None
+        } else {
+            Some(branch.to_owned())
+        };
+        ChangeIndex::update(
+            &git,
+            change.id.clone(),
+            change.number,
+            change.patchset().patchset,
+            local_branch,
+        )
+    }
+
+    /// Log which votes will be kept or removed when pushing `branch` to an existing change.
+    ///
+    /// Changes which haven't been pushed or fetched from this repository are skipped without
+    /// talking to Gerrit, so pushing a new change doesn't wait on lookups which can only miss.
+    fn log_sticky_votes(&mut self, branch: &str) -> miette::Result<()> {
+        let git = self.git();
+        let change_id = git.change_id(branch)?;
+        let number = match ChangeIndex::read(&git)?.changes.get(&change_id) {
+            Some(entry) => entry.number,
+            None => {
+                tracing::debug!("{change_id} isn't in the Change-Id index; not checking votes");
+                return Ok(());
+            }
+        };
+        let change = self.get_change(number)?;
+        if !change.open {
+            return Ok(());
+        }
+        let old = self.fetch_cl(change.patchset())?;
+        crate::sticky_votes::log_sticky_votes(self, &change, &old, branch)
+    }
+
+    #[instrument(skip(self))]
     pub fn restack(
         &mut self,
         branch: &str,
         options: Option<RestackContinue>,
+        include_others: bool,
     ) -> miette::Result<()> {
-        restack(self, branch, options)
+        restack(self, branch, options, include_others)
+    }
+
+    #[instrument(skip(self))]
+    pub fn restack_abort(&mut self) -> miette::Result<()> {
+        restack_abort(self)
     }
 
     pub fn restack_continue(&mut self, options: RestackContinue) -> miette::Result<()> {
-        self.restack("HEAD", Some(options))
+        self.restack("HEAD", Some(options), false)
     }
 
-    pub fn restack_push(&self) -> miette::Result<()> {
-        restack_push(self)
+    #[instrument(skip(self))]
+    pub fn restack_push(
+        &mut self,
+        wip: Option<PushWip>,
+        require_labels: &[LabelVote],
+        rerequest_review: Option<bool>,
+    ) -> miette::Result<RestackSummary> {
+        restack_push(self, wip, require_labels, rerequest_review)
     }
 
     pub fn restack_write_git_rebase_todo(&mut self, path: &Utf8Path) -> miette::Result<()> {
@@ -651,40 +1834,172 @@ impl GerritGitRemote {
         Ok(())
     }
 
-    pub fn format_chain(&mut self, query: Option<String>) -> miette::Result<String> {
+    /// Format the chain of changes containing a change as a tree.
+    ///
+    /// Lines are truncated to the terminal width unless `full` is set. If `age` is given, changes
+    /// are annotated with their ages, and highlighted if they've stalled.
+    pub fn format_chain(
+        &mut self,
+        query: Option<String>,
+        full: bool,
+        age: Option<StalledAfter>,
+    ) -> miette::Result<String> {
         let git = self.git();
-        let change_number = match query {
-            Some(query) => self.get_change(query)?.number,
+        let change_number = self.change_or_head(query)?;
+        let mut graph = DependencyGraph::traverse(self, change_number)?;
+
+        let refs = if let Some(todo) = crate::restack::get_todo(self)? {
+            todo.refs
+        } else if let Ok(todo) = crate::restack_push::maybe_get_todo(self)? {
+            todo.refs
+        } else {
+            Default::default()
+        };
+
+        let mut cross_repo_dependencies = BTreeMap::new();
+        for change in graph.changes() {
+            let change = self.get_change(change)?;
+            cross_repo_dependencies.insert(change.number, self.format_depends_on_footers(&change)?);
+        }
+
+        let unverified = graph
+            .changes()
+            .into_iter()
+            .filter(|change| graph.is_unverified(*change))
+            .collect::<BTreeSet<_>>();
+
+        let gerrit: &Gerrit = self;
+        let width = if full { None } else { terminal_width() };
+        let now = OffsetDateTime::now_utc();
+        graph.format_tree_with_leaves(
+            gerrit,
+            width,
+            |change| {
+                let change_number = change;
+                let change = gerrit.get_change(change_number)?;
+                let mut label = Vec::new();
+                label.extend(change.readiness_label());
+                label.extend(change.kind_label());
+                label.extend(age.map(|stalled_after| change.age_label(now, stalled_after)));
+                label.extend(refs.get(&change_number).map(|update| update.to_string()));
+                if unverified.contains(&change_number) {
+                    label.push(
+                        "⚠ dependents may be out of date"
+                            .if_supports_color(Stdout, |label| label.yellow())
+                            .to_string(),
+                    );
+                }
+                label.extend(note_label(&git, &change.id)?.map(|note| {
+                    note.if_supports_color(Stdout, |note| note.dimmed())
+                        .to_string()
+                }));
+                Ok(label)
+            },
+            |change| Ok(cross_repo_dependencies.remove(&change).unwrap_or_default()),
+        )
+    }
+
+    /// Format each change in a chain with a user-supplied template, one per line, from the bottom
+    /// of the stack up.
+    pub fn format_chain_template(
+        &mut self,
+        query: Option<String>,
+        template: &OutputTemplate,
+    ) -> miette::Result<String> {
+        let change_number = self.change_or_head(query)?;
+        let mut graph = DependencyGraph::traverse(self, change_number)?;
+        let changes = graph
+            .changes_bottom_up()?
+            .into_iter()
+            .map(|change| self.get_change(change))
+            .collect::<miette::Result<Vec<_>>>()?;
+        template.render_changes(&changes)
+    }
+
+    /// Get the number of the change matching `query`, or the `HEAD` commit's change.
+    fn change_or_head(&self, query: Option<String>) -> miette::Result<ChangeNumber> {
+        match query {
+            Some(query) => Ok(self.get_change(query)?.number),
             None => {
-                let change_id = git
+                let change_id = self
+                    .git()
                     .change_id("HEAD")
                     .wrap_err("Failed to get Change-Id for HEAD")?;
-                self.get_change(change_id)?.number
+                Ok(self.get_change(change_id)?.number)
             }
+        }
+    }
+
+    /// Format the changes named in a change's `Depends-On:` trailers, one per line.
+    ///
+    /// Changes are looked up with the REST API. Changes on other hosts are looked up with the
+    /// same username, and shown as-is if that fails.
+    fn format_depends_on_footers(&mut self, change: &Change) -> miette::Result<Vec<String>> {
+        let footers = match &change.commit_message {
+            Some(commit_message) => DependsOnFooter::from_commit_message(commit_message),
+            None => return Ok(Vec::new()),
         };
-        let mut graph = DependencyGraph::traverse(self, change_number)?;
 
-        if let Some(todo) = crate::restack::get_todo(self)? {
-            graph.format_tree(self, |change| {
-                Ok(todo
-                    .refs
-                    .get(&change)
-                    .into_iter()
-                    .map(|update| update.to_string())
-                    .collect())
-            })
-        } else if let Ok(todo) = crate::restack_push::maybe_get_todo(self)? {
-            graph.format_tree(self, |change| {
-                Ok(todo
-                    .refs
-                    .get(&change)
-                    .into_iter()
-                    .map(|update| update.to_string())
-                    .collect())
-            })
-        } else {
-            graph.format_tree(self, |_change| Ok(vec![]))
+        let mut lines = Vec::new();
+        for footer in footers {
+            let dependencies = match footer.query(&self.host.host) {
+                Some(query) => self.http_json::<Vec<ChangeInfo>>(
+                    Method::GET,
+                    &Endpoint::new(&format!("changes/?q={}", encode_path_segment(&query))),
+                )?,
+                None => match self.query_other_host(&footer) {
+                    Ok(dependencies) => dependencies,
+                    Err(error) => {
+                        tracing::debug!("Failed to look up {footer}: {error:?}");
+                        lines.push(format!("depends on {footer}"));
+                        continue;
+                    }
+                },
+            };
+
+            if dependencies.is_empty() {
+                lines.push(format!("depends on {footer} (not found)"));
+            }
+            for dependency in dependencies {
+                let status = match dependency.status {
+                    ChangeStatus::Merged => dependency
+                        .status
+                        .if_supports_color(Stdout, |status| status.magenta())
+                        .to_string(),
+                    ChangeStatus::Abandoned => dependency
+                        .status
+                        .if_supports_color(Stdout, |status| status.red())
+                        .to_string(),
+                    ChangeStatus::New => dependency
+                        .status
+                        .if_supports_color(Stdout, |status| status.green())
+                        .to_string(),
+                };
+                lines.push(format!(
+                    "depends on {} {} ({}) {status}",
+                    dependency.project, dependency.number, dependency.subject
+                ));
+            }
         }
+        Ok(lines)
+    }
+
+    /// Look up the changes a `Depends-On:` trailer for another Gerrit host refers to.
+    fn query_other_host(&mut self, footer: &DependsOnFooter) -> miette::Result<Vec<ChangeInfo>> {
+        let host = footer
+            .host()
+            .ok_or_else(|| miette!("`Depends-On: {footer}` doesn't name a Gerrit host"))?;
+        let query = footer
+            .query(host)
+            .ok_or_else(|| miette!("Could not parse change URL: {footer}"))?;
+        let host = GerritHost {
+            host: host.to_owned(),
+            ..(**self.host()).clone()
+        };
+        self.others.get(host)?.http_json::<Vec<ChangeInfo>>(
+            Method::GET,
+            &Endpoint::new(&format!("changes/?q={}", encode_path_segment(&query))),
+        )
     }
 }
 
@@ -0,0 +1,36 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use crate::gerrit::Gerrit;
+use crate::gerrit_host::GerritHost;
+use crate::gerrit_project::GerritProject;
+
+/// The project to use for [`Gerrit`] clients which aren't tied to a particular project.
+///
+/// Every Gerrit server has this project, and project-independent requests (like change queries)
+/// work regardless.
+const ALL_PROJECTS: &str = "All-Projects";
+
+/// [`Gerrit`] clients for several hosts, like the hosts named in cross-repository `Depends-On:`
+/// trailers.
+///
+/// Each client has its own cache and REST API credentials. Clients are created lazily, at most once
+/// per host, so their on-disk caches don't contend with each other.
+#[derive(Debug, Default)]
+pub struct GerritPool {
+    gerrits: BTreeMap<String, Gerrit>,
+}
+
+impl GerritPool {
+    /// Get the client for a host, connecting to it if needed.
+    pub fn get(&mut self, host: GerritHost) -> miette::Result<&mut Gerrit> {
+        match self.gerrits.entry(host.host.clone()) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                tracing::debug!(%host, "Connecting to Gerrit host");
+                let gerrit = Gerrit::new(GerritProject::new(host, ALL_PROJECTS.to_owned()))?;
+                Ok(entry.insert(gerrit))
+            }
+        }
+    }
+}
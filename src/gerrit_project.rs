@@ -17,6 +17,10 @@ pub struct GerritProject {
 }
 
 impl GerritProject {
+    pub fn new(host: GerritHost, project: String) -> Self {
+        Self { host, project }
+    }
+
     /// Parse a Gerrit configuration from a Git remote URL.
     pub fn parse_from_remote_url(url: &str) -> miette::Result<Self> {
         static RE: OnceLock<Regex> = OnceLock::new();
@@ -33,7 +37,7 @@ impl GerritProject {
                     :
                     (?P<port>[0-9]+)
                     /
-                    (?P<project>[[:word:].-]+)
+                    (?P<project>[[:word:].-]+(?:/[[:word:].-]+)*)
                     $",
                 )
                 .expect("Regex parses")
@@ -62,6 +66,11 @@ impl GerritProject {
     pub fn remote_url(&self) -> String {
         format!("{}/{}", self.connect_to(), self.project)
     }
+
+    /// The authenticated HTTPS clone URL, like `https://USER@HOST/a/PROJECT`.
+    pub fn http_remote_url(&self) -> String {
+        format!("https://{}@{}/a/{}", self.username, self.host, self.project)
+    }
 }
 
 impl Display for GerritProject {
@@ -97,5 +106,14 @@ mod tests {
                 project: "ouppy".to_owned(),
             }
         );
+
+        assert_eq!(
+            GerritProject::parse_from_remote_url(
+                "ssh://rbt@ooga.booga.systems:2022/platform/build"
+            )
+            .unwrap()
+            .project,
+            "platform/build"
+        );
     }
 }
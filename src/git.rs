@@ -1,17 +1,32 @@
+use std::io::Write;
 use std::process::Command;
+use std::process::Stdio;
 use std::sync::OnceLock;
 
+use bstr::BString;
+use bstr::ByteSlice;
+use camino::Utf8Path;
 use camino::Utf8PathBuf;
+use command_error::ChildExt;
 use command_error::CommandExt;
+use command_error::OutputContext;
 use miette::miette;
 use miette::Context;
 use miette::IntoDiagnostic;
 use regex::Regex;
+use tracing::instrument;
+use utf8_command::Utf8Output;
 
 use crate::change_id::ChangeId;
+use crate::change_key::ChangeKey;
+use crate::change_kind::ChangeKind;
 use crate::commit_hash::CommitHash;
+use crate::config::Config;
 use crate::format_bulleted_list;
 use crate::gerrit::GerritGitRemote;
+use crate::git_operation::GitOperation;
+use crate::push_options::PushOptions;
+use crate::quote_path::quote_path;
 
 /// `git` CLI wrapper.
 #[derive(Debug, Default)]
@@ -28,15 +43,50 @@ impl Git {
     }
 
     /// Push to a `refs/for/{branch}` ref.
-    pub fn gerrit_push(&self, remote: &str, commitish: &str, target: &str) -> miette::Result<()> {
+    #[instrument(level = "debug", skip(self))]
+    pub fn gerrit_push(
+        &self,
+        remote: &str,
+        commitish: &str,
+        target: &str,
+        options: &PushOptions,
+    ) -> miette::Result<()> {
+        let suffix = options.refspec_suffix();
+        // Capture the output so that permission errors can be explained; `git push` prints
+        // its progress and the URLs of new changes to stderr.
+        let output = self
+            .command()
+            .args([
+                "push",
+                remote,
+                &format!("{commitish}:refs/for/{target}{suffix}"),
+            ])
+            .output_checked_utf8()
+            .into_diagnostic()?;
+        let _ = calm_io::stderr!("{}", output.stderr);
+        Ok(())
+    }
+
+    /// Get the top-level directory of the working tree, if we're in one.
+    #[instrument(level = "debug", skip(self))]
+    pub fn toplevel(&self) -> miette::Result<Option<Utf8PathBuf>> {
         self.command()
-            .args(["push", remote, &format!("{commitish}:refs/for/{target}")])
-            .status_checked()
-            .map(|_| ())
+            .args(["rev-parse", "--show-toplevel"])
+            .output_checked_as(|context: OutputContext<Utf8Output>| {
+                if context.status().success() {
+                    Ok(Some(Utf8PathBuf::from(context.output().stdout.trim())))
+                } else if context.status().code() == Some(128) {
+                    // Not in a repository, or in a bare one.
+                    Ok(None)
+                } else {
+                    Err(context.error())
+                }
+            })
             .into_diagnostic()
     }
 
     /// Get a list of all `git remote`s.
+    #[instrument(level = "debug", skip(self))]
     pub fn remotes(&self) -> miette::Result<Vec<String>> {
         Ok(self
             .command()
@@ -51,6 +101,7 @@ impl Git {
     }
 
     /// Get the (push) URL for the given remote.
+    #[instrument(level = "debug", skip(self))]
     pub fn remote_url(&self, remote: &str) -> miette::Result<String> {
         Ok(self
             .command()
@@ -125,6 +176,7 @@ impl Git {
         }
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub fn default_branch(&self, remote: &str) -> miette::Result<String> {
         self.default_branch_symbolic_ref(remote).or_else(|err| {
             tracing::debug!("Failed to get default branch: {err}");
@@ -132,6 +184,7 @@ impl Git {
         })
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub fn commit_message(&self, commit: &str) -> miette::Result<String> {
         Ok(self
             .command()
@@ -142,34 +195,213 @@ impl Git {
             .stdout)
     }
 
+    /// The change to act on: the one given on the command line, or else `HEAD`'s.
+    #[instrument(level = "debug", skip(self))]
+    pub fn change_key_or_head(&self, change: Option<String>) -> miette::Result<ChangeKey> {
+        match change {
+            Some(query) => Ok(query.into()),
+            None => Ok(self.change_id("HEAD")?.into()),
+        }
+    }
+
+    #[instrument(level = "debug", skip(self))]
     pub fn change_id(&self, commit: &str) -> miette::Result<ChangeId> {
         let commit_message = self.commit_message(commit)?;
 
-        static RE: OnceLock<Regex> = OnceLock::new();
-        let captures = RE
-            .get_or_init(|| {
-                Regex::new(
-                    r"(?xm)
-                    ^
-                    Change-Id:\ (?P<change_id>I[[:xdigit:]]{40})
-                    $
-                    ",
-                )
-                .expect("Regex parses")
+        ChangeId::from_commit_message(&commit_message).ok_or_else(|| {
+            miette!("Could not find Change-Id in message for commit {commit}:\n{commit_message}")
+        })
+    }
+
+    /// Get a `git config` value, if it's set.
+    #[instrument(level = "debug", skip(self))]
+    pub fn config_get(&self, key: &str) -> miette::Result<Option<String>> {
+        self.command()
+            .args(["config", "--get", key])
+            .output_checked_as(|context: OutputContext<Utf8Output>| {
+                if context.status().success() {
+                    Ok(Some(context.output().stdout.trim().to_owned()))
+                } else if context.status().code() == Some(1) {
+                    // The key isn't set.
+                    Ok(None)
+                } else {
+                    Err(context.error())
+                }
             })
-            .captures(&commit_message);
+            .into_diagnostic()
+    }
 
-        match captures {
-            Some(captures) => Ok(ChangeId::new(captures["change_id"].to_owned())),
-            None => Err(miette!(
-                "Could not find Change-Id in message for commit {commit}:\n{commit_message}"
-            )),
+    /// Get a boolean `git config` value, if it's set.
+    #[instrument(level = "debug", skip(self))]
+    pub fn config_get_bool(&self, key: &str) -> miette::Result<Option<bool>> {
+        self.command()
+            .args(["config", "--type=bool", "--get", key])
+            .output_checked_as(|context: OutputContext<Utf8Output>| {
+                if context.status().success() {
+                    Ok(Some(context.output().stdout.trim() == "true"))
+                } else if context.status().code() == Some(1) {
+                    // The key isn't set.
+                    Ok(None)
+                } else {
+                    Err(context.error())
+                }
+            })
+            .into_diagnostic()
+    }
+
+    /// Hash `contents` as a blob, optionally writing it to the object database.
+    #[instrument(level = "debug", skip(self, contents))]
+    pub fn hash_object(&self, contents: &str, write: bool) -> miette::Result<String> {
+        let mut command = self.command();
+        command.args(["hash-object", "--stdin"]);
+        if write {
+            command.arg("-w");
         }
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn_checked()
+            .into_diagnostic()?;
+        child
+            .child_mut()
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(contents.as_bytes())
+            .into_diagnostic()?;
+        Ok(child
+            .output_checked_utf8()
+            .into_diagnostic()?
+            .stdout
+            .trim()
+            .to_owned())
     }
 
+    /// Get the note attached to an object, if any.
+    #[instrument(level = "debug", skip(self))]
+    pub fn notes_show(&self, notes_ref: &str, object: &str) -> miette::Result<Option<String>> {
+        self.command()
+            .args(["notes", "--ref", notes_ref, "show", object])
+            .output_checked_as(|context: OutputContext<Utf8Output>| {
+                if context.status().success() {
+                    Ok(Some(context.output().stdout.trim_end().to_owned()))
+                } else if context.status().code() == Some(1) {
+                    // No note for the object.
+                    Ok(None)
+                } else {
+                    Err(context.error())
+                }
+            })
+            .into_diagnostic()
+    }
+
+    /// Append a paragraph to the note attached to an object.
+    #[instrument(level = "debug", skip(self, message))]
+    pub fn notes_append(&self, notes_ref: &str, object: &str, message: &str) -> miette::Result<()> {
+        self.command()
+            .args(["notes", "--ref", notes_ref, "append", "-m", message, object])
+            .output_checked_utf8()
+            .into_diagnostic()?;
+        Ok(())
+    }
+
+    /// Remove the note attached to an object.
+    #[instrument(level = "debug", skip(self))]
+    pub fn notes_remove(&self, notes_ref: &str, object: &str) -> miette::Result<()> {
+        self.command()
+            .args(["notes", "--ref", notes_ref, "remove", object])
+            .output_checked_utf8()
+            .into_diagnostic()?;
+        Ok(())
+    }
+
+    /// Get all values of a multi-valued `git config` key.
+    #[instrument(level = "debug", skip(self))]
+    pub fn config_get_all(&self, key: &str) -> miette::Result<Vec<String>> {
+        self.command()
+            .args(["config", "--get-all", key])
+            .output_checked_as(|context: OutputContext<Utf8Output>| {
+                if context.status().success() {
+                    Ok(context
+                        .output()
+                        .stdout
+                        .lines()
+                        .map(|line| line.to_owned())
+                        .collect())
+                } else if context.status().code() == Some(1) {
+                    // The key isn't set.
+                    Ok(Vec::new())
+                } else {
+                    Err(context.error())
+                }
+            })
+            .into_diagnostic()
+    }
+
+    /// Get every Git config key matching a regex, with its value.
+    ///
+    /// Keys set without a value, like `[section] key`, have a value of `None`. Git lowercases
+    /// section and variable names, but not subsection names.
+    #[instrument(level = "debug", skip(self))]
+    pub fn config_get_regexp(
+        &self,
+        pattern: &str,
+    ) -> miette::Result<Vec<(String, Option<String>)>> {
+        self.command()
+            .args(["config", "--null", "--get-regexp", pattern])
+            .output_checked_as(|context: OutputContext<Utf8Output>| {
+                if context.status().success() {
+                    Ok(context
+                        .output()
+                        .stdout
+                        .split_terminator('\0')
+                        .map(|entry| match entry.split_once('\n') {
+                            Some((key, value)) => (key.to_owned(), Some(value.to_owned())),
+                            // A key set without a value, like `[section] key`.
+                            None => (entry.to_owned(), None),
+                        })
+                        .collect())
+                } else if context.status().code() == Some(1) {
+                    // No keys match.
+                    Ok(Vec::new())
+                } else {
+                    Err(context.error())
+                }
+            })
+            .into_diagnostic()
+    }
+
+    /// Set a `git config` value in the repository's local configuration.
+    #[instrument(level = "debug", skip(self))]
+    pub fn config_set(&self, key: &str, value: &str) -> miette::Result<()> {
+        self.command()
+            .args(["config", "--local", key, value])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to set Git config `{key}`"))
+    }
+
+    /// Add a `git remote`.
+    #[instrument(level = "debug", skip(self))]
+    pub fn remote_add(&self, name: &str, url: &str) -> miette::Result<()> {
+        self.command()
+            .args(["remote", "add", name, url])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to add Git remote `{name}`"))
+    }
+
+    /// Get the Gerrit remote.
+    ///
+    /// If `gerrit_remote_name` isn't given, the `git-gr.remote` config value is used (see
+    /// `git gr init`), and otherwise the first remote which parses as a Gerrit URL.
     pub fn gerrit(&self, gerrit_remote_name: Option<&str>) -> miette::Result<GerritGitRemote> {
         let mut tried = Vec::new();
 
+        let gerrit_remote_name = gerrit_remote_name.or(Config::global().remote.as_deref());
+
         if let Some(remote_name) = gerrit_remote_name {
             tracing::debug!(remote_name, "Looking for remote");
         }
@@ -199,6 +431,7 @@ impl Git {
         Err(miette!("Failed to parse Gerrit configuration from Git remotes. Tried to parse these remotes:\n{}", format_bulleted_list(tried)))
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub fn cherry_pick(&self, commitish: &str) -> miette::Result<()> {
         self.command()
             .args(["cherry-pick", "--ff", commitish])
@@ -208,6 +441,7 @@ impl Git {
         Ok(())
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub fn rebase_interactive(&self, sequence_editor: &str, onto: &str) -> miette::Result<()> {
         self.command()
             .args(["rebase", "--interactive", onto])
@@ -219,12 +453,24 @@ impl Git {
     }
 
     /// Determine if a rebase is currently in progress.
+    #[instrument(level = "debug", skip(self))]
     pub fn rebase_in_progress(&self) -> miette::Result<bool> {
         let git_dir = self.get_git_dir()?;
         let rebase_dir = git_dir.join("rebase-merge");
         Ok(rebase_dir.exists())
     }
 
+    /// Get the rebase, merge, or other operation in progress in this worktree, if any.
+    #[instrument(level = "debug", skip(self))]
+    pub fn operation_in_progress(&self) -> miette::Result<Option<GitOperation>> {
+        let git_dir = self.get_git_dir()?;
+        Ok(GitOperation::MARKERS
+            .into_iter()
+            .find(|(marker, _)| git_dir.join(marker).exists())
+            .map(|(_, operation)| operation))
+    }
+
+    #[instrument(level = "debug", skip(self))]
     pub fn fetch(&self, remote: &str) -> miette::Result<()> {
         self.command()
             .args(["fetch", remote])
@@ -233,6 +479,7 @@ impl Git {
             .into_diagnostic()
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub fn checkout(&self, commitish: &str) -> miette::Result<()> {
         self.command()
             .args(["checkout", commitish])
@@ -241,6 +488,7 @@ impl Git {
             .into_diagnostic()
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub fn checkout_quiet(&self, commitish: &str) -> miette::Result<()> {
         self.command()
             .args(["checkout", commitish])
@@ -249,6 +497,7 @@ impl Git {
             .into_diagnostic()
     }
 
+    #[instrument(level = "debug", skip(self))]
     pub fn detach_head(&self) -> miette::Result<()> {
         self.command()
             .args(["checkout", "--detach"])
@@ -257,20 +506,440 @@ impl Git {
         Ok(())
     }
 
+    /// Get the parent commit hashes of a commit.
+    #[instrument(level = "debug", skip(self))]
+    pub fn parents(&self, commit: &str) -> miette::Result<Vec<CommitHash>> {
+        Ok(self
+            .command()
+            .args(["rev-parse", &format!("{commit}^@")])
+            .output_checked_utf8()
+            .into_diagnostic()?
+            .stdout
+            .lines()
+            .map(|line| CommitHash::new(line.trim().to_owned()))
+            .collect())
+    }
+
+    /// Get a commit's diff against its parent, without line numbers or blob hashes.
+    fn normalized_diff(&self, commit: &str) -> miette::Result<String> {
+        Ok(self
+            .command()
+            .args([
+                "diff-tree",
+                "--patch",
+                "--no-color",
+                "--no-ext-diff",
+                "--no-commit-id",
+                commit,
+            ])
+            .output_checked_utf8()
+            .into_diagnostic()?
+            .stdout
+            .lines()
+            .filter(|line| !line.starts_with("index "))
+            .map(|line| if line.starts_with("@@ ") { "@@" } else { line })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Determine the kind of change between two versions of a commit, like Gerrit does for
+    /// patchsets.
+    ///
+    /// This is an approximation: Gerrit checks if the old commit merges cleanly onto the new
+    /// parent, while we compare the commits' diffs.
+    #[instrument(level = "debug", skip(self))]
+    pub fn change_kind(&self, old: &str, new: &str) -> miette::Result<ChangeKind> {
+        if self.rev_parse(old)? == self.rev_parse(new)? {
+            return Ok(ChangeKind::NoChange);
+        }
+
+        let same_message = self.commit_message(old)? == self.commit_message(new)?;
+        let same_parents = self.parents(old)? == self.parents(new)?;
+        let same_tree = self.rev_parse(&format!("{old}^{{tree}}"))?
+            == self.rev_parse(&format!("{new}^{{tree}}"))?;
+
+        if same_parents && same_tree {
+            Ok(if same_message {
+                ChangeKind::NoChange
+            } else {
+                ChangeKind::NoCodeChange
+            })
+        } else if self.normalized_diff(old)? == self.normalized_diff(new)? {
+            Ok(if same_message {
+                ChangeKind::TrivialRebase
+            } else {
+                ChangeKind::TrivialRebaseWithMessageUpdate
+            })
+        } else {
+            Ok(ChangeKind::Rework)
+        }
+    }
+
+    /// List the commits in a range like `base..HEAD`, oldest first.
+    #[instrument(level = "debug", skip(self))]
+    pub fn rev_list(&self, range: &str) -> miette::Result<Vec<CommitHash>> {
+        Ok(self
+            .command()
+            .args(["rev-list", "--reverse", range])
+            .output_checked_utf8()
+            .into_diagnostic()?
+            .stdout
+            .lines()
+            .map(|line| CommitHash::new(line.trim().to_owned()))
+            .collect())
+    }
+
+    /// Apply patches from a patch or mbox file with `git am`.
+    #[instrument(level = "debug", skip(self, patch))]
+    pub fn am(&self, patch: &str) -> miette::Result<()> {
+        self.command()
+            .args(["am", "--3way", patch])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+    }
+
+    /// Rewrite the commits after `base` up to `HEAD` so that each has a `Change-Id` trailer,
+    /// and check out the result.
+    ///
+    /// Commits which already have a `Change-Id` keep it. New `Change-Id`s are the original
+    /// commit hash, truncated to 40 hex digits so that SHA-256 repositories still get
+    /// `Change-Id`s Gerrit accepts.
+    #[instrument(level = "debug", skip(self))]
+    pub fn ensure_change_ids(&self, base: &str) -> miette::Result<CommitHash> {
+        let mut parent = self.rev_parse(base)?;
+
+        for commit in self.rev_list(&format!("{base}..HEAD"))? {
+            let message = self.commit_message(&commit)?;
+            let message = match ChangeId::from_commit_message(&message) {
+                Some(_) => message,
+                None => add_trailer(&message, &format!("Change-Id: {}", new_change_id(&commit))),
+            };
+
+            let author = self
+                .command()
+                .args(["show", "--no-patch", "--format=%an%x00%ae%x00%aI", &commit])
+                .output_checked_utf8()
+                .into_diagnostic()?
+                .stdout;
+            let mut author = author.trim_end().split('\0');
+
+            let new_commit = self
+                .command()
+                .args([
+                    "commit-tree",
+                    &format!("{commit}^{{tree}}"),
+                    "-p",
+                    &parent,
+                    "-m",
+                    &message,
+                ])
+                .env("GIT_AUTHOR_NAME", author.next().unwrap_or_default())
+                .env("GIT_AUTHOR_EMAIL", author.next().unwrap_or_default())
+                .env("GIT_AUTHOR_DATE", author.next().unwrap_or_default())
+                .output_checked_utf8()
+                .into_diagnostic()
+                .wrap_err_with(|| format!("Failed to add Change-Id to {}", commit.abbrev()))?
+                .stdout;
+            parent = CommitHash::new(new_commit.trim().to_owned());
+        }
+
+        self.checkout_quiet(&parent)?;
+        Ok(parent)
+    }
+
+    /// Format a single commit as a patch, like `git format-patch`.
+    #[instrument(level = "debug", skip(self))]
+    pub fn format_patch(&self, commit: &str) -> miette::Result<String> {
+        Ok(self
+            .command()
+            .args(["format-patch", "-1", "--stdout", commit])
+            .output_checked_utf8()
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to format patch for {commit}"))?
+            .stdout)
+    }
+
     /// Get the `HEAD` commit hash.
+    /// Get the name of the checked-out branch, if any.
+    #[instrument(level = "debug", skip(self))]
+    pub fn current_branch(&self) -> miette::Result<Option<String>> {
+        let output = self
+            .command()
+            .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+            .output_checked_as(|context: OutputContext<Utf8Output>| {
+                if context.status().success() {
+                    Ok(Some(context.output().stdout.trim().to_owned()))
+                } else if context.status().code() == Some(1) {
+                    // Detached `HEAD`.
+                    Ok(None)
+                } else {
+                    Err(context.error())
+                }
+            })
+            .into_diagnostic()?;
+        Ok(output)
+    }
+
+    /// List refs matching a pattern, like `refs/heads/`.
+    #[instrument(level = "debug", skip(self))]
+    pub fn refs(&self, pattern: &str) -> miette::Result<Vec<String>> {
+        Ok(self
+            .command()
+            .args(["for-each-ref", "--format=%(refname)", pattern])
+            .output_checked_utf8()
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to list refs matching `{pattern}`"))?
+            .stdout
+            .lines()
+            .map(|line| line.to_owned())
+            .collect())
+    }
+
+    /// Is `ancestor` an ancestor of (or the same as) `commit`?
+    #[instrument(level = "debug", skip(self))]
+    pub fn is_ancestor(&self, ancestor: &str, commit: &str) -> miette::Result<bool> {
+        self.command()
+            .args(["merge-base", "--is-ancestor", ancestor, commit])
+            .output_checked_as(
+                |context: OutputContext<Utf8Output>| match context.status().code() {
+                    Some(0) => Ok(true),
+                    Some(1) => Ok(false),
+                    _ => Err(context.error()),
+                },
+            )
+            .into_diagnostic()
+    }
+
+    /// Update a ref to point to `new`, if it currently points to `old`.
+    /// Add a worktree at `path` with `commit` checked out, detached.
+    #[instrument(level = "debug", skip(self))]
+    pub fn worktree_add(&self, path: &Utf8Path, commit: &str) -> miette::Result<()> {
+        self.command()
+            .args([
+                "worktree",
+                "add",
+                "--quiet",
+                "--detach",
+                path.as_str(),
+                commit,
+            ])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to add worktree at `{path}`"))
+    }
+
+    /// Check out `commit` in the worktree at `path`, detached.
+    #[instrument(level = "debug", skip(self))]
+    pub fn worktree_checkout(&self, path: &Utf8Path, commit: &str) -> miette::Result<()> {
+        self.command()
+            .args([
+                "-C",
+                path.as_str(),
+                "checkout",
+                "--quiet",
+                "--detach",
+                commit,
+            ])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+    }
+
+    /// Remove the worktree at `path`, discarding any changes in it.
+    #[instrument(level = "debug", skip(self))]
+    pub fn worktree_remove(&self, path: &Utf8Path) -> miette::Result<()> {
+        self.command()
+            .args(["worktree", "remove", "--force", path.as_str()])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to remove worktree at `{path}`"))
+    }
+
+    /// Forget about worktrees which have been deleted.
+    #[instrument(level = "debug", skip(self))]
+    pub fn worktree_prune(&self) -> miette::Result<()> {
+        self.command()
+            .args(["worktree", "prune"])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+    }
+
+    /// Create or overwrite a ref.
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_ref(&self, name: &str, new: &str) -> miette::Result<()> {
+        self.command()
+            .args(["update-ref", name, new])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to update `{name}`"))
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub fn update_ref(&self, name: &str, new: &str, old: &str) -> miette::Result<()> {
+        self.command()
+            .args(["update-ref", name, new, old])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to update `{name}`"))
+    }
+
+    /// Reset the current branch to a commit, keeping uncommitted changes.
+    ///
+    /// Fails if uncommitted changes would be overwritten.
+    #[instrument(level = "debug", skip(self))]
+    pub fn reset_keep(&self, commitish: &str) -> miette::Result<()> {
+        self.command()
+            .args(["reset", "--keep", commitish])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+    }
+
+    /// Get the upstream ref of a branch, like `refs/remotes/origin/main`, if it has one.
+    #[instrument(level = "debug", skip(self))]
+    pub fn upstream(&self, branch: &str) -> miette::Result<Option<String>> {
+        let upstream = self
+            .command()
+            .args(["for-each-ref", "--format=%(upstream)", branch])
+            .output_checked_utf8()
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to get upstream of `{branch}`"))?
+            .stdout
+            .trim()
+            .to_owned();
+        Ok(Some(upstream).filter(|upstream| !upstream.is_empty()))
+    }
+
+    /// Is `commit` contained in any remote-tracking ref?
+    #[instrument(level = "debug", skip(self))]
+    pub fn on_remote(&self, commit: &str) -> miette::Result<bool> {
+        Ok(!self
+            .command()
+            .args([
+                "for-each-ref",
+                "--count=1",
+                "--format=%(refname)",
+                "--contains",
+                commit,
+                "refs/remotes/",
+            ])
+            .output_checked_utf8()
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to check if `{commit}` is on a remote"))?
+            .stdout
+            .trim()
+            .is_empty())
+    }
+
+    /// Delete a ref, like `refs/heads/my-branch`.
+    #[instrument(level = "debug", skip(self))]
+    pub fn delete_ref(&self, name: &str) -> miette::Result<()> {
+        self.command()
+            .args(["update-ref", "-d", name])
+            .status_checked()
+            .map(|_| ())
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to delete `{name}`"))
+    }
+
+    #[instrument(level = "debug", skip(self))]
     pub fn get_head(&self) -> miette::Result<CommitHash> {
         self.rev_parse("HEAD")
     }
 
     /// Get the `.git` directory path.
+    #[instrument(level = "debug", skip(self))]
     pub fn get_git_dir(&self) -> miette::Result<Utf8PathBuf> {
-        self.command()
-            .args(["rev-parse", "--git-dir"])
+        self.path_output(&["rev-parse", "--git-dir"])
+    }
+
+    /// Should paths with bytes outside of ASCII be quoted when shown to the user?
+    ///
+    /// See `core.quotePath` in `git help config`.
+    #[instrument(level = "debug", skip(self))]
+    pub fn quote_path(&self) -> miette::Result<bool> {
+        Ok(self.config_get_bool("core.quotePath")?.unwrap_or(true))
+    }
+
+    /// List the paths a commit changes, relative to the top of the working tree.
+    ///
+    /// Paths are returned byte-for-byte, regardless of `core.quotePath` or their encoding; use
+    /// [`crate::quote_path::quote_path`] to show them.
+    #[instrument(level = "debug", skip(self))]
+    pub fn changed_files(&self, commit: &str) -> miette::Result<Vec<BString>> {
+        let output = self
+            .command()
+            .args([
+                "diff-tree",
+                "--no-commit-id",
+                "--name-only",
+                "-r",
+                "-z",
+                "--root",
+                commit,
+            ])
+            .output_checked()
+            .into_diagnostic()?;
+        Ok(output
+            .stdout
+            .split_str("\0")
+            .filter(|path| !path.is_empty())
+            .map(BString::from)
+            .collect())
+    }
+
+    /// Run a command which prints a single path, like `git rev-parse --git-dir`.
+    ///
+    /// Paths which aren't UTF-8 are reported with a clear error, rather than failing to decode
+    /// the command's output.
+    fn path_output(&self, args: &[&str]) -> miette::Result<Utf8PathBuf> {
+        let output = self
+            .command()
+            .args(args)
+            .output_checked()
+            .into_diagnostic()?;
+        let path = output.stdout.trim_end_with(|c| c == '\n').as_bstr();
+        path.to_path()
+            .ok()
+            .and_then(Utf8Path::from_path)
+            .map(Utf8Path::to_owned)
+            .ok_or_else(|| miette!("Path isn't valid UTF-8: {}", quote_path(path, true)))
+    }
+
+    /// Open a file in the user's editor, as picked by `git var GIT_EDITOR`, and wait for it to
+    /// close.
+    #[instrument(level = "debug", skip(self))]
+    pub fn edit_file(&self, path: &Utf8Path) -> miette::Result<()> {
+        let editor = self
+            .command()
+            .args(["var", "GIT_EDITOR"])
             .output_checked_utf8()
+            .into_diagnostic()?
+            .stdout
+            .trim()
+            .to_owned();
+        // Like Git, run the editor with the shell so that it can include arguments.
+        Command::new("sh")
+            .args(["-c", &format!("{editor} \"$@\""), &editor, path.as_str()])
+            .status_checked()
             .into_diagnostic()
-            .map(|output| Utf8PathBuf::from(output.stdout.trim()))
+            .wrap_err("Editor failed")?;
+        Ok(())
     }
 
+    /// Resolve a path inside the `.git` directory, respecting `core.hooksPath` and similar.
+    #[instrument(level = "debug", skip(self))]
+    pub fn git_path(&self, path: &str) -> miette::Result<Utf8PathBuf> {
+        self.path_output(&["rev-parse", "--git-path", path])
+    }
+
+    #[instrument(level = "debug", skip(self))]
     pub fn rev_parse(&self, commitish: &str) -> miette::Result<CommitHash> {
         Ok(CommitHash::new(
             self.command()
@@ -283,3 +952,68 @@ impl Git {
         ))
     }
 }
+
+/// Add a trailer like `Change-Id: ...` to a commit message.
+///
+/// If the message already ends in a paragraph of trailers (like `Signed-off-by: ...`), the new
+/// trailer is added to that paragraph.
+fn add_trailer(message: &str, trailer: &str) -> String {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let trailer_re = RE.get_or_init(|| Regex::new(r"^[[:alnum:]-]+: ").expect("Regex parses"));
+
+    let message = message.trim_end();
+    let last_paragraph = message.rsplit("\n\n").next().unwrap_or_default();
+    let has_trailers =
+        message.contains("\n\n") && last_paragraph.lines().all(|line| trailer_re.is_match(line));
+
+    if has_trailers {
+        format!("{message}\n{trailer}\n")
+    } else {
+        format!("{message}\n\n{trailer}\n")
+    }
+}
+
+/// A new `Change-Id` for `commit`, which doesn't have one yet.
+///
+/// `Change-Id`s are always `I` followed by 40 hex digits, so SHA-256 hashes are truncated.
+fn new_change_id(commit: &CommitHash) -> String {
+    format!("I{}", commit.get(..40).unwrap_or(commit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_add_trailer() {
+        assert_eq!(
+            add_trailer("Fix the bug\n", "Change-Id: I1234"),
+            "Fix the bug\n\nChange-Id: I1234\n"
+        );
+        assert_eq!(
+            add_trailer(
+                "Fix the bug\n\nIt was bad.\n\nSigned-off-by: Me <me@example.com>\n",
+                "Change-Id: I1234"
+            ),
+            "Fix the bug\n\nIt was bad.\n\nSigned-off-by: Me <me@example.com>\nChange-Id: I1234\n"
+        );
+        assert_eq!(
+            add_trailer("Fix: the bug\n", "Change-Id: I1234"),
+            "Fix: the bug\n\nChange-Id: I1234\n"
+        );
+    }
+
+    #[test]
+    fn test_new_change_id() {
+        let sha1 = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(
+            new_change_id(&CommitHash::new(sha1.to_owned())),
+            format!("I{sha1}")
+        );
+        assert_eq!(
+            new_change_id(&CommitHash::new(format!("{sha1}89abcdef0123456789abcdef"))),
+            format!("I{sha1}")
+        );
+    }
+}
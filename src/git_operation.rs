@@ -0,0 +1,55 @@
+use std::fmt::Display;
+
+/// A multi-step `git` operation which can be left in progress, like a rebase stopped by
+/// conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitOperation {
+    Rebase,
+    Am,
+    Merge,
+    CherryPick,
+    Revert,
+    Bisect,
+}
+
+impl GitOperation {
+    /// Files and directories in the `.git` directory which mark each operation as in progress,
+    /// in the order they should be checked.
+    ///
+    /// Rebases come first, because the commands a rebase runs (like cherry-picks) can leave
+    /// their own markers while it's stopped.
+    pub const MARKERS: [(&'static str, GitOperation); 7] = [
+        ("rebase-merge", GitOperation::Rebase),
+        ("rebase-apply/applying", GitOperation::Am),
+        ("rebase-apply", GitOperation::Rebase),
+        ("MERGE_HEAD", GitOperation::Merge),
+        ("CHERRY_PICK_HEAD", GitOperation::CherryPick),
+        ("REVERT_HEAD", GitOperation::Revert),
+        ("BISECT_LOG", GitOperation::Bisect),
+    ];
+
+    /// The command which aborts this operation.
+    pub fn abort_command(&self) -> &'static str {
+        match self {
+            GitOperation::Rebase => "git rebase --abort",
+            GitOperation::Am => "git am --abort",
+            GitOperation::Merge => "git merge --abort",
+            GitOperation::CherryPick => "git cherry-pick --abort",
+            GitOperation::Revert => "git revert --abort",
+            GitOperation::Bisect => "git bisect reset",
+        }
+    }
+}
+
+impl Display for GitOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitOperation::Rebase => write!(f, "rebase"),
+            GitOperation::Am => write!(f, "`git am`"),
+            GitOperation::Merge => write!(f, "merge"),
+            GitOperation::CherryPick => write!(f, "cherry-pick"),
+            GitOperation::Revert => write!(f, "revert"),
+            GitOperation::Bisect => write!(f, "bisect"),
+        }
+    }
+}
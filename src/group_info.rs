@@ -0,0 +1,19 @@
+/// A Gerrit group, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-groups.html#group-info>
+#[derive(serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct GroupInfo {
+    /// The URL-encoded UUID of the group.
+    ///
+    /// Not set when groups are returned as a map keyed by UUID.
+    #[serde(default)]
+    pub id: String,
+    /// The name of the group.
+    ///
+    /// Not set when groups are returned as a map keyed by name.
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// The name of the owner group.
+    pub owner: Option<String>,
+}
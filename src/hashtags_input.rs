@@ -0,0 +1,10 @@
+/// Hashtags to add to and remove from a change.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#hashtags-input>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct HashtagsInput {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<String>,
+}
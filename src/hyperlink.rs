@@ -0,0 +1,94 @@
+use std::fmt::Display;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::OnceLock;
+
+use regex::Regex;
+pub use supports_hyperlinks::Stream;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Never emit hyperlinks, even if the terminal supports them.
+pub fn disable_hyperlinks() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+fn enabled(stream: Stream) -> bool {
+    !DISABLED.load(Ordering::Relaxed) && supports_hyperlinks::on(stream)
+}
+
+/// Link `text` to `url` with an OSC 8 escape sequence, if the terminal supports it.
+pub fn hyperlink(text: impl Display, url: &str, stream: Stream) -> String {
+    if enabled(stream) {
+        force_hyperlink(text, url)
+    } else {
+        text.to_string()
+    }
+}
+
+fn force_hyperlink(text: impl Display, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Link the numbers at the start of each row of a rendered table, if the terminal supports it.
+///
+/// The first line of `table` is the header, and each following line is a row, linked to the
+/// corresponding URL in `urls`. Links are added after rendering so that the escape sequences
+/// don't count towards the column widths.
+pub fn link_table_rows(table: String, urls: &[&str], stream: Stream) -> String {
+    if !enabled(stream) {
+        return table;
+    }
+    link_table_rows_unchecked(table, urls)
+}
+
+fn link_table_rows_unchecked(table: String, urls: &[&str]) -> String {
+    let lines = table.lines().collect::<Vec<_>>();
+    if lines.len() != urls.len() + 1 {
+        // Rows wrapped onto multiple lines; we can't tell which line is which row.
+        return table;
+    }
+
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(r"^(?P<prefix>\s*(?:\x1b\[[0-9;]*m)*)(?P<number>[0-9]+)").expect("Regex parses")
+    });
+
+    let mut linked = String::with_capacity(table.len());
+    linked.push_str(lines[0]);
+    for (line, url) in lines[1..].iter().zip(urls) {
+        linked.push('\n');
+        match re.captures(line) {
+            Some(captures) => {
+                let end = captures.get(0).expect("Match exists").end();
+                linked.push_str(&captures["prefix"]);
+                linked.push_str(&force_hyperlink(&captures["number"], url));
+                linked.push_str(&line[end..]);
+            }
+            None => linked.push_str(line),
+        }
+    }
+    linked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_link_table_rows() {
+        let table = "  # Subject\n 12 Puppy\n\x1b[1m123\x1b[0m Doggy".to_owned();
+        assert_eq!(
+            link_table_rows_unchecked(table, &["https://a/12", "https://a/123"]),
+            "  # Subject\n \x1b]8;;https://a/12\x1b\\12\x1b]8;;\x1b\\ Puppy\n\x1b[1m\x1b]8;;https://a/123\x1b\\123\x1b]8;;\x1b\\\x1b[0m Doggy"
+        );
+
+        // Wrapped rows are left alone.
+        let table = "  # Subject\n 12 Puppy\n    more".to_owned();
+        assert_eq!(
+            link_table_rows_unchecked(table.clone(), &["https://a/12"]),
+            table
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use camino::Utf8Path;
+use miette::Context;
+
+use crate::gerrit::GerritGitRemote;
+
+/// Apply a patch or mbox file onto a target branch and push it for review.
+pub fn import(
+    gerrit: &mut GerritGitRemote,
+    patch: &Utf8Path,
+    target: Option<String>,
+) -> miette::Result<()> {
+    let git = gerrit.git();
+    let target = match target {
+        Some(target) => target,
+        None => git.default_branch(&gerrit.remote)?,
+    };
+    let base = format!("{}/{target}", gerrit.remote);
+
+    git.fetch(&gerrit.remote)?;
+    git.checkout_quiet(&base)
+        .wrap_err_with(|| format!("Failed to check out {base}"))?;
+    git.am(patch.as_str()).wrap_err(
+        "Failed to apply patch; fix conflicts and use `git am --continue` and then `git-gr push`, or use `git am --abort` to give up",
+    )?;
+
+    let head = git.ensure_change_ids(&base)?;
+    tracing::info!("Applied {} onto {base} as {}", patch, head.abbrev());
+
+    gerrit.push(None, Some(target), &Default::default())
+}
@@ -0,0 +1,130 @@
+use calm_io::stdoutln;
+use camino::Utf8Path;
+use dialoguer::Confirm;
+use dialoguer::Input;
+use dialoguer::Select;
+use fs_err as fs;
+use miette::Context;
+use miette::IntoDiagnostic;
+use reqwest::Method;
+
+use crate::account_info::AccountInfo;
+use crate::endpoint::Endpoint;
+use crate::gerrit::GerritGitRemote;
+use crate::gerrit_project::GerritProject;
+use crate::git::Git;
+
+/// Interactively set up `git-gr` for the current repository.
+///
+/// Finds or adds the Gerrit remote, checks SSH and REST API access, installs the `commit-msg`
+/// hook, records the remote in the repository's Git config, and runs a test query.
+pub fn init(git: &Git) -> miette::Result<()> {
+    let mut gerrit = choose_remote(git)?;
+    git.config_set("git-gr.remote", &gerrit.remote)?;
+    tracing::info!("Using Gerrit remote `{}`", gerrit.remote);
+
+    let version = gerrit
+        .version()
+        .wrap_err("Failed to connect to Gerrit over SSH; is your SSH key added to Gerrit?")?;
+    tracing::info!("Connected to Gerrit {version} over SSH");
+
+    let account = gerrit
+        .http_json::<AccountInfo>(Method::GET, &Endpoint::new("accounts/self"))
+        .wrap_err("Failed to authenticate to the Gerrit REST API")?;
+    tracing::info!("Authenticated to the REST API as {account}");
+
+    let hook = git.git_path("hooks/commit-msg")?;
+    if hook.exists() {
+        tracing::info!("`commit-msg` hook is already installed");
+    } else if Confirm::new()
+        .with_prompt("Install Gerrit's `commit-msg` hook to add `Change-Id` trailers to commits?")
+        .default(true)
+        .interact()
+        .into_diagnostic()?
+    {
+        install_commit_msg_hook(&gerrit.host().host, &hook)?;
+        tracing::info!("Installed `commit-msg` hook to `{hook}`");
+    }
+
+    let query = format!("status:open owner:self project:{}", gerrit.project());
+    let _ = stdoutln!("Your open changes ({query}):");
+    let table = gerrit.format_query_results(query, Default::default())?;
+    let _ = stdoutln!("{table}");
+
+    tracing::info!("All set! Run `git-gr doctor` at any time to check your setup");
+    Ok(())
+}
+
+/// Pick a Gerrit remote, or add one if none exist.
+fn choose_remote(git: &Git) -> miette::Result<GerritGitRemote> {
+    let mut candidates = Vec::new();
+    for remote in git.remotes()? {
+        let url = git.remote_url(&remote)?;
+        if GerritProject::parse_from_remote_url(&url).is_ok() {
+            candidates.push((remote, url));
+        }
+    }
+
+    match candidates.len() {
+        0 => {
+            tracing::info!("No Gerrit remotes found");
+            let url: String = Input::new()
+                .with_prompt("Gerrit remote URL (ssh://USER@HOST:PORT/PROJECT)")
+                .validate_with(|url: &String| {
+                    GerritProject::parse_from_remote_url(url)
+                        .map(|_| ())
+                        .map_err(|error| error.to_string())
+                })
+                .interact_text()
+                .into_diagnostic()?;
+            let remote: String = Input::new()
+                .with_prompt("Remote name")
+                .default("gerrit".to_owned())
+                .interact_text()
+                .into_diagnostic()?;
+            git.remote_add(&remote, &url)?;
+            GerritGitRemote::from_remote(&remote, &url)
+        }
+        1 => {
+            let (remote, url) = &candidates[0];
+            GerritGitRemote::from_remote(remote, url)
+        }
+        _ => {
+            let items = candidates
+                .iter()
+                .map(|(remote, url)| format!("{remote} ({url})"))
+                .collect::<Vec<_>>();
+            let index = Select::new()
+                .with_prompt("Which remote is Gerrit?")
+                .items(&items)
+                .default(0)
+                .interact()
+                .into_diagnostic()?;
+            let (remote, url) = &candidates[index];
+            GerritGitRemote::from_remote(remote, url)
+        }
+    }
+}
+
+/// Download Gerrit's `commit-msg` hook and make it executable.
+fn install_commit_msg_hook(host: &str, path: &Utf8Path) -> miette::Result<()> {
+    let url = format!("https://{host}/tools/hooks/commit-msg");
+    let response = reqwest::blocking::get(&url)
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .into_diagnostic()
+        .wrap_err_with(|| format!("Failed to download `commit-msg` hook from {url}"))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).into_diagnostic()?;
+    }
+    fs::write(path, response).into_diagnostic()?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).into_diagnostic()?;
+    }
+
+    Ok(())
+}
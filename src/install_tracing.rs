@@ -1,18 +1,74 @@
+use camino::Utf8Path;
+use miette::miette;
+use miette::Context;
 use miette::IntoDiagnostic;
+use tracing_appender::rolling::RollingFileAppender;
+use tracing_appender::rolling::Rotation;
+use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::Layer;
 
-pub fn install_tracing(filter_directives: &str) -> miette::Result<()> {
+/// How many rotated log files to keep.
+const MAX_LOG_FILES: usize = 7;
+
+pub fn install_tracing(
+    filter_directives: &str,
+    log_file: Option<&Utf8Path>,
+    log_file_filter_directives: &str,
+) -> miette::Result<()> {
     let env_filter = tracing_subscriber::EnvFilter::try_new(filter_directives).into_diagnostic()?;
 
     let human_layer = tracing_human_layer::HumanLayer::new()
         .with_output_writer(std::io::stderr())
         .with_filter(env_filter);
 
+    let file_layer = match log_file {
+        Some(log_file) => Some(json_layer(log_file, log_file_filter_directives)?),
+        None => None,
+    };
+
     let registry = tracing_subscriber::registry();
 
-    registry.with(human_layer).init();
+    registry.with(human_layer).with(file_layer).init();
 
     Ok(())
 }
+
+/// A layer writing JSON logs to a file which is rotated daily.
+///
+/// Rotated files are named like `{log_file}.2024-06-01`. Span closures are logged with their
+/// durations, so slow queries and REST requests can be found after the fact.
+fn json_layer<S>(
+    log_file: &Utf8Path,
+    filter_directives: &str,
+) -> miette::Result<impl Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    let env_filter = tracing_subscriber::EnvFilter::try_new(filter_directives).into_diagnostic()?;
+
+    let file_name = log_file
+        .file_name()
+        .ok_or_else(|| miette!("Log file path has no file name: {log_file}"))?;
+    let directory = match log_file.parent() {
+        Some(parent) if !parent.as_str().is_empty() => parent,
+        _ => Utf8Path::new("."),
+    };
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(file_name)
+        .max_log_files(MAX_LOG_FILES)
+        .build(directory)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("Failed to open log file `{log_file}`"))?;
+
+    Ok(tracing_subscriber::fmt::layer()
+        .json()
+        .with_span_list(true)
+        .with_current_span(true)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(appender)
+        .with_filter(env_filter))
+}
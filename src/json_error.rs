@@ -0,0 +1,91 @@
+use miette::Diagnostic;
+
+use crate::change_number::ChangeNumber;
+use crate::error_kind::error_kind;
+use crate::error_kind::exit_code;
+use crate::error_kind::related_changes;
+
+/// An error, as written to stderr with `--output json`.
+#[derive(serde::Serialize, Debug, PartialEq, Eq)]
+pub struct JsonError {
+    /// The exit code; see [`crate::error_kind::ErrorKind`].
+    pub code: u8,
+    /// The kind of error, like `not-found`, if it's known.
+    pub kind: Option<&'static str>,
+    pub message: String,
+    /// The errors which caused this one, outermost first.
+    pub causes: Vec<String>,
+    /// Advice for fixing the error.
+    pub hint: Option<String>,
+    /// Changes the error relates to.
+    pub changes: Vec<ChangeNumber>,
+}
+
+impl JsonError {
+    pub fn new(error: &miette::Report) -> Self {
+        let mut messages = error.chain().map(|error| error.to_string());
+        let message = messages.next().unwrap_or_default();
+        let causes = messages.collect();
+
+        let hint = std::iter::successors(Some(error.as_ref() as &dyn Diagnostic), |diagnostic| {
+            diagnostic.diagnostic_source()
+        })
+        .find_map(|diagnostic| diagnostic.help())
+        .map(|help| help.to_string());
+
+        Self {
+            code: exit_code(error),
+            kind: error_kind(error).map(|kind| kind.name()),
+            message,
+            causes,
+            hint,
+            changes: related_changes(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use miette::miette;
+    use miette::Context;
+    use pretty_assertions::assert_eq;
+
+    use crate::error_kind::Classify;
+    use crate::error_kind::ErrorKind;
+
+    #[test]
+    fn test_json_error() {
+        let error = Err::<(), _>(
+            miette!(help = "Fix the conflicts", "Merge conflict in src/main.rs")
+                .classify(ErrorKind::Conflict),
+        )
+        .for_change("123".parse().unwrap())
+        .wrap_err("Failed to restack 123")
+        .unwrap_err();
+
+        assert_eq!(
+            JsonError::new(&error),
+            JsonError {
+                code: 5,
+                kind: Some("conflict"),
+                message: "Failed to restack 123".to_owned(),
+                causes: vec!["Merge conflict in src/main.rs".to_owned()],
+                hint: Some("Fix the conflicts".to_owned()),
+                changes: vec!["123".parse().unwrap()],
+            }
+        );
+
+        assert_eq!(
+            JsonError::new(&miette!("Oops")),
+            JsonError {
+                code: 1,
+                kind: None,
+                message: "Oops".to_owned(),
+                causes: vec![],
+                hint: None,
+                changes: vec![],
+            }
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+/// A label definition for a project, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-projects.html#label-definition-info>
+#[derive(serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct LabelDefinitionInfo {
+    /// The name of the label, like `Code-Review`.
+    pub name: String,
+    /// The project this label is defined in.
+    pub project_name: Option<String>,
+    /// The label function, like `MaxWithBlock` or `NoBlock`.
+    pub function: Option<String>,
+    /// Map from values (like `+2`) to their descriptions.
+    #[serde(default)]
+    pub values: BTreeMap<String, String>,
+    /// The default value for the label.
+    #[serde(default)]
+    pub default_value: i32,
+    /// The branches the label applies to, if restricted.
+    pub branches: Option<Vec<String>>,
+    /// The condition determining which votes are copied to new patch sets.
+    pub copy_condition: Option<String>,
+}
+
+impl LabelDefinitionInfo {
+    /// Get the label's values, sorted from highest to lowest.
+    pub fn sorted_values(&self) -> Vec<(i32, &str)> {
+        let mut values = self
+            .values
+            .iter()
+            .filter_map(|(value, description)| {
+                value
+                    .trim()
+                    .trim_start_matches('+')
+                    .parse::<i32>()
+                    .ok()
+                    .map(|value| (value, description.as_str()))
+            })
+            .collect::<Vec<_>>();
+        values.sort_by(|(a, _), (b, _)| b.cmp(a));
+        values
+    }
+}
@@ -0,0 +1,12 @@
+use crate::approval_info::ApprovalInfo;
+
+/// The state of a label on a change, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#label-info>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct LabelInfo {
+    /// All votes on the label, if requested with `o=DETAILED_LABELS`.
+    #[serde(default)]
+    pub all: Vec<ApprovalInfo>,
+}
@@ -0,0 +1,139 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use miette::miette;
+use regex::Regex;
+
+use crate::approval::Approval;
+
+/// A vote on a label, like `Code-Review+2` or `Verified=-1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelVote {
+    pub label: String,
+    pub value: i32,
+}
+
+impl FromStr for LabelVote {
+    type Err = miette::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        static RE: OnceLock<Regex> = OnceLock::new();
+        let captures = RE
+            .get_or_init(|| {
+                Regex::new(r"^(?P<label>[A-Za-z0-9-]*[A-Za-z])(?:=(?P<assign>[+-]?[0-9]+)|(?P<signed>[+-][0-9]+))?$")
+                    .expect("Regex parses")
+            })
+            .captures(s)
+            .ok_or_else(|| {
+                miette!(
+                    help = "Use a label and value like `Code-Review+2` or `Verified=-1`",
+                    "Invalid label vote: {s}"
+                )
+            })?;
+
+        let label = captures["label"].to_owned();
+        let value = match captures.name("assign").or_else(|| captures.name("signed")) {
+            Some(value) => value
+                .as_str()
+                .parse()
+                .map_err(|_| miette!("Invalid vote value in {s}"))?,
+            // Like `gerrit review`, a bare label means `+1`.
+            None => 1,
+        };
+
+        Ok(Self { label, value })
+    }
+}
+
+impl LabelVote {
+    /// Does one of `approvals` satisfy this vote?
+    ///
+    /// Positive votes are satisfied by votes at least as high, so `Code-Review+1` is satisfied by
+    /// a `Code-Review+2`. Other votes must match exactly.
+    pub fn is_satisfied_by(&self, approvals: &[Approval]) -> bool {
+        approvals
+            .iter()
+            .filter(|approval| approval.type_ == self.label)
+            .filter_map(|approval| approval.numeric_value())
+            .any(|value| {
+                if self.value > 0 {
+                    value >= self.value
+                } else {
+                    value == self.value
+                }
+            })
+    }
+}
+
+impl Display for LabelVote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{:+}", self.label, self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vote(label: &str, value: i32) -> LabelVote {
+        LabelVote {
+            label: label.to_owned(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_label_vote_from_str() {
+        assert_eq!(
+            "Code-Review+2".parse::<LabelVote>().unwrap(),
+            vote("Code-Review", 2)
+        );
+        assert_eq!(
+            "Code-Review-1".parse::<LabelVote>().unwrap(),
+            vote("Code-Review", -1)
+        );
+        assert_eq!(
+            "Verified=-1".parse::<LabelVote>().unwrap(),
+            vote("Verified", -1)
+        );
+        assert_eq!(
+            "Verified=1".parse::<LabelVote>().unwrap(),
+            vote("Verified", 1)
+        );
+        assert_eq!(
+            "Code-Review+0".parse::<LabelVote>().unwrap(),
+            vote("Code-Review", 0)
+        );
+        assert_eq!(
+            "Verified".parse::<LabelVote>().unwrap(),
+            vote("Verified", 1)
+        );
+        assert!("+2".parse::<LabelVote>().is_err());
+        assert!("Code-Review+".parse::<LabelVote>().is_err());
+        assert!("Code-Review 2".parse::<LabelVote>().is_err());
+    }
+
+    #[test]
+    fn test_label_vote_is_satisfied_by() {
+        let approvals: Vec<Approval> = serde_json::from_value(serde_json::json!([
+            { "type": "Code-Review", "value": "2", "by": { "name": "Jade", "username": "jade" } },
+            { "type": "Verified", "value": "-1", "by": { "name": "CI", "username": "ci" } },
+        ]))
+        .unwrap();
+        assert!(vote("Code-Review", 2).is_satisfied_by(&approvals));
+        assert!(vote("Code-Review", 1).is_satisfied_by(&approvals));
+        assert!(!vote("Verified", 1).is_satisfied_by(&approvals));
+        assert!(vote("Verified", -1).is_satisfied_by(&approvals));
+        assert!(!vote("Verified", -2).is_satisfied_by(&approvals));
+        assert!(!vote("Library-Compliance", 1).is_satisfied_by(&approvals));
+    }
+
+    #[test]
+    fn test_label_vote_display() {
+        assert_eq!(vote("Code-Review", 2).to_string(), "Code-Review+2");
+        assert_eq!(vote("Verified", -1).to_string(), "Verified-1");
+        assert_eq!(vote("Code-Review", 0).to_string(), "Code-Review+0");
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use comfy_table::Attribute;
+use comfy_table::Cell;
+use itertools::Itertools;
+
+use crate::format_table::new_table;
+use crate::gerrit::Gerrit;
+
+/// Format a label vote like Gerrit does, e.g. `+2`, ` 0`, or `-1`.
+pub fn format_vote(value: i32) -> String {
+    match value {
+        0 => " 0".to_owned(),
+        _ => format!("{value:+}"),
+    }
+}
+
+/// Format the labels for the current project, their values, who can vote on them, and the
+/// project's submit requirements.
+pub fn format_labels(gerrit: &mut Gerrit) -> miette::Result<String> {
+    let labels = gerrit.project_labels()?;
+    let voters = label_voters(gerrit)?;
+
+    let mut seen = BTreeSet::new();
+    let mut table = new_table(["Label", "Values", "Who can vote"]);
+    for label in &labels {
+        if !seen.insert(&label.name) {
+            // Overridden in a child project.
+            continue;
+        }
+
+        let values = label
+            .sorted_values()
+            .into_iter()
+            .map(|(value, description)| format!("{} {description}", format_vote(value)))
+            .join("\n");
+
+        let mut name = label.name.clone();
+        if let Some(function) = &label.function {
+            name.push_str(&format!("\n({function})"));
+        }
+        if let Some(branches) = &label.branches {
+            name.push_str(&format!("\non {}", branches.join(", ")));
+        }
+
+        table.add_row([
+            Cell::new(name).add_attribute(Attribute::Bold),
+            Cell::new(values),
+            Cell::new(
+                voters
+                    .get(&label.name)
+                    .map(|voters| voters.join("\n"))
+                    .unwrap_or_default(),
+            ),
+        ]);
+    }
+
+    let mut output = table.to_string();
+
+    match gerrit.project_submit_requirements() {
+        Ok(requirements) => {
+            if !requirements.is_empty() {
+                let mut table = new_table(["Submit requirement", "Satisfied when"]);
+                for requirement in requirements {
+                    let mut satisfied = requirement.submittability_expression;
+                    if let Some(applicable) = requirement.applicability_expression {
+                        satisfied.push_str(&format!("\nApplies to: {applicable}"));
+                    }
+                    if let Some(override_expression) = requirement.override_expression {
+                        satisfied.push_str(&format!("\nOverridden by: {override_expression}"));
+                    }
+                    table.add_row([
+                        Cell::new(requirement.name).add_attribute(Attribute::Bold),
+                        Cell::new(satisfied),
+                    ]);
+                }
+                output.push_str(&format!("\n\n{table}"));
+            }
+        }
+        Err(error) => {
+            // Submit requirements were added in Gerrit 3.6.
+            tracing::debug!("Failed to get submit requirements: {error}");
+        }
+    }
+
+    Ok(output)
+}
+
+/// Get a map from label names to descriptions of who can vote on them.
+///
+/// This walks the project's access rights and all of the projects it inherits from. Parent
+/// projects we can't read (often `All-Projects`) are skipped, along with their own parents.
+fn label_voters(gerrit: &mut Gerrit) -> miette::Result<BTreeMap<String, Vec<String>>> {
+    let mut voters = BTreeMap::<String, Vec<String>>::new();
+
+    let mut seen = BTreeSet::new();
+    let mut project = Some(gerrit.project().to_owned());
+    while let Some(name) = project.take() {
+        if !seen.insert(name.clone()) {
+            break;
+        }
+
+        let access = match gerrit.project_access(&name) {
+            Ok(access) => access,
+            Err(error) if name != gerrit.project() => {
+                tracing::debug!(
+                    project = name,
+                    "Skipping unreadable parent project: {error}"
+                );
+                break;
+            }
+            Err(error) => return Err(error),
+        };
+        for (ref_pattern, section) in &access.local {
+            for (permission_name, permission) in &section.permissions {
+                let label = match permission_name.strip_prefix("label-") {
+                    Some(label) => label,
+                    None => continue,
+                };
+
+                for (group_uuid, rule) in &permission.rules {
+                    let group = access
+                        .groups
+                        .get(group_uuid)
+                        .and_then(|group| group.name.as_deref())
+                        .unwrap_or(group_uuid);
+                    let range = match (rule.min, rule.max) {
+                        (Some(min), Some(max)) => {
+                            format!("{}..{}", format_vote(min), format_vote(max)).replace(' ', "")
+                        }
+                        _ => String::new(),
+                    };
+                    let mut description = match rule.action.as_str() {
+                        "BLOCK" => format!("{group}: blocked {range}"),
+                        "DENY" => format!("{group}: denied {range}"),
+                        _ => format!("{group}: {range}"),
+                    };
+                    description.push_str(&format!(" on {ref_pattern}"));
+                    if name != gerrit.project() {
+                        description.push_str(&format!(" (from {name})"));
+                    }
+
+                    voters
+                        .entry(label.to_owned())
+                        .or_default()
+                        .push(description);
+                }
+            }
+        }
+
+        project = access.inherits_from.map(|parent| parent.id);
+    }
+
+    Ok(voters)
+}
@@ -1,84 +1,308 @@
+mod account_info;
+mod activity;
+mod age;
+mod annotate_chain;
 mod approval;
+mod approval_info;
+mod archive;
+mod atom_feed;
+mod attention_set_info;
+mod attention_set_input;
 mod author;
+mod bisect;
 mod cache;
+mod chain;
 mod change;
+mod change_arg;
 mod change_id;
+mod change_index;
+mod change_info;
 mod change_key;
+mod change_kind;
+mod change_message_info;
 mod change_number;
+mod change_picker;
 mod change_status;
+mod cherry_pick_input;
+mod cleanup;
 mod cli;
+mod comment;
+mod comment_info;
+mod comment_input;
+mod comment_threads;
 mod commit_hash;
 mod commit_info;
+mod config;
+mod copy_condition;
 mod current_exe;
 mod current_patch_set;
+mod dashboard;
+mod date_format;
+mod delimited;
 mod dependency_graph;
 mod dependency_graph_builder;
 mod depends_on;
+mod depends_on_footer;
+mod did_you_mean;
+mod doctor;
+mod drafts;
 mod endpoint;
+mod error_kind;
+mod fetch_stack;
+mod foreach;
 mod format_bulleted_list;
+mod format_table;
 mod gerrit;
 mod gerrit_host;
+mod gerrit_pool;
 mod gerrit_project;
 mod git;
+mod git_operation;
 mod git_person_info;
+mod group_info;
+mod hashtags_input;
+mod hyperlink;
+mod import;
+mod init;
 mod install_tracing;
+mod json_error;
+mod label_definition_info;
+mod label_info;
+mod label_vote;
+mod labels;
+mod mock_server;
 mod needed_by;
+mod notes;
+mod offline_queue;
+mod output_format;
+mod output_template;
+mod owner_format;
 mod patchset;
+mod patchset_picker;
+mod permission_error;
+mod project_access_info;
+mod project_id;
+mod prune;
+mod push_options;
+mod push_template;
+mod push_wip;
 mod query;
+mod query_format;
 mod query_result;
+mod quote_path;
+mod rate_limit;
+mod recording;
 mod related_change_and_commit_info;
 mod related_changes_info;
+mod reminders;
+mod rerequest_review;
 mod restack;
+mod restack_hashtag;
 mod restack_push;
+mod restack_summary;
+mod review;
+mod review_input;
+mod reviewer_info;
+mod reviewer_input;
+mod revision_info;
+mod show;
+mod ssh;
+mod stack_log;
+mod stacks;
+mod status;
+mod stdin_input;
+mod sticky_votes;
 mod submit_label;
 mod submit_label_status;
 mod submit_records;
+mod submit_requirement;
+mod submit_requirement_info;
+mod submit_requirement_status;
 mod submit_status;
+mod sync;
+mod terminal_width;
+mod timeout;
+mod timezone;
 mod tmpdir;
+mod transport;
 mod unicode_tree;
+mod view;
 
+use std::io::Read;
+use std::process::ExitCode;
+
+use calm_io::stdout;
 use calm_io::stdoutln;
+use change_arg::ChangeArg;
 use clap::CommandFactory;
 use clap::Parser;
 use cli::Opts;
 use command_error::CommandExt;
+use error_kind::Classify;
+use error_kind::ErrorKind;
 use format_bulleted_list::format_bulleted_list;
 use git::Git;
 use install_tracing::install_tracing;
+use label_vote::LabelVote;
 use miette::IntoDiagnostic;
+use offline_queue::PerformOutcome;
+use output_format::OutputFormat;
+use output_template::OutputTemplate;
 use patchset::ChangePatchset;
+use patchset::ChangeSpec;
+use query::QueryOptions;
+use query_format::QueryFormat;
+use recording::Recording;
+use reminders::Reminders;
 use restack::create_todo;
+use stdin_input::MaybeStdin;
+use timezone::local_time_zone;
 
 #[allow(unused_imports)]
 use miette::Context;
 
-fn main() -> miette::Result<()> {
+fn main() -> ExitCode {
     let opts = Opts::parse();
-    install_tracing(&opts.log)?;
+    let output = opts.output;
+    let remote = opts.remote.clone();
+    match run(opts) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            let error = suggest_similar_changes(error, remote.as_deref());
+            let _ = match output {
+                OutputFormat::Human => calm_io::stderrln!("Error: {error:?}"),
+                OutputFormat::Json => calm_io::stderrln!(
+                    "{}",
+                    serde_json::to_string(&json_error::JsonError::new(&error)).unwrap_or_default()
+                ),
+            };
+            ExitCode::from(error_kind::exit_code(&error))
+        }
+    }
+}
+
+/// If a change the user asked for wasn't found, suggest similar ones.
+fn suggest_similar_changes(error: miette::Report, remote: Option<&str>) -> miette::Report {
+    if error_kind::error_kind(&error) != Some(error_kind::ErrorKind::NotFound) {
+        return error;
+    }
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match Git::new().gerrit(remote) {
+        Ok(gerrit) => did_you_mean::suggest_similar_changes(&gerrit, error, &args),
+        Err(_) => error,
+    }
+}
+
+fn run(opts: Opts) -> miette::Result<()> {
+    // Before `--log-file` starts its writer thread.
+    timezone::init();
+    install_tracing(&opts.log, opts.log_file.as_deref(), &opts.log_file_filter)?;
+    if opts.no_hyperlinks {
+        hyperlink::disable_hyperlinks();
+    }
+    config::Config::init(&Git::new())?;
+    Recording::init()?;
+    if let Some(server) = &opts.server_override {
+        // Read by `Gerrit::new`, and inherited by `git-gr` subprocesses.
+        std::env::set_var("GIT_GR_SERVER_OVERRIDE", server);
+    }
 
+    let remote = opts.remote.as_deref();
+    if let Some(remote) = remote {
+        // Subprocesses like `git-gr restack write-todo` should use the same remote.
+        std::env::set_var("GIT_GR_REMOTE", remote);
+    }
     match opts.command {
         cli::Command::Push {
             branch,
             target,
             restack,
+            include_others,
+            template,
+            base,
         } => {
             let git = Git::new();
-            let mut gerrit = git.gerrit(None)?;
+            let mut gerrit = git.gerrit(remote)?;
+            let mut template = match template {
+                Some(name) => push_template::PushTemplate::from_git_config(&git, &name)?,
+                None => Default::default(),
+            };
+            if let Some(base) = base {
+                let patchset = match base.patchset {
+                    Some(patchset) => base.change.with_patchset(patchset),
+                    None => gerrit.get_change(base.change)?.patchset(),
+                };
+                let commit = gerrit.fetch_cl(patchset)?;
+                tracing::debug!("Using {commit} from {patchset} as the push base");
+                template.options.base = Some(commit.to_string());
+            }
+            let target = target.or(template.branch);
             if restack {
                 let branch_str = branch.as_deref().unwrap_or("HEAD");
-                let todo = create_todo(&mut gerrit, branch_str)?;
+                let todo = create_todo(&mut gerrit, branch_str, include_others)?;
                 todo.write(&git)?;
-                gerrit.push(branch.clone(), target)?;
-                gerrit.restack(branch_str, None)?;
+                gerrit.push(branch.clone(), target, &template.options)?;
+                gerrit.restack(branch_str, None, include_others)?;
             } else {
-                gerrit.push(branch, target)?;
+                gerrit.push(branch, target, &template.options)?;
             }
         }
-        cli::Command::Checkout { patchset, number } => {
+        cli::Command::Checkout {
+            patchset,
+            change,
+            pick_patchset,
+            latest,
+        } => {
+            let change = match change {
+                MaybeStdin::Stdin => ChangeArg::Spec(ChangeSpec {
+                    change: stdin_input::read_change()?,
+                    patchset: None,
+                }),
+                MaybeStdin::Value(change) => change,
+            };
             let git = Git::new();
-            let gerrit = git.gerrit(None)?;
+            let gerrit = git.gerrit(remote)?;
+            let number = match &change {
+                ChangeArg::Spec(spec) => spec.change,
+                ChangeArg::Id(_) | ChangeArg::Url { .. } => {
+                    let resolved = gerrit.get_change(change.key())?;
+                    let urls = [resolved.url.clone(), resolved.web_url()];
+                    let mut hosts = vec![gerrit.host().host.as_str()];
+                    hosts.extend(urls.iter().filter_map(|url| change_arg::url_host(url)));
+                    hosts.sort();
+                    hosts.dedup();
+                    change.check_host(&hosts)?;
+                    resolved.number
+                }
+            };
+            let patchset = patchset.or_else(|| change.patchset());
+            if latest {
+                gerrit.forget_change(number)?;
+            }
+            let patchset = if pick_patchset {
+                Some(patchset_picker::pick_patchset(&gerrit, number)?)
+            } else {
+                patchset
+            };
             match patchset {
                 Some(patchset) => {
+                    let patch_sets = gerrit.get_patch_sets(number)?;
+                    if !patch_sets
+                        .iter()
+                        .any(|patch_set| patch_set.number == *patchset)
+                    {
+                        return Err(miette::miette!(
+                            "Change {number} has no patchset {patchset}; available patchsets:\n{}",
+                            format_bulleted_list(
+                                patch_sets
+                                    .iter()
+                                    .map(|patch_set| patch_set.summary())
+                                    .collect::<miette::Result<Vec<_>>>()?
+                            )
+                        )
+                        .classify(ErrorKind::NotFound)
+                        .for_change(number));
+                    }
                     gerrit.checkout_cl(ChangePatchset {
                         change: number,
                         patchset,
@@ -88,40 +312,101 @@ fn main() -> miette::Result<()> {
                     gerrit.checkout_cl(gerrit.get_change(number)?.patchset())?;
                 }
             }
+
+            let latest = gerrit.get_change(number)?.current_patch_set;
+            if git.rev_parse("HEAD")?.to_string() != latest.revision {
+                tracing::warn!(
+                    "Checked out an outdated patchset of {number}; the latest patchset is {}\n\
+                    Run `git gr checkout {number} --latest` to check out the latest patchset",
+                    latest.summary()?,
+                );
+            }
+        }
+        cli::Command::Pick {
+            print,
+            all_projects,
+            query,
+        } => {
+            let git = Git::new();
+            let gerrit = git.gerrit(remote)?;
+            let mut query = match query {
+                Some(query) => config::Config::global().expand_query(query)?,
+                None => config::Config::global().default_query().to_owned(),
+            };
+            if !all_projects {
+                query = query::in_project(&query, gerrit.project());
+            }
+            let change = change_picker::pick_change(&gerrit, query)?;
+            if print {
+                let _ = stdoutln!("{}", change.number);
+            } else {
+                gerrit.checkout_cl(change.patchset())?;
+            }
+        }
+        cli::Command::Fetch { number, all_stack } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            if all_stack {
+                fetch_stack::fetch_stack(&mut gerrit)?;
+            } else if let Some(number) = number {
+                let change = gerrit.get_change(number)?;
+                let git_ref = gerrit.fetch_cl(change.patchset())?;
+                let _ = stdoutln!("{git_ref}");
+            }
+        }
+        cli::Command::Log { query } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = match query {
+                Some(query) => gerrit.get_change(query)?.number,
+                None => gerrit.get_change(git.change_id("HEAD")?)?.number,
+            };
+            stack_log::stack_log(&mut gerrit, change)?;
         }
-        cli::Command::Fetch { number } => {
+        cli::Command::Foreach { stack, command } => {
             let git = Git::new();
-            let gerrit = git.gerrit(None)?;
-            let change = gerrit.get_change(number)?;
-            let git_ref = gerrit.fetch_cl(change.patchset())?;
-            let _ = stdoutln!("{git_ref}");
+            let mut gerrit = git.gerrit(remote)?;
+            foreach::foreach(&mut gerrit, stack, &command)?;
+        }
+        cli::Command::Bisect { stack, command } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            bisect::bisect(&mut gerrit, stack, &command)?;
+        }
+        cli::Command::AnnotateChain { dry_run } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            annotate_chain::annotate_chain(&mut gerrit, dry_run)?;
         }
         cli::Command::Up => {
             let git = Git::new();
-            let gerrit = git.gerrit(None)?;
+            let gerrit = git.gerrit(remote)?;
             gerrit.up()?;
         }
         cli::Command::Top => {
             let git = Git::new();
-            let gerrit = git.gerrit(None)?;
+            let gerrit = git.gerrit(remote)?;
             gerrit.top()?;
         }
         cli::Command::Down => {
             let git = Git::new();
-            let gerrit = git.gerrit(None)?;
+            let gerrit = git.gerrit(remote)?;
             gerrit.down()?;
         }
         cli::Command::Cli { args } => {
             let git = Git::new();
-            let gerrit = git.gerrit(None)?;
+            let gerrit = git.gerrit(remote)?;
             gerrit.command(args).status_checked().into_diagnostic()?;
         }
-        cli::Command::Restack { command } => {
+        cli::Command::Restack {
+            include_others,
+            command,
+        } => {
             let git = Git::new();
-            let mut gerrit = git.gerrit(None)?;
+            let mut gerrit = git.gerrit(remote)?;
             match command {
                 None => {
-                    gerrit.restack("HEAD", None)?;
+                    gerrit.restack("HEAD", None, include_others)?;
                 }
                 Some(command) => match command {
                     cli::Restack::Continue(restack_continue) => {
@@ -130,8 +415,25 @@ fn main() -> miette::Result<()> {
                     cli::Restack::Abort => {
                         gerrit.restack_abort()?;
                     }
-                    cli::Restack::Push => {
-                        gerrit.restack_push()?;
+                    cli::Restack::Push {
+                        copy,
+                        wip,
+                        require_label,
+                        rerequest_review,
+                        no_rerequest_review,
+                    } => {
+                        let rerequest_review = match (rerequest_review, no_rerequest_review) {
+                            (true, _) => Some(true),
+                            (_, true) => Some(false),
+                            _ => None,
+                        };
+                        let summary = gerrit
+                            .restack_push(wip, &require_label, rerequest_review)?
+                            .to_string();
+                        let _ = stdoutln!("{summary}");
+                        if copy {
+                            restack_summary::copy_to_clipboard(&summary);
+                        }
                     }
                     cli::Restack::This => {
                         gerrit.restack_this()?;
@@ -157,17 +459,32 @@ fn main() -> miette::Result<()> {
             query,
             mine,
             needs_review,
+            full,
+            size,
+            age,
+            kind,
+            votes,
+            my_vote,
+            stalled_after,
+            owner_format,
+            format,
+            template,
+            all_projects,
+            limit,
+            start,
+            all,
         } => {
             let git = Git::new();
-            let gerrit = git.gerrit(None)?;
+            let gerrit = git.gerrit(remote)?;
 
             let mut query = match query {
-                Some(query) => query,
+                Some(MaybeStdin::Stdin) => stdin_input::read_query()?,
+                Some(MaybeStdin::Value(query)) => config::Config::global().expand_query(query)?,
                 None => {
                     if mine || needs_review {
                         "".to_owned()
                     } else {
-                        "status:open -is:wip".to_owned()
+                        config::Config::global().default_query().to_owned()
                     }
                 }
             };
@@ -181,38 +498,855 @@ fn main() -> miette::Result<()> {
                 }
                 query.push_str(" -is:wip -is:reviewed");
             }
-            let table = gerrit.format_query_results(query)?;
-
-            let _ = stdoutln!("{table}");
+            if !all_projects {
+                query = query::in_project(&query, gerrit.project());
+            }
+            let options = query::QueryTableOptions {
+                full,
+                size,
+                age,
+                kind,
+                votes,
+                my_vote,
+                stalled_after,
+                owner_format,
+                review_priority: needs_review,
+                limit,
+                start,
+                all,
+            };
+            match (template, format) {
+                (Some(template), _) => {
+                    let template = OutputTemplate::new(template)?;
+                    let _ = stdoutln!(
+                        "{}",
+                        gerrit.format_query_template(query, options, &template)?
+                    );
+                }
+                (None, QueryFormat::Table) => {
+                    let table = gerrit.format_query_results(query, options)?;
+                    let _ = stdoutln!("{table}");
+                }
+                (None, QueryFormat::Atom) => {
+                    let _ = stdout!("{}", gerrit.format_query_feed(query, options)?);
+                }
+                (None, QueryFormat::Csv) => {
+                    let _ = stdout!("{}", gerrit.format_query_delimited(query, options, ',')?);
+                }
+                (None, QueryFormat::Tsv) => {
+                    let _ = stdout!("{}", gerrit.format_query_delimited(query, options, '\t')?);
+                }
+            }
         }
         cli::Command::Api { method, endpoint } => {
             let git = Git::new();
-            let mut gerrit = git.gerrit(None)?;
+            let mut gerrit = git.gerrit(remote)?;
             let response = gerrit.http_request(method, &endpoint)?;
             let _ = stdoutln!("{response}");
         }
-        cli::Command::ShowChain { query } => {
+        cli::Command::Dashboard {
+            full,
+            votes,
+            stalled_after,
+            owner_format,
+            all_projects,
+        } => {
+            let git = Git::new();
+            let gerrit = git.gerrit(remote)?;
+            let options = query::QueryTableOptions {
+                full,
+                votes,
+                stalled_after,
+                owner_format,
+                ..Default::default()
+            };
+            let dashboard = dashboard::format_dashboard(&gerrit, all_projects, options)?;
+            let _ = stdoutln!("{dashboard}");
+        }
+        cli::Command::Stacks {
+            owner,
+            group,
+            all_projects,
+            full,
+        } => {
             let git = Git::new();
-            let mut gerrit = git.gerrit(None)?;
-            let chain = gerrit.format_chain(query)?;
+            let gerrit = git.gerrit(remote)?;
+            let owner = if group {
+                stacks::StackOwner::Group(owner)
+            } else {
+                stacks::StackOwner::User(owner)
+            };
+            let stacks = stacks::format_stacks(&gerrit, &owner, all_projects, full)?;
+            let _ = stdoutln!("{stacks}");
+        }
+        cli::Command::ShowChain {
+            query,
+            full,
+            age,
+            stalled_after,
+            template,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let chain = match template {
+                Some(template) => {
+                    let template = OutputTemplate::new(template)?;
+                    gerrit.format_chain_template(query, &template)?
+                }
+                None => gerrit.format_chain(query, full, age.then_some(stalled_after))?,
+            };
             let _ = stdoutln!("{chain}");
         }
-        cli::Command::View { query } => {
+        cli::Command::View {
+            query,
+            chain,
+            print,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let query = git.change_key_or_head(query)?;
+            view::view(&mut gerrit, query, chain, print)?;
+        }
+        cli::Command::Groups { group, member } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            match group {
+                Some(group) => match member {
+                    Some(member) => {
+                        let member = if member == "self" {
+                            gerrit.username().to_owned()
+                        } else {
+                            member
+                        };
+                        let members = gerrit.group_members(&group)?;
+                        if members.iter().any(|account| account.matches(&member)) {
+                            let _ = stdoutln!("{member} is a member of {group}");
+                        } else {
+                            return Err(miette::miette!("{member} is not a member of {group}"));
+                        }
+                    }
+                    None => {
+                        let table = gerrit.format_group_members(&group)?;
+                        let _ = stdoutln!("{table}");
+                    }
+                },
+                None => {
+                    let table = gerrit.format_groups(member.as_deref())?;
+                    let _ = stdoutln!("{table}");
+                }
+            }
+        }
+        cli::Command::Labels => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let labels = labels::format_labels(&mut gerrit)?;
+            let _ = stdoutln!("{labels}");
+        }
+        cli::Command::Remind {
+            change,
+            by,
+            clear,
+            list,
+        } => {
+            let git = Git::new();
+            let gerrit = git.gerrit(remote)?;
+            if list {
+                let _ = stdout!("{}", Reminders::read(&git)?.format(&gerrit)?);
+            } else {
+                let change = git.change_key_or_head(change)?;
+                let change = gerrit.get_change(change)?.number;
+                if clear {
+                    if !Reminders::clear(&git, change)? {
+                        tracing::info!("No deadline for {change}");
+                    }
+                } else if let Some(by) = by {
+                    let reminder = Reminders::set(&git, change, &by)?;
+                    tracing::info!(
+                        "Review {} by {}",
+                        change.pretty(&gerrit)?,
+                        local_time_zone().to_local(reminder.by).date()
+                    );
+                }
+            }
+        }
+        cli::Command::Note { command } => {
             let git = Git::new();
-            let gerrit = git.gerrit(None)?;
-            let query = match query {
-                Some(query) => query,
-                None => git.change_id("HEAD")?.into(),
+            // Notes for the `HEAD` commit's change don't need to talk to Gerrit.
+            let change_id = |change: Option<String>| match change {
+                Some(change) => Ok(git.gerrit(remote)?.get_change(change)?.id),
+                None => git.change_id("HEAD"),
             };
+            match command {
+                cli::Note::Add { message, change } => {
+                    let change_id = change_id(change)?;
+                    notes::add_note(&git, &change_id, &message)?;
+                }
+                cli::Note::Show { change } => {
+                    let change_id = change_id(change)?;
+                    match notes::get_note(&git, &change_id)? {
+                        Some(note) => {
+                            let _ = stdoutln!("{note}");
+                        }
+                        None => {
+                            tracing::info!("No note for {change_id}");
+                        }
+                    }
+                }
+                cli::Note::Remove { change } => {
+                    let change_id = change_id(change)?;
+                    if !notes::remove_note(&git, &change_id)? {
+                        tracing::info!("No note for {change_id}");
+                    }
+                }
+            }
+        }
+        cli::Command::Show {
+            query,
+            json,
+            template,
+        } => {
+            let git = Git::new();
+            let gerrit = git.gerrit(remote)?;
+            let query = git.change_key_or_head(query)?;
             let change = gerrit.get_change(query)?;
-            let url = &change.url;
-            webbrowser::open(url)
-                .into_diagnostic()
-                .wrap_err_with(|| format!("Failed to open browser for {url}"))?;
+            if json {
+                let _ = stdoutln!(
+                    "{}",
+                    serde_json::to_string_pretty(&change).into_diagnostic()?
+                );
+            } else if let Some(template) = template {
+                let template = OutputTemplate::new(template)?;
+                let _ = stdoutln!("{}", template.render(&change)?);
+            } else {
+                let note = notes::get_note(&git, &change.id)?;
+                let _ = stdoutln!("{}", show::format_change(&change, note.as_deref())?);
+            }
+        }
+        cli::Command::Status => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = gerrit.get_change(git.change_id("HEAD")?)?;
+            let head = git.rev_parse("HEAD")?;
+            let _ = stdout!("{}", status::format_status(&mut gerrit, &change, &head)?);
+        }
+        cli::Command::Activity { query, since } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let query = git.change_key_or_head(query)?;
+            let change = gerrit.get_change(query)?.number;
+            let _ = stdout!("{}", activity::activity(&mut gerrit, change, since)?);
+        }
+        cli::Command::Archive { query, dir } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = gerrit.get_change(query)?.number;
+            archive::archive(&mut gerrit, change, &dir)?;
+        }
+        cli::Command::Import { patch, target } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            import::import(&mut gerrit, &patch, target)?;
+        }
+        cli::Command::Edit { change, command } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let query = git.change_key_or_head(change)?;
+            let change = gerrit.get_change(query)?.number;
+            match command {
+                cli::Edit::Put { path, source } => {
+                    let contents = match source {
+                        Some(source) => fs_err::read(source).into_diagnostic()?,
+                        None => {
+                            let mut contents = Vec::new();
+                            std::io::stdin()
+                                .read_to_end(&mut contents)
+                                .into_diagnostic()?;
+                            contents
+                        }
+                    };
+                    gerrit.edit_put_file(change, &path, contents)?;
+                }
+                cli::Edit::Delete { path } => {
+                    gerrit.edit_delete_file(change, &path)?;
+                }
+                cli::Edit::Publish => {
+                    gerrit.edit_publish(change)?;
+                    tracing::info!("Published change edit for {change}");
+                }
+                cli::Edit::Rebase => {
+                    gerrit.edit_rebase(change)?;
+                }
+                cli::Edit::Discard => {
+                    gerrit.edit_discard(change)?;
+                }
+            }
+        }
+        cli::Command::Review {
+            change,
+            query,
+            all_projects,
+            mut labels,
+            code_review,
+            verified,
+            message,
+            yes,
+        } => {
+            labels.extend(code_review.map(|value| LabelVote {
+                label: "Code-Review".to_owned(),
+                value,
+            }));
+            labels.extend(verified.map(|value| LabelVote {
+                label: "Verified".to_owned(),
+                value,
+            }));
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let changes = match query {
+                Some(mut query) => {
+                    if !all_projects {
+                        query = query::in_project(&query, gerrit.project());
+                    }
+                    gerrit
+                        .query(QueryOptions::new(query))?
+                        .changes
+                        .into_iter()
+                        .map(|change| change.number)
+                        .collect()
+                }
+                None => match change {
+                    Some(MaybeStdin::Stdin) => stdin_input::read_changes()?,
+                    Some(MaybeStdin::Value(query)) => vec![gerrit.get_change(query)?.number],
+                    None => vec![gerrit.get_change(git.change_id("HEAD")?)?.number],
+                },
+            };
+            let review = review_input::ReviewInput {
+                message,
+                labels: labels
+                    .into_iter()
+                    .map(|vote| (vote.label, vote.value))
+                    .collect(),
+                ..Default::default()
+            };
+            review::review_changes(
+                &mut gerrit,
+                &changes,
+                &offline_queue::Action::Review(review),
+                yes,
+            )?;
+        }
+        cli::Command::Comments {
+            command:
+                Some(cli::Comments::Reply {
+                    thread,
+                    change,
+                    message,
+                    resolve,
+                    unresolve,
+                }),
+            ..
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let change = gerrit.get_change(change)?;
+            let thread = comment_threads::find_thread(&gerrit.comments(change.number)?, &thread)?;
+            let message = match message {
+                Some(message) => Some(message),
+                None => comment::compose_message(&git, &change, None)?,
+            };
+            let unresolved = if resolve {
+                Some(false)
+            } else if unresolve {
+                Some(true)
+            } else {
+                None
+            };
+            match message {
+                Some(message) => {
+                    comment_threads::reply_to_thread(
+                        &mut gerrit,
+                        change.number,
+                        &thread,
+                        message,
+                        unresolved,
+                    )?;
+                }
+                None => {
+                    tracing::info!("Aborting reply due to empty message");
+                }
+            }
+        }
+        cli::Command::Comments {
+            command: Some(cli::Comments::Resolve(args)),
+            ..
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            comment_threads::resolve_threads(&git, &mut gerrit, args, true)?;
+        }
+        cli::Command::Comments {
+            command: Some(cli::Comments::Unresolve(args)),
+            ..
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            comment_threads::resolve_threads(&git, &mut gerrit, args, false)?;
+        }
+        cli::Command::Comments {
+            command: None,
+            change,
+            unresolved,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let change = gerrit.get_change(change)?.number;
+            let comments = gerrit.comments(change)?;
+            let output = comment_threads::format_comment_threads(&comments, unresolved);
+            if output.is_empty() {
+                tracing::info!("No comments on {}", change.pretty(&gerrit)?);
+            } else {
+                let _ = stdout!("{output}");
+            }
+        }
+        cli::Command::Drafts { command } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            match command {
+                cli::Drafts::Add {
+                    location,
+                    change,
+                    message,
+                    unresolved,
+                } => {
+                    let change = git.change_key_or_head(change)?;
+                    let change = gerrit.get_change(change)?;
+                    let message = match message {
+                        Some(message) => Some(message),
+                        None => comment::compose_message(&git, &change, None)?,
+                    };
+                    match message {
+                        Some(message) => {
+                            drafts::add_draft(
+                                &mut gerrit,
+                                &change,
+                                &location,
+                                message,
+                                unresolved,
+                            )?;
+                        }
+                        None => {
+                            tracing::info!("Aborting draft due to empty message");
+                        }
+                    }
+                }
+                cli::Drafts::List { change } => {
+                    let change = git.change_key_or_head(change)?;
+                    let change = gerrit.get_change(change)?.number;
+                    let output = drafts::format_drafts(&gerrit.drafts(change)?);
+                    if output.is_empty() {
+                        tracing::info!("No drafts on {}", change.pretty(&gerrit)?);
+                    } else {
+                        let _ = stdout!("{output}");
+                    }
+                }
+                cli::Drafts::Publish {
+                    change,
+                    mut labels,
+                    code_review,
+                    verified,
+                    message,
+                } => {
+                    labels.extend(code_review.map(|value| LabelVote {
+                        label: "Code-Review".to_owned(),
+                        value,
+                    }));
+                    labels.extend(verified.map(|value| LabelVote {
+                        label: "Verified".to_owned(),
+                        value,
+                    }));
+                    let change = git.change_key_or_head(change)?;
+                    let outcome = offline_queue::perform_or_queue(
+                        &mut gerrit,
+                        change,
+                        offline_queue::Action::Review(review_input::ReviewInput {
+                            message,
+                            labels: labels
+                                .into_iter()
+                                .map(|vote| (vote.label, vote.value))
+                                .collect(),
+                            drafts: Some("PUBLISH_ALL_REVISIONS".to_owned()),
+                            ..Default::default()
+                        }),
+                    )?;
+                    if let PerformOutcome::Performed(change) = outcome {
+                        tracing::info!("Published drafts on {}", change.pretty(&gerrit)?);
+                    }
+                }
+            }
+        }
+        cli::Command::Comment {
+            change,
+            message,
+            edit,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let message = match message {
+                Some(message) if !edit => Some(message),
+                message => comment::compose_message(
+                    &git,
+                    &gerrit.get_change(change.clone())?,
+                    message.as_deref(),
+                )?,
+            };
+            match message {
+                Some(message) => {
+                    let outcome = offline_queue::perform_or_queue(
+                        &mut gerrit,
+                        change,
+                        offline_queue::Action::Review(review_input::ReviewInput {
+                            message: Some(message),
+                            ..Default::default()
+                        }),
+                    )?;
+                    if let PerformOutcome::Performed(change) = outcome {
+                        tracing::info!("Commented on {}", change.pretty(&gerrit)?);
+                    }
+                }
+                None => {
+                    tracing::info!("Aborting comment due to empty message");
+                }
+            }
+        }
+        cli::Command::Rebase { change, base } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let change = gerrit.get_change(change)?.number;
+            gerrit.rebase(change, base.as_deref())?;
+            tracing::info!(
+                "Rebased {}; run `git gr checkout {change}` to check out the new patchset",
+                change.pretty(&gerrit)?
+            );
+        }
+        cli::Command::CherryPickTo {
+            branch,
+            change,
+            message,
+            keep_reviewers,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let change = gerrit.get_change(change)?.number;
+            let new_change = gerrit.cherry_pick(
+                change,
+                &cherry_pick_input::CherryPickInput {
+                    destination: branch,
+                    message,
+                    keep_reviewers,
+                },
+            )?;
+            let new_change = gerrit.get_change(new_change.number)?;
+            let _ = stdoutln!("{} {}", new_change.number, new_change.web_url());
+        }
+        cli::Command::Abandon { change, message } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let outcome = offline_queue::perform_or_queue(
+                &mut gerrit,
+                change,
+                offline_queue::Action::Abandon(message),
+            )?;
+            if let PerformOutcome::Performed(change) = outcome {
+                tracing::info!("Abandoned {}", change.pretty(&gerrit)?);
+            }
+        }
+        cli::Command::Restore { change, message } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let outcome = offline_queue::perform_or_queue(
+                &mut gerrit,
+                change,
+                offline_queue::Action::Restore(message),
+            )?;
+            if let PerformOutcome::Performed(change) = outcome {
+                tracing::info!("Restored {}", change.pretty(&gerrit)?);
+            }
+        }
+        cli::Command::Wip { change, message } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let outcome = offline_queue::perform_or_queue(
+                &mut gerrit,
+                change,
+                offline_queue::Action::Wip(message),
+            )?;
+            if let PerformOutcome::Performed(change) = outcome {
+                tracing::info!("Marked {} as work-in-progress", change.pretty(&gerrit)?);
+            }
+        }
+        cli::Command::Ready { change, message } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let outcome = offline_queue::perform_or_queue(
+                &mut gerrit,
+                change,
+                offline_queue::Action::Ready(message),
+            )?;
+            if let PerformOutcome::Performed(change) = outcome {
+                tracing::info!("Marked {} as ready for review", change.pretty(&gerrit)?);
+            }
+        }
+        cli::Command::Cleanup {
+            older_than,
+            owner,
+            message,
+            dry_run,
+            yes,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            cleanup::cleanup(&mut gerrit, &older_than, &owner, &message, dry_run, yes)?;
+        }
+        cli::Command::Resolve { change_id } => {
+            let git = Git::new();
+            let entry = match change_index::ChangeIndex::resolve(&git, &change_id)? {
+                Some(entry) => entry,
+                None => {
+                    let gerrit = git.gerrit(remote)?;
+                    let change = gerrit.get_change(change_id.clone())?;
+                    change_index::ChangeIndex::update(
+                        &git,
+                        change_id.clone(),
+                        change.number,
+                        change.patchset().patchset,
+                        None,
+                    )?;
+                    change_index::ChangeIndex::resolve(&git, &change_id)?
+                        .expect("Change was just added to the index")
+                }
+            };
+            match entry.branch {
+                Some(branch) => {
+                    let _ = stdoutln!("{}/{} {branch}", entry.number, entry.patchset);
+                }
+                None => {
+                    let _ = stdoutln!("{}/{}", entry.number, entry.patchset);
+                }
+            }
+        }
+        cli::Command::Topic {
+            change,
+            chain,
+            command,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let changes = chain::target_changes(&mut gerrit, change, chain)?;
+            let action = match command {
+                None | Some(cli::Topic::Get) => None,
+                Some(cli::Topic::Set { name }) => Some(offline_queue::Action::SetTopic(Some(name))),
+                Some(cli::Topic::Clear) => Some(offline_queue::Action::SetTopic(None)),
+            };
+            match action {
+                Some(action) => {
+                    chain::perform_on_changes(&mut gerrit, &changes, &action)?;
+                }
+                None => {
+                    for change in &changes {
+                        let topic = gerrit.get_change(*change)?.topic;
+                        if chain {
+                            let _ =
+                                stdoutln!("{change}: {}", topic.as_deref().unwrap_or("(no topic)"));
+                        } else if let Some(topic) = topic {
+                            let _ = stdoutln!("{topic}");
+                        }
+                    }
+                }
+            }
+        }
+        cli::Command::Hashtag {
+            change,
+            chain,
+            command,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let changes = chain::target_changes(&mut gerrit, change, chain)?;
+            let hashtags = match command {
+                cli::Hashtag::List => None,
+                cli::Hashtag::Add { tags } => Some(hashtags_input::HashtagsInput {
+                    add: tags,
+                    remove: Vec::new(),
+                }),
+                cli::Hashtag::Remove { tags } => Some(hashtags_input::HashtagsInput {
+                    add: Vec::new(),
+                    remove: tags,
+                }),
+            };
+            match hashtags {
+                Some(hashtags) => {
+                    let action = offline_queue::Action::SetHashtags(hashtags);
+                    chain::perform_on_changes(&mut gerrit, &changes, &action)?;
+                }
+                None => {
+                    for change in &changes {
+                        let hashtags = gerrit.get_change(*change)?.hashtags;
+                        if !chain {
+                            for hashtag in hashtags {
+                                let _ = stdoutln!("{hashtag}");
+                            }
+                        } else if hashtags.is_empty() {
+                            let _ = stdoutln!("{change}: (no hashtags)");
+                        } else {
+                            let _ = stdoutln!("{change}: {}", hashtags.join(", "));
+                        }
+                    }
+                }
+            }
+        }
+        cli::Command::Attention {
+            change,
+            chain,
+            command,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let changes = chain::target_changes(&mut gerrit, change, chain)?;
+            let inputs = |users: Vec<String>, reason: String| {
+                users
+                    .into_iter()
+                    .map(|user| attention_set_input::AttentionSetInput {
+                        user,
+                        reason: reason.clone(),
+                    })
+                    .collect()
+            };
+            let action = match command {
+                cli::Attention::List => None,
+                cli::Attention::Add { users, reason } => Some(
+                    offline_queue::Action::AddToAttentionSet(inputs(users, reason)),
+                ),
+                cli::Attention::Remove { users, reason } => Some(
+                    offline_queue::Action::RemoveFromAttentionSet(inputs(users, reason)),
+                ),
+            };
+            match action {
+                Some(action) => {
+                    chain::perform_on_changes(&mut gerrit, &changes, &action)?;
+                }
+                None => {
+                    for change in &changes {
+                        let attention = gerrit.attention_set(*change)?;
+                        if chain {
+                            let _ = stdoutln!("{change}:");
+                        }
+                        for entry in attention {
+                            let indent = if chain { "  " } else { "" };
+                            let _ = stdoutln!("{indent}{entry}");
+                        }
+                    }
+                }
+            }
+        }
+        cli::Command::Reviewers {
+            change,
+            chain,
+            command,
+        } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            let change = git.change_key_or_head(change)?;
+            let changes = chain::target_changes(&mut gerrit, change, chain)?;
+            let action = match command {
+                cli::Reviewers::List => None,
+                cli::Reviewers::Add { reviewers, cc } => {
+                    let state = if cc {
+                        reviewer_input::ReviewerState::Cc
+                    } else {
+                        reviewer_input::ReviewerState::Reviewer
+                    };
+                    Some(offline_queue::Action::AddReviewers(
+                        reviewers
+                            .into_iter()
+                            .map(|reviewer| reviewer_input::ReviewerInput { reviewer, state })
+                            .collect(),
+                    ))
+                }
+                cli::Reviewers::Remove { reviewers } => {
+                    Some(offline_queue::Action::RemoveReviewers(reviewers))
+                }
+            };
+            match action {
+                Some(action) => {
+                    chain::perform_on_changes(&mut gerrit, &changes, &action)?;
+                }
+                None => {
+                    for change in &changes {
+                        let reviewers = gerrit.reviewers(*change)?;
+                        if chain {
+                            let _ = stdoutln!("{change}:");
+                        }
+                        for reviewer in reviewers {
+                            let indent = if chain { "  " } else { "" };
+                            let _ = stdoutln!("{indent}{reviewer}");
+                        }
+                    }
+                }
+            }
+        }
+        cli::Command::Sync { flush, yes } => {
+            let git = Git::new();
+            let mut gerrit = git.gerrit(remote)?;
+            if flush {
+                offline_queue::flush(&mut gerrit)?;
+            } else {
+                let queue = offline_queue::ActionQueue::read(&git)?;
+                if !queue.actions.is_empty() {
+                    tracing::info!(
+                        "Queued actions; use `--flush` to send them:\n{}",
+                        format_bulleted_list(&queue.actions)
+                    );
+                }
+                sync::sync_branches(&gerrit, yes)?;
+            }
+        }
+        cli::Command::Prune { dry_run, yes } => {
+            let git = Git::new();
+            let gerrit = git.gerrit(remote)?;
+            prune::prune(&gerrit, dry_run, yes)?;
+        }
+        cli::Command::MockServer { fixture, port } => {
+            mock_server::mock_server(&fixture, port)?;
+        }
+        cli::Command::MockSsh { server, args } => {
+            mock_server::mock_ssh(&server, &args)?;
+        }
+        cli::Command::RecordSsh { dir, args } => {
+            recording::record_ssh(Recording::Record(dir), &args)?;
+        }
+        cli::Command::ReplaySsh { dir, args } => {
+            recording::replay_ssh(Recording::Replay(dir), &args)?;
+        }
+        cli::Command::Init => {
+            init::init(&Git::new())?;
+        }
+        cli::Command::Doctor => {
+            doctor::doctor(&Git::new(), remote)?;
         }
         cli::Command::ClearCache => {
             let git = Git::new();
-            let mut gerrit = git.gerrit(None)?;
+            let mut gerrit = git.gerrit(remote)?;
             gerrit.clear_cache();
         }
     }
@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use calm_io::stdout;
+use camino::Utf8Path;
+use fs_err as fs;
+use miette::miette;
+use miette::Context;
+use miette::IntoDiagnostic;
+use serde_json::Value;
+use tiny_http::Response;
+use tiny_http::Server;
+
+/// Canned data for a [`MockServer`].
+///
+/// Changes are in the format printed by `gerrit query --format json`, and REST responses are
+/// keyed by endpoint (like `accounts/self`) or method and endpoint (like
+/// `POST changes/123/abandon`).
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+pub struct Fixture {
+    #[serde(default)]
+    pub changes: Vec<Value>,
+    #[serde(default)]
+    pub rest: BTreeMap<String, Value>,
+}
+
+impl Fixture {
+    pub fn from_path(path: &Utf8Path) -> miette::Result<Self> {
+        serde_json::from_str(&fs::read_to_string(path).into_diagnostic()?)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to parse mock server fixture `{path}`"))
+    }
+}
+
+/// A fake Gerrit server, for tests and debugging.
+///
+/// Serves the REST API under `/a/`, and `gerrit` SSH commands (sent by `git-gr mock-ssh`) as
+/// JSON arrays of arguments `POST`ed to `/ssh`.
+///
+/// Point `git-gr` at it with `--server-override` or `GIT_GR_SERVER_OVERRIDE`.
+pub struct MockServer {
+    server: Server,
+    fixture: Fixture,
+}
+
+impl MockServer {
+    pub fn new(fixture: Fixture, port: u16) -> miette::Result<Self> {
+        let server = Server::http(("127.0.0.1", port))
+            .map_err(|error| miette!("Failed to start mock server: {error}"))?;
+        Ok(Self { server, fixture })
+    }
+
+    /// The base URL to pass to `--server-override`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.server.server_addr())
+    }
+
+    /// Serve requests forever.
+    pub fn serve(&self) -> miette::Result<()> {
+        for mut request in self.server.incoming_requests() {
+            let mut body = String::new();
+            request
+                .as_reader()
+                .read_to_string(&mut body)
+                .into_diagnostic()?;
+            let method = request.method().to_string();
+            let url = request.url().to_owned();
+            tracing::debug!(method, url, body, "Mock server request");
+
+            let response = match url.strip_prefix("/a/") {
+                Some(endpoint) => self.rest(&method, endpoint),
+                None if url == "/ssh" => match serde_json::from_str::<Vec<String>>(&body) {
+                    Ok(args) => self.ssh(&args),
+                    Err(error) => Err(error.to_string()),
+                },
+                None => Err(format!("Not found: {url}")),
+            };
+
+            let response = match response {
+                Ok(body) => Response::from_string(body),
+                Err(error) => Response::from_string(error).with_status_code(404),
+            };
+            request.respond(response).into_diagnostic()?;
+        }
+        Ok(())
+    }
+
+    fn rest(&self, method: &str, endpoint: &str) -> Result<String, String> {
+        let value = self
+            .fixture
+            .rest
+            .get(&format!("{method} {endpoint}"))
+            .or_else(|| {
+                if method == "GET" {
+                    self.fixture.rest.get(endpoint)
+                } else {
+                    None
+                }
+            });
+        match value {
+            // Gerrit prefixes JSON responses to prevent XSSI.
+            Some(value) => Ok(format!(")]}}'\n{value}")),
+            // Accept writes we don't know about.
+            None if method != "GET" => Ok(")]}'\n{}".to_owned()),
+            None => Err(format!("Not found: {endpoint}")),
+        }
+    }
+
+    fn ssh(&self, args: &[String]) -> Result<String, String> {
+        match args.first().map(|arg| arg.as_str()) {
+            Some("version") => Ok("gerrit version mock\n".to_owned()),
+            Some("set-account") if args.iter().any(|arg| arg == "--generate-http-password") => {
+                Ok("New password: mock\n".to_owned())
+            }
+            Some("query") => {
+                let query = args
+                    .iter()
+                    .position(|arg| arg == "--")
+                    .and_then(|index| args.get(index + 1))
+                    .ok_or_else(|| format!("No query given: {args:?}"))?;
+                Ok(self.query(query))
+            }
+            _ => Err(format!("Unsupported command: gerrit {}", args.join(" "))),
+        }
+    }
+
+    fn query(&self, query: &str) -> String {
+        let mut output = String::new();
+        let mut count = 0;
+        for change in &self.fixture.changes {
+            if query
+                .split_whitespace()
+                .map(|term| term.trim_matches(['(', ')']))
+                .all(|term| query_term_matches(change, term))
+            {
+                output.push_str(&change.to_string());
+                output.push('\n');
+                count += 1;
+            }
+        }
+        output.push_str(
+            &serde_json::json!({
+                "type": "stats",
+                "rowCount": count,
+                "runTimeMilliseconds": 0,
+                "moreChanges": false,
+            })
+            .to_string(),
+        );
+        output.push('\n');
+        output
+    }
+}
+
+/// Check if a change matches a single term of a Gerrit query.
+///
+/// Only a few operators are supported; unknown operators match everything.
+fn query_term_matches(change: &Value, term: &str) -> bool {
+    let field = |name: &str| change.get(name).and_then(|value| value.as_str());
+    let number = change.get("number").and_then(|value| value.as_u64());
+
+    let (operator, value) = term.split_once(':').unwrap_or(("change", term));
+    match operator {
+        "change" => {
+            value
+                .parse::<u64>()
+                .is_ok_and(|value| Some(value) == number)
+                || field("id") == Some(value)
+        }
+        "status" => match value {
+            "open" => field("status") == Some("NEW"),
+            "closed" => field("status") != Some("NEW"),
+            _ => field("status").map(|status| status.eq_ignore_ascii_case(value)) == Some(true),
+        },
+        "project" | "branch" | "topic" => field(operator) == Some(value),
+        "owner" => {
+            value == "self"
+                || change
+                    .get("owner")
+                    .and_then(|owner| owner.get("username"))
+                    .and_then(|username| username.as_str())
+                    == Some(value)
+        }
+        _ => true,
+    }
+}
+
+/// Start a mock server, printing its URL.
+pub fn mock_server(fixture: &Utf8Path, port: u16) -> miette::Result<()> {
+    let server = MockServer::new(Fixture::from_path(fixture)?, port)?;
+    let _ = stdout!("{}\n", server.url());
+    server.serve()
+}
+
+/// Forward a `gerrit` SSH command to a mock server, printing its output.
+///
+/// This stands in for `ssh` when `--server-override` is used; `args` are the destination
+/// followed by the remote command.
+pub fn mock_ssh(server: &str, args: &[String]) -> miette::Result<()> {
+    let command = args.get(1..).unwrap_or_default().join(" ");
+    let mut args = shell_words::split(&command).into_diagnostic()?;
+    if args.first().map(|arg| arg.as_str()) != Some("gerrit") {
+        return Err(miette!("Expected a `gerrit` command: {command}"));
+    }
+    args.remove(0);
+
+    let response = reqwest::blocking::Client::new()
+        .post(format!("{server}/ssh"))
+        .body(serde_json::to_string(&args).into_diagnostic()?)
+        .send()
+        .into_diagnostic()?;
+    let status = response.status();
+    let body = response.text().into_diagnostic()?;
+    if status.is_success() {
+        let _ = stdout!("{body}");
+        Ok(())
+    } else {
+        Err(miette!("{body}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_term_matches() {
+        let change = serde_json::json!({
+            "number": 123,
+            "id": "I0123456789abcdef0123456789abcdef01234567",
+            "project": "gr",
+            "status": "NEW",
+            "owner": { "username": "rbt" },
+        });
+        assert!(query_term_matches(&change, "123"));
+        assert!(query_term_matches(&change, "change:123"));
+        assert!(query_term_matches(
+            &change,
+            "I0123456789abcdef0123456789abcdef01234567"
+        ));
+        assert!(!query_term_matches(&change, "124"));
+        assert!(query_term_matches(&change, "status:open"));
+        assert!(!query_term_matches(&change, "status:merged"));
+        assert!(query_term_matches(&change, "project:gr"));
+        assert!(query_term_matches(&change, "owner:rbt"));
+        assert!(!query_term_matches(&change, "owner:someone-else"));
+        assert!(query_term_matches(&change, "is:attention"));
+    }
+}
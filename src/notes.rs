@@ -0,0 +1,37 @@
+use crate::change_id::ChangeId;
+use crate::git::Git;
+
+/// The notes ref personal change notes are stored in.
+pub const NOTES_REF: &str = "refs/notes/git-gr";
+
+/// Get the note for a change, if it has one.
+pub fn get_note(git: &Git, change_id: &ChangeId) -> miette::Result<Option<String>> {
+    git.notes_show(NOTES_REF, &note_object(git, change_id, false)?)
+}
+
+/// Add a paragraph to the note for a change, creating the note if needed.
+pub fn add_note(git: &Git, change_id: &ChangeId, message: &str) -> miette::Result<()> {
+    git.notes_append(NOTES_REF, &note_object(git, change_id, true)?, message)
+}
+
+/// Remove the note for a change. Returns `false` if there was no note.
+pub fn remove_note(git: &Git, change_id: &ChangeId) -> miette::Result<bool> {
+    let object = note_object(git, change_id, false)?;
+    if git.notes_show(NOTES_REF, &object)?.is_none() {
+        return Ok(false);
+    }
+    git.notes_remove(NOTES_REF, &object)?;
+    Ok(true)
+}
+
+/// The first line of a change's note, for labels in `show-chain` trees.
+pub fn note_label(git: &Git, change_id: &ChangeId) -> miette::Result<Option<String>> {
+    Ok(get_note(git, change_id)?
+        .and_then(|note| note.lines().next().map(|line| format!("✎ {line}"))))
+}
+
+/// Notes are attached to a blob containing the Change-Id, rather than to a commit, so that they
+/// stay with the change across patchsets.
+fn note_object(git: &Git, change_id: &ChangeId, write: bool) -> miette::Result<String> {
+    git.hash_object(&change_id.to_string(), write)
+}
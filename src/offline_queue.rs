@@ -0,0 +1,437 @@
+use std::fmt::Display;
+use std::io::BufReader;
+use std::io::BufWriter;
+
+use camino::Utf8PathBuf;
+use fs_err as fs;
+use fs_err::File;
+use miette::Context;
+use miette::IntoDiagnostic;
+use serde_with::serde_as;
+use serde_with::TimestampSeconds;
+use time::OffsetDateTime;
+
+use crate::attention_set_input::AttentionSetInput;
+use crate::change_key::ChangeKey;
+use crate::change_number::ChangeNumber;
+use crate::change_status::ChangeStatus;
+use crate::error_kind::error_kind;
+use crate::error_kind::ErrorKind;
+use crate::format_bulleted_list;
+use crate::gerrit::Gerrit;
+use crate::git::Git;
+use crate::hashtags_input::HashtagsInput;
+use crate::labels::format_vote;
+use crate::review_input::ReviewInput;
+use crate::reviewer_input::ReviewerInput;
+use crate::reviewer_input::ReviewerState;
+use crate::timeout::is_connection_message;
+
+/// An action on a change which can be queued while offline.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub enum Action {
+    /// Post votes and/or a message.
+    Review(ReviewInput),
+    /// Set or clear the topic.
+    SetTopic(Option<String>),
+    /// Add and remove hashtags.
+    SetHashtags(HashtagsInput),
+    /// Abandon the change, with an optional message.
+    Abandon(Option<String>),
+    /// Restore the abandoned change, with an optional message.
+    Restore(Option<String>),
+    /// Mark the change as work-in-progress, with an optional message.
+    Wip(Option<String>),
+    /// Mark the change as ready for review, with an optional message.
+    Ready(Option<String>),
+    /// Add reviewers or CCs.
+    AddReviewers(Vec<ReviewerInput>),
+    /// Remove reviewers or CCs, by username or email.
+    RemoveReviewers(Vec<String>),
+    /// Add accounts to the attention set.
+    AddToAttentionSet(Vec<AttentionSetInput>),
+    /// Remove accounts from the attention set.
+    RemoveFromAttentionSet(Vec<AttentionSetInput>),
+}
+
+impl Action {
+    /// Perform this action on a change.
+    pub fn perform(&self, gerrit: &mut Gerrit, change: ChangeNumber) -> miette::Result<()> {
+        match self {
+            Action::Review(review) => gerrit.set_review(change, review),
+            Action::SetTopic(topic) => gerrit.set_topic(change, topic.as_deref()),
+            Action::SetHashtags(hashtags) => gerrit.set_hashtags(change, hashtags),
+            Action::Abandon(message) => gerrit.abandon(change, message.as_deref()),
+            Action::Restore(message) => gerrit.restore(change, message.as_deref()),
+            Action::Wip(message) => gerrit.set_wip(change, true, message.as_deref()),
+            Action::Ready(message) => gerrit.set_wip(change, false, message.as_deref()),
+            Action::AddReviewers(reviewers) => reviewers
+                .iter()
+                .try_for_each(|reviewer| gerrit.add_reviewer(change, reviewer)),
+            Action::RemoveReviewers(accounts) => accounts
+                .iter()
+                .try_for_each(|account| gerrit.remove_reviewer(change, account)),
+            Action::AddToAttentionSet(inputs) => inputs
+                .iter()
+                .try_for_each(|input| gerrit.add_to_attention_set(change, input)),
+            Action::RemoveFromAttentionSet(inputs) => inputs
+                .iter()
+                .try_for_each(|input| gerrit.remove_from_attention_set(change, input)),
+        }
+    }
+
+    /// Split this action into steps which are each sent to Gerrit in a single request.
+    ///
+    /// If we lose the connection partway through adding several reviewers, only the reviewers
+    /// which weren't added yet are queued.
+    pub fn steps(self) -> Vec<Action> {
+        match self {
+            Action::AddReviewers(reviewers) => reviewers
+                .into_iter()
+                .map(|reviewer| Action::AddReviewers(vec![reviewer]))
+                .collect(),
+            Action::RemoveReviewers(accounts) => accounts
+                .into_iter()
+                .map(|account| Action::RemoveReviewers(vec![account]))
+                .collect(),
+            Action::AddToAttentionSet(inputs) => inputs
+                .into_iter()
+                .map(|input| Action::AddToAttentionSet(vec![input]))
+                .collect(),
+            Action::RemoveFromAttentionSet(inputs) => inputs
+                .into_iter()
+                .map(|input| Action::RemoveFromAttentionSet(vec![input]))
+                .collect(),
+            action => vec![action],
+        }
+    }
+
+    /// Can this action be performed on a change with the given status?
+    ///
+    /// Only abandoned changes can be restored, and only open changes can be modified otherwise.
+    pub fn applies_to(&self, status: ChangeStatus) -> bool {
+        match self {
+            Action::Restore(_) => status == ChangeStatus::Abandoned,
+            _ => status == ChangeStatus::New,
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Review(review) => {
+                let mut parts = review
+                    .labels
+                    .iter()
+                    .map(|(label, value)| format!("{label}{}", format_vote(*value).trim()))
+                    .collect::<Vec<_>>();
+                if review.message.is_some() {
+                    parts.push("message".to_owned());
+                }
+                if !review.add_to_attention_set.is_empty() {
+                    parts.push(format!(
+                        "attention to {}",
+                        format_users(&review.add_to_attention_set)
+                    ));
+                }
+                write!(f, "review ({})", parts.join(", "))
+            }
+            Action::SetTopic(Some(topic)) => write!(f, "set topic to {topic:?}"),
+            Action::SetTopic(None) => write!(f, "clear topic"),
+            Action::SetHashtags(hashtags) => {
+                let format_tags = |tags: &[String]| {
+                    tags.iter()
+                        .map(|tag| format!("#{tag}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                let mut parts = Vec::new();
+                if !hashtags.add.is_empty() {
+                    parts.push(format!("add {}", format_tags(&hashtags.add)));
+                }
+                if !hashtags.remove.is_empty() {
+                    parts.push(format!("remove {}", format_tags(&hashtags.remove)));
+                }
+                write!(f, "{}", parts.join(" and "))
+            }
+            Action::Abandon(_) => write!(f, "abandon"),
+            Action::Restore(_) => write!(f, "restore"),
+            Action::Wip(_) => write!(f, "mark as work-in-progress"),
+            Action::Ready(_) => write!(f, "mark as ready for review"),
+            Action::AddReviewers(reviewers) => {
+                let names = |state: ReviewerState| {
+                    reviewers
+                        .iter()
+                        .filter(|reviewer| reviewer.state == state)
+                        .map(|reviewer| reviewer.reviewer.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                let mut parts = Vec::new();
+                let added = names(ReviewerState::Reviewer);
+                if !added.is_empty() {
+                    parts.push(format!("add reviewers {added}"));
+                }
+                let cced = names(ReviewerState::Cc);
+                if !cced.is_empty() {
+                    parts.push(format!("CC {cced}"));
+                }
+                write!(f, "{}", parts.join(" and "))
+            }
+            Action::RemoveReviewers(accounts) => {
+                write!(f, "remove reviewers {}", accounts.join(", "))
+            }
+            Action::AddToAttentionSet(inputs) => {
+                write!(f, "add {} to attention set", format_users(inputs))
+            }
+            Action::RemoveFromAttentionSet(inputs) => {
+                write!(f, "remove {} from attention set", format_users(inputs))
+            }
+        }
+    }
+}
+
+fn format_users(inputs: &[AttentionSetInput]) -> String {
+    inputs
+        .iter()
+        .map(|input| input.user.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[serde_as]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct QueuedAction {
+    /// The change to act on, resolved when the action is sent so that queueing doesn't need
+    /// the network.
+    pub change: ChangeKey,
+    pub action: Action,
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    pub queued_at: OffsetDateTime,
+}
+
+impl Display for QueuedAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.change, self.action)
+    }
+}
+
+/// A journal of actions to perform once we're back online.
+///
+/// Stored in the `.git` directory.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
+pub struct ActionQueue {
+    pub actions: Vec<QueuedAction>,
+}
+
+impl ActionQueue {
+    pub fn read(git: &Git) -> miette::Result<Self> {
+        let path = queue_path(git)?;
+        if path.exists() {
+            serde_json::from_reader(BufReader::new(File::open(&path).into_diagnostic()?))
+                .into_diagnostic()
+                .wrap_err_with(|| format!("Failed to read offline queue from `{path}`"))
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn write(&self, git: &Git) -> miette::Result<()> {
+        let path = queue_path(git)?;
+        if self.actions.is_empty() {
+            if path.exists() {
+                fs::remove_file(path).into_diagnostic()?;
+            }
+            return Ok(());
+        }
+        let writer = BufWriter::new(File::create(path).into_diagnostic()?);
+        serde_json::to_writer(writer, self).into_diagnostic()?;
+        Ok(())
+    }
+}
+
+pub fn queue_path(git: &Git) -> miette::Result<Utf8PathBuf> {
+    git.get_git_dir()
+        .map(|git_dir| git_dir.join("git-gr-offline-queue.json"))
+}
+
+/// The result of [`perform_or_queue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerformOutcome {
+    /// The action was sent to Gerrit.
+    Performed(ChangeNumber),
+    /// The action was queued, to be sent by `git gr sync --flush`.
+    Queued,
+}
+
+/// Did an error happen because we couldn't connect to Gerrit (or it stopped responding), rather
+/// than because Gerrit rejected a request?
+fn is_unreachable(error: &miette::Report) -> bool {
+    error_kind(error) == Some(ErrorKind::Timeout)
+        || error
+            .chain()
+            .any(|cause| is_connection_message(&cause.to_string()))
+}
+
+/// Perform an action on a change, or queue it for later if Gerrit is unreachable.
+///
+/// Actions are always queued if `GIT_GR_OFFLINE` is set. The change is only resolved when the
+/// action is performed, so queueing an action never touches the network.
+///
+/// Only connection errors and timeouts queue the action; other errors, like Gerrit rejecting the
+/// action, are returned. If the connection drops partway through an action with several
+/// [steps](Action::steps), only the steps which weren't sent are queued.
+pub fn perform_or_queue(
+    gerrit: &mut Gerrit,
+    change: impl Into<ChangeKey>,
+    action: Action,
+) -> miette::Result<PerformOutcome> {
+    let change: ChangeKey = change.into();
+    let offline = std::env::var("GIT_GR_OFFLINE")
+        .map(|value| !value.is_empty())
+        .unwrap_or(false);
+    if offline {
+        return queue(gerrit, change, vec![action], true);
+    }
+
+    let number = match gerrit.get_change(change.clone()) {
+        Ok(resolved) => resolved.number,
+        Err(error) if is_unreachable(&error) => {
+            tracing::debug!("Failed to look up {change}: {error}");
+            return queue(gerrit, change, vec![action], false);
+        }
+        Err(error) => return Err(error),
+    };
+
+    let steps = action.steps();
+    for (i, step) in steps.iter().enumerate() {
+        if let Err(error) = step.perform(gerrit, number) {
+            gerrit.forget_change(number)?;
+            if !is_unreachable(&error) {
+                return Err(error);
+            }
+            tracing::debug!("Failed to {step} on {number}: {error}");
+            return queue(gerrit, number.into(), steps[i..].to_vec(), false);
+        }
+    }
+    gerrit.forget_change(number)?;
+    Ok(PerformOutcome::Performed(number))
+}
+
+/// Add actions on a change to the end of the queue.
+fn queue(
+    gerrit: &Gerrit,
+    change: ChangeKey,
+    actions: Vec<Action>,
+    offline: bool,
+) -> miette::Result<PerformOutcome> {
+    let git = gerrit.git();
+    let mut queue = ActionQueue::read(&git)?;
+    let queued_at = OffsetDateTime::now_utc();
+    for action in actions {
+        let queued = QueuedAction {
+            change: change.clone(),
+            action,
+            queued_at,
+        };
+        if offline {
+            tracing::info!("Queued {queued}; run `git-gr sync --flush` to send it");
+        } else {
+            tracing::warn!(
+                "Gerrit is unreachable; queued {queued}. Run `git-gr sync --flush` to send it later."
+            );
+        }
+        queue.actions.push(queued);
+    }
+    queue.write(&git)?;
+    Ok(PerformOutcome::Queued)
+}
+
+/// Replay queued actions in order.
+///
+/// Actions which no longer apply, like votes on changes which have since been merged or
+/// abandoned, are dropped and reported.
+/// If Gerrit is unreachable, the remaining actions are kept in the queue.
+pub fn flush(gerrit: &mut Gerrit) -> miette::Result<()> {
+    let git = gerrit.git();
+    let mut queue = ActionQueue::read(&git)?;
+    if queue.actions.is_empty() {
+        tracing::info!("No queued actions");
+        return Ok(());
+    }
+
+    let mut conflicts = Vec::new();
+    let mut performed = 0;
+    while !queue.actions.is_empty() {
+        let queued = queue.actions.remove(0);
+        let change = match gerrit.get_change_fresh(queued.change.clone()) {
+            Ok(change) => change,
+            Err(error) => {
+                queue.actions.insert(0, queued);
+                queue.write(&git)?;
+                return Err(error).wrap_err("Failed to replay queued actions");
+            }
+        };
+
+        if !queued.action.applies_to(change.status) {
+            conflicts.push(format!("{queued} (change is {})", change.status));
+            queue.write(&git)?;
+            continue;
+        }
+
+        let steps = queued.action.clone().steps();
+        for (i, step) in steps.iter().enumerate() {
+            if let Err(error) = step.perform(gerrit, change.number) {
+                gerrit.forget_change(change.number)?;
+                // Keep the steps which weren't sent, so they aren't sent twice.
+                queue.actions.splice(
+                    0..0,
+                    steps[i..].iter().map(|step| QueuedAction {
+                        action: step.clone(),
+                        ..queued.clone()
+                    }),
+                );
+                queue.write(&git)?;
+                return Err(error).wrap_err("Failed to replay queued actions");
+            }
+        }
+        tracing::info!("Sent {queued}");
+        gerrit.forget_change(change.number)?;
+        performed += 1;
+        queue.write(&git)?;
+    }
+
+    tracing::info!("Sent {performed} queued actions");
+    if !conflicts.is_empty() {
+        tracing::warn!(
+            "Dropped queued actions which no longer apply:\n{}",
+            format_bulleted_list(conflicts)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_action_steps() {
+        let steps = Action::RemoveReviewers(vec!["alice".to_owned(), "bob".to_owned()])
+            .steps()
+            .iter()
+            .map(|step| step.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(steps, ["remove reviewers alice", "remove reviewers bob"]);
+
+        let steps = Action::Abandon(None)
+            .steps()
+            .iter()
+            .map(|step| step.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(steps, ["abandon"]);
+    }
+}
@@ -0,0 +1,9 @@
+/// How to print errors.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Graphical reports for people.
+    #[default]
+    Human,
+    /// A JSON object on stderr, for wrappers and editor plugins.
+    Json,
+}
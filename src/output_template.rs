@@ -0,0 +1,78 @@
+use miette::miette;
+use minijinja::Environment;
+use serde::Serialize;
+
+use crate::change::Change;
+
+/// A user-supplied [minijinja] template for formatting changes, like
+/// `{{ number }} {{ subject }} {{ owner.username }}`.
+///
+/// Templates are rendered with a change's fields as they're written by `show --json`.
+///
+/// [minijinja]: https://docs.rs/minijinja/latest/minijinja/syntax/index.html
+pub struct OutputTemplate {
+    env: Environment<'static>,
+}
+
+impl OutputTemplate {
+    const NAME: &'static str = "template";
+
+    pub fn new(template: String) -> miette::Result<Self> {
+        let mut env = Environment::new();
+        env.add_template_owned(Self::NAME, template)
+            .map_err(|error| {
+                miette!(
+                    help = "See: https://docs.rs/minijinja/latest/minijinja/syntax/index.html",
+                    "Invalid output template: {error:#}"
+                )
+            })?;
+        Ok(Self { env })
+    }
+
+    /// Render the template with a value as its context.
+    pub fn render(&self, value: impl Serialize) -> miette::Result<String> {
+        self.env
+            .get_template(Self::NAME)
+            .and_then(|template| template.render(value))
+            .map_err(|error| miette!("Failed to render output template: {error:#}"))
+    }
+
+    /// Render the template for each change, one per line.
+    pub fn render_changes<'a>(
+        &self,
+        changes: impl IntoIterator<Item = &'a Change>,
+    ) -> miette::Result<String> {
+        Ok(changes
+            .into_iter()
+            .map(|change| self.render(change))
+            .collect::<miette::Result<Vec<_>>>()?
+            .join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template =
+            OutputTemplate::new("{{ number }} {{ owner.username }} {{ tags | join(',') }}".into())
+                .unwrap();
+        assert_eq!(
+            template
+                .render(serde_json::json!({
+                    "number": 123,
+                    "owner": { "username": "rbt" },
+                    "tags": ["a", "b"],
+                }))
+                .unwrap(),
+            "123 rbt a,b"
+        );
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        assert!(OutputTemplate::new("{{ number".into()).is_err());
+    }
+}
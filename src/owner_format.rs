@@ -0,0 +1,25 @@
+use crate::author::Author;
+
+/// How to show the owners of changes.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OwnerFormat {
+    /// Gerrit usernames, like `rbt`.
+    #[default]
+    Username,
+    /// Display names, like `Rebecca Turner`.
+    Name,
+    /// Email addresses, like `rbt@sent.as`.
+    ///
+    /// Falls back to the username for accounts without a visible email.
+    Email,
+}
+
+impl OwnerFormat {
+    pub fn format(self, author: &Author) -> &str {
+        match self {
+            OwnerFormat::Username => &author.username,
+            OwnerFormat::Name => &author.name,
+            OwnerFormat::Email => author.email.as_deref().unwrap_or(&author.username),
+        }
+    }
+}
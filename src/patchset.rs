@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
 use clap::builder::RangedU64ValueParser;
 use clap::builder::TypedValueParser;
@@ -86,3 +87,88 @@ impl Display for ChangePatchset {
         write!(f, "{}/{}", self.change, self.patchset)
     }
 }
+
+/// A change, optionally at a specific patchset, like `1234` or `1234/5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangeSpec {
+    pub change: ChangeNumber,
+    /// The patchset, or `None` for the current patchset.
+    pub patchset: Option<Patchset>,
+}
+
+impl FromStr for ChangeSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (change, patchset) = match s.split_once('/') {
+            Some((change, patchset)) => (change, Some(patchset)),
+            None => (s, None),
+        };
+        let change = change
+            .parse()
+            .map_err(|_| format!("Invalid change number `{change}`"))?;
+        let patchset = patchset
+            .map(|patchset| {
+                patchset
+                    .parse()
+                    .map(Patchset)
+                    .map_err(|_| format!("Invalid patchset `{patchset}`"))
+            })
+            .transpose()?;
+        Ok(Self { change, patchset })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_change_spec_from_str() {
+        assert_eq!(
+            "1234".parse::<ChangeSpec>().unwrap(),
+            ChangeSpec {
+                change: "1234".parse().unwrap(),
+                patchset: None,
+            }
+        );
+        assert_eq!(
+            "1234/5".parse::<ChangeSpec>().unwrap(),
+            ChangeSpec {
+                change: "1234".parse().unwrap(),
+                patchset: Some(Patchset::new(5)),
+            }
+        );
+        assert!("1234/".parse::<ChangeSpec>().is_err());
+        assert!("abc".parse::<ChangeSpec>().is_err());
+    }
+
+    #[test]
+    fn test_git_ref() {
+        assert_eq!(
+            ChangePatchset {
+                change: "7".parse().unwrap(),
+                patchset: Patchset::new(1),
+            }
+            .git_ref(),
+            "refs/changes/07/7/1"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_git_ref(change in 1..10_000u64, patchset in 1..100u64) {
+            let git_ref = ChangePatchset {
+                change: change.to_string().parse().unwrap(),
+                patchset: Patchset::new(patchset),
+            }
+            .git_ref();
+            prop_assert_eq!(
+                git_ref,
+                format!("refs/changes/{:02}/{change}/{patchset}", change % 100)
+            );
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use dialoguer::Select;
+use miette::miette;
+use miette::IntoDiagnostic;
+
+use crate::change_number::ChangeNumber;
+use crate::gerrit::Gerrit;
+use crate::patchset::Patchset;
+
+/// Interactively choose one of a change's patchsets.
+///
+/// The latest patchset is selected by default.
+pub fn pick_patchset(gerrit: &Gerrit, change: ChangeNumber) -> miette::Result<Patchset> {
+    let patch_sets = gerrit.get_patch_sets(change)?;
+    if patch_sets.is_empty() {
+        return Err(miette!("Change {change} has no patchsets"));
+    }
+
+    let items = patch_sets
+        .iter()
+        .map(|patch_set| patch_set.summary())
+        .collect::<miette::Result<Vec<_>>>()?;
+    let index = Select::new()
+        .with_prompt(format!("Which patchset of {change}?"))
+        .items(&items)
+        .default(items.len() - 1)
+        .interact()
+        .into_diagnostic()?;
+
+    Ok(Patchset::new(patch_sets[index].number))
+}
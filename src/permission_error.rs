@@ -0,0 +1,146 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Guidance for an error caused by missing Gerrit permissions, if it looks like one.
+///
+/// `message` is the error output from Gerrit (over SSH, `git push`, or the REST API), and
+/// `status` is the HTTP status code, if any.
+pub fn permission_help(project: &str, message: &str, status: Option<u16>) -> Option<String> {
+    static PATTERNS: OnceLock<Vec<(Regex, Guidance)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            (
+                r"(?i)not permitted: (?:create change on|create|push to|update) (?P<ref>[^\s)]+)",
+                Guidance::Push,
+            ),
+            (
+                r"(?i)not permitted: add patch set to (?P<ref>[^\s)]+)",
+                Guidance::AddPatchSet,
+            ),
+            (
+                r#"(?i)applying label "(?P<label>[^"]+)": (?P<value>\S+) is restricted"#,
+                Guidance::Label,
+            ),
+            (
+                r"(?i)capability (?P<capability>\S+) is required",
+                Guidance::Capability,
+            ),
+            (r"(?i)permission denied \(publickey", Guidance::SshKey),
+            (r"(?i)\bread[- ]only\b", Guidance::ReadOnly),
+            (
+                r"(?i)not permitted: (?P<permission>[a-z][a-z ]*[a-z])",
+                Guidance::Permission,
+            ),
+        ]
+        .into_iter()
+        .map(|(pattern, guidance)| (Regex::new(pattern).expect("Regex parses"), guidance))
+        .collect()
+    });
+
+    let ask_owner = format!("ask an owner of the `{project}` project to grant it to you (check which groups you're in with `git gr groups --member self`)");
+
+    for (pattern, guidance) in patterns {
+        if let Some(captures) = pattern.captures(message) {
+            return Some(match guidance {
+                Guidance::Push => format!(
+                    "You don't have permission to push changes for review to `{}`; {ask_owner}. The permission is `Push` on `refs/for/refs/heads/*`.",
+                    &captures["ref"]
+                ),
+                Guidance::AddPatchSet => format!(
+                    "Only the change's owner, or users with the `Add Patch Set` permission on `{}`, can upload new patchsets. Ask the owner to push, or {ask_owner}.",
+                    &captures["ref"]
+                ),
+                Guidance::Label => format!(
+                    "You can't vote `{}` on `{}`. Run `git gr labels` to see who can, or {ask_owner}. The permission is `label-{}`.",
+                    &captures["value"],
+                    &captures["label"],
+                    &captures["label"]
+                ),
+                Guidance::Capability => format!(
+                    "This requires the `{}` global capability; ask a Gerrit administrator to grant it to you.",
+                    &captures["capability"]
+                ),
+                Guidance::SshKey => "Gerrit rejected your SSH key. Add your public key under SSH Keys in Gerrit's settings, then run `git gr doctor`.".to_owned(),
+                Guidance::ReadOnly => "Gerrit (or your account) is read-only, so changes can't be made right now. If your account should be able to write, ask a Gerrit administrator.".to_owned(),
+                Guidance::Permission => format!(
+                    "You don't have the `{}` permission; {ask_owner}.",
+                    &captures["permission"]
+                ),
+            });
+        }
+    }
+
+    match status {
+        Some(401) => Some("Gerrit rejected your HTTP credentials; run `git gr doctor` to check your REST API access.".to_owned()),
+        Some(403) => Some(format!("You don't have permission to do this; {ask_owner}.")),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Guidance {
+    Push,
+    AddPatchSet,
+    Label,
+    Capability,
+    SshKey,
+    ReadOnly,
+    Permission,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_help() {
+        let help = permission_help(
+            "gr",
+            " ! [remote rejected] HEAD -> refs/for/main (prohibited by Gerrit: not permitted: create change on refs/heads/main)",
+            None,
+        )
+        .unwrap();
+        assert!(
+            help.contains("push changes for review to `refs/heads/main`"),
+            "{help}"
+        );
+        assert!(help.contains("owner of the `gr` project"), "{help}");
+
+        let help = permission_help(
+            "gr",
+            " ! [remote rejected] HEAD -> refs/for/main (prohibited by Gerrit: not permitted: add patch set to refs/changes/23/123/3)",
+            None,
+        )
+        .unwrap();
+        assert!(help.contains("Add Patch Set"), "{help}");
+
+        let help = permission_help(
+            "gr",
+            r#"POST changes/123/revisions/current/review failed with status 403:
+Applying label "Code-Review": 2 is restricted"#,
+            Some(403),
+        )
+        .unwrap();
+        assert!(help.contains("vote `2` on `Code-Review`"), "{help}");
+
+        let help = permission_help("gr", "not permitted: abandon", Some(403)).unwrap();
+        assert!(help.contains("`abandon` permission"), "{help}");
+
+        let help = permission_help(
+            "gr",
+            "fatal: administrateServer for plugin gerrit not permitted; Capability administrateServer is required to access this command.",
+            None,
+        )
+        .unwrap();
+        assert!(
+            help.contains("`administrateServer` global capability"),
+            "{help}"
+        );
+
+        let help = permission_help("gr", "Unauthorized", Some(401)).unwrap();
+        assert!(help.contains("HTTP credentials"), "{help}");
+
+        assert_eq!(permission_help("gr", "Not found: 123", Some(404)), None);
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use crate::group_info::GroupInfo;
+
+/// Access rights for a project, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-access.html#project-access-info>
+#[derive(serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct ProjectAccessInfo {
+    /// The parent project this project inherits access rights from.
+    pub inherits_from: Option<ParentProject>,
+    /// Map from ref patterns to the access rights defined locally in this project.
+    #[serde(default)]
+    pub local: BTreeMap<String, AccessSectionInfo>,
+    /// Map from group UUIDs to the groups referenced in the access rights.
+    #[serde(default)]
+    pub groups: BTreeMap<String, GroupInfo>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct ParentProject {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct AccessSectionInfo {
+    /// Map from permission names (like `label-Code-Review`) to permissions.
+    #[serde(default)]
+    pub permissions: BTreeMap<String, PermissionInfo>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct PermissionInfo {
+    /// The label name, for label permissions.
+    pub label: Option<String>,
+    /// Map from group UUIDs to rules.
+    #[serde(default)]
+    pub rules: BTreeMap<String, PermissionRuleInfo>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct PermissionRuleInfo {
+    /// `ALLOW`, `DENY`, `BLOCK`, `INTERACTIVE`, or `BATCH`.
+    pub action: String,
+    /// The minimum value for label permissions.
+    pub min: Option<i32>,
+    /// The maximum value for label permissions.
+    pub max: Option<i32>,
+}
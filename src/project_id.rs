@@ -0,0 +1,39 @@
+use std::fmt::Display;
+
+use crate::endpoint::encode_path_segment;
+
+/// A project name, for use in REST API paths.
+///
+/// `Display` percent-encodes the name, so nested projects like `platform/build` become
+/// `platform%2Fbuild`.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-projects.html#project-name>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectId<'a>(&'a str);
+
+impl<'a> ProjectId<'a> {
+    pub fn new(project: &'a str) -> Self {
+        Self(project)
+    }
+}
+
+impl Display for ProjectId<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", encode_path_segment(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_project_id_display() {
+        assert_eq!(ProjectId::new("gr").to_string(), "gr");
+        assert_eq!(
+            ProjectId::new("platform/build/soong").to_string(),
+            "platform%2Fbuild%2Fsoong"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::io::IsTerminal;
+
+use calm_io::stdoutln;
+use dialoguer::Confirm;
+use miette::miette;
+use miette::IntoDiagnostic;
+
+use crate::change::Change;
+use crate::change_key::ChangeKey;
+use crate::change_number::ChangeNumber;
+use crate::change_status::ChangeStatus;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+use crate::gerrit::GerritGitRemote;
+
+/// Delete local branches and change refs for merged and abandoned changes.
+///
+/// Branches are matched to changes by the `Change-Id` of the commit they point to, and
+/// `refs/changes/*` refs by the change number in the ref name. Only change branches are
+/// considered: branches with no upstream, or whose tip isn't on any remote. The checked-out
+/// branch and the remote's default branch are never deleted.
+///
+/// The refs to delete are listed, and deleted after confirmation unless `yes` is set.
+pub fn prune(gerrit: &GerritGitRemote, dry_run: bool, yes: bool) -> miette::Result<()> {
+    let git = gerrit.git();
+    let current_branch = git
+        .current_branch()?
+        .map(|branch| format!("refs/heads/{branch}"));
+    let default_branch = git
+        .default_branch(&gerrit.remote)
+        .ok()
+        .map(|branch| format!("refs/heads/{branch}"));
+
+    let mut prunable = Vec::new();
+
+    for name in git.refs("refs/heads/")? {
+        if Some(&name) == current_branch.as_ref() || Some(&name) == default_branch.as_ref() {
+            continue;
+        }
+
+        match is_change_branch(gerrit, &name) {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::debug!(name, "Skipping branch which tracks a remote branch");
+                continue;
+            }
+            Err(error) => {
+                tracing::debug!(name, "Skipping branch: {error}");
+                continue;
+            }
+        }
+
+        match git
+            .change_id(&name)
+            .and_then(|change_id| closed_change(gerrit, change_id.into()))
+        {
+            Ok(Some(change)) => prunable.push((name, change)),
+            Ok(None) => {}
+            Err(error) => tracing::debug!(name, "Skipping branch: {error}"),
+        }
+    }
+
+    for name in git.refs("refs/changes/")? {
+        match change_number_from_ref(&name)
+            .ok_or_else(|| miette!("Failed to parse change number from `{name}`"))
+            .and_then(|number| closed_change(gerrit, number.into()))
+        {
+            Ok(Some(change)) => prunable.push((name, change)),
+            Ok(None) => {}
+            Err(error) => tracing::debug!(name, "Skipping ref: {error}"),
+        }
+    }
+
+    if prunable.is_empty() {
+        tracing::info!("Nothing to prune");
+        return Ok(());
+    }
+
+    let _ = stdoutln!(
+        "{}",
+        if dry_run {
+            "Would delete:"
+        } else {
+            "Refs to delete:"
+        }
+    );
+    for (name, change) in &prunable {
+        let short_name = name.strip_prefix("refs/heads/").unwrap_or(name);
+        let _ = stdoutln!(
+            "• {short_name} ({} is {})",
+            change.number.pretty(gerrit)?,
+            change.status,
+        );
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            return Err(miette!(
+                help = "Use `--yes` to delete them without asking",
+                "Refusing to delete refs without confirmation"
+            )
+            .classify(ErrorKind::Usage));
+        }
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Delete {} refs?", prunable.len()))
+            .default(false)
+            .interact()
+            .into_diagnostic()?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    for (name, _change) in &prunable {
+        git.delete_ref(name)?;
+    }
+    tracing::info!("Pruned {} refs", prunable.len());
+
+    Ok(())
+}
+
+/// Is this branch one of our change branches, rather than a local copy of a remote branch?
+///
+/// Change branches either have no upstream, or point to commits which aren't on any remote yet.
+fn is_change_branch(gerrit: &GerritGitRemote, name: &str) -> miette::Result<bool> {
+    let git = gerrit.git();
+    Ok(git.upstream(name)?.is_none() || !git.on_remote(name)?)
+}
+
+/// Get a change if it's merged or abandoned.
+///
+/// The status is fetched fresh from Gerrit, so a stale cache can't cause a branch to be
+/// deleted (or kept) by mistake.
+fn closed_change(gerrit: &GerritGitRemote, key: ChangeKey) -> miette::Result<Option<Change>> {
+    let change = gerrit.get_change_fresh(key)?;
+    Ok(Some(change).filter(|change| change.status != ChangeStatus::New))
+}
+
+/// Parse a change number from a ref like `refs/changes/45/12345/3`.
+fn change_number_from_ref(name: &str) -> Option<ChangeNumber> {
+    name.strip_prefix("refs/changes/")?
+        .split('/')
+        .nth(1)?
+        .parse()
+        .ok()
+}
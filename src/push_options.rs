@@ -0,0 +1,73 @@
+/// Options for a push to `refs/for/*`, like the topic and reviewers.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/user-upload.html#push_options>
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushOptions {
+    pub topic: Option<String>,
+    pub reviewers: Vec<String>,
+    pub hashtags: Vec<String>,
+    pub wip: bool,
+    /// The commit to compute the new change's parent relations from, for changes built on
+    /// another open change.
+    pub base: Option<String>,
+}
+
+impl PushOptions {
+    /// Format the options as a suffix for a `refs/for/{branch}` ref, like `%topic=foo,wip`.
+    ///
+    /// Returns an empty string if no options are set.
+    pub fn refspec_suffix(&self) -> String {
+        let mut options = Vec::new();
+        if let Some(topic) = &self.topic {
+            options.push(format!("topic={topic}"));
+        }
+        for reviewer in &self.reviewers {
+            options.push(format!("r={reviewer}"));
+        }
+        for hashtag in &self.hashtags {
+            options.push(format!("hashtag={hashtag}"));
+        }
+        if self.wip {
+            options.push("wip".to_owned());
+        }
+        if let Some(base) = &self.base {
+            options.push(format!("base={base}"));
+        }
+
+        if options.is_empty() {
+            String::new()
+        } else {
+            format!("%{}", options.join(","))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_refspec_suffix() {
+        assert_eq!(PushOptions::default().refspec_suffix(), "");
+        assert_eq!(
+            PushOptions {
+                topic: Some("hotfix-1234".to_owned()),
+                reviewers: vec!["jade".to_owned(), "rbt@sent.as".to_owned()],
+                hashtags: vec!["hotfix".to_owned()],
+                wip: true,
+                base: None,
+            }
+            .refspec_suffix(),
+            "%topic=hotfix-1234,r=jade,r=rbt@sent.as,hashtag=hotfix,wip"
+        );
+        assert_eq!(
+            PushOptions {
+                base: Some("0123456789abcdef0123456789abcdef01234567".to_owned()),
+                ..Default::default()
+            }
+            .refspec_suffix(),
+            "%base=0123456789abcdef0123456789abcdef01234567"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use miette::miette;
+
+use crate::git::Git;
+use crate::push_options::PushOptions;
+
+/// A named set of push settings for a recurring workflow, like hotfixes or backports.
+///
+/// Templates are read from Git config:
+///
+/// ```ini
+/// [git-gr "template.hotfix"]
+///     branch = release
+///     topic = hotfix-{branch}
+///     reviewer = jade
+///     hashtag = hotfix
+///     wip = false
+/// ```
+///
+/// `{branch}` in the topic is replaced with the name of the checked-out branch.
+#[derive(Debug, Clone, Default)]
+pub struct PushTemplate {
+    /// The branch to target.
+    pub branch: Option<String>,
+    pub options: PushOptions,
+}
+
+impl PushTemplate {
+    pub fn from_git_config(git: &Git, name: &str) -> miette::Result<Self> {
+        let key = |field: &str| format!("git-gr.template.{name}.{field}");
+
+        let branch = git.config_get(&key("branch"))?;
+        let topic = git.config_get(&key("topic"))?;
+        let reviewers = git.config_get_all(&key("reviewer"))?;
+        let hashtags = git.config_get_all(&key("hashtag"))?;
+        let wip = git.config_get(&key("wip"))?;
+
+        if branch.is_none()
+            && topic.is_none()
+            && reviewers.is_empty()
+            && hashtags.is_empty()
+            && wip.is_none()
+        {
+            return Err(miette!(
+                help = format!(
+                    "Configure the template with `git config {}`, `topic`, `reviewer`, `hashtag`, or `wip`",
+                    key("branch")
+                ),
+                "No push template named `{name}`"
+            ));
+        }
+
+        let topic = match topic {
+            Some(topic) if topic.contains("{branch}") => {
+                let current_branch = git.current_branch()?.ok_or_else(|| {
+                    miette!("Push template `{name}` uses `{{branch}}` in its topic, but no branch is checked out")
+                })?;
+                Some(topic.replace("{branch}", &current_branch))
+            }
+            topic => topic,
+        };
+
+        let wip = match wip.as_deref() {
+            None | Some("false" | "no" | "off" | "0") => false,
+            Some("true" | "yes" | "on" | "1") => true,
+            Some(other) => {
+                return Err(miette!("Invalid boolean for `{}`: {other}", key("wip")));
+            }
+        };
+
+        Ok(Self {
+            branch,
+            options: PushOptions {
+                topic,
+                reviewers,
+                hashtags,
+                wip,
+                base: None,
+            },
+        })
+    }
+}
@@ -0,0 +1,12 @@
+/// Whether `git gr restack push` uploads new patchsets to work-in-progress and private changes.
+#[derive(clap::ValueEnum, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PushWip {
+    /// List the work-in-progress and private changes and ask whether to push them.
+    #[default]
+    Prompt,
+    /// Push them like any other change.
+    Push,
+    /// Leave them (and the changes which depend on them) alone.
+    Skip,
+}
@@ -1,6 +1,6 @@
+use std::time::Duration;
+
 /// Options for performing a `gerrit query`.
-///
-/// Not modeled: `--deadline`.
 #[derive(Default, Debug, Clone)]
 pub struct QueryOptions {
     /// The query to execute.
@@ -15,11 +15,15 @@ pub struct QueryOptions {
     commit_message: bool,
     /// Include information about current patch set
     current_patch_set: bool,
+    /// Ask the server to give up on the query after this long
+    deadline: Option<Duration>,
 
     /// Include depends-on and needed-by information
     dependencies: bool,
     /// Include file list on patch sets
     files: bool,
+    /// Return at most this many results, with a `limit:` operator
+    limit: Option<usize>,
     /// Return all results, overriding the default limit
     no_limit: bool,
     /// Include information about all patch sets
@@ -40,8 +44,10 @@ impl QueryOptions {
             comments: false,
             commit_message: false,
             current_patch_set: false,
+            deadline: None,
             dependencies: false,
             files: false,
+            limit: None,
             no_limit: false,
             patch_sets: false,
             start: 0,
@@ -54,6 +60,34 @@ impl QueryOptions {
         &self.query
     }
 
+    /// The query string, with the limit added.
+    ///
+    /// The query is parenthesized so the limit applies to all of it, rather than binding to the
+    /// last term of an `OR`.
+    fn limited_query(&self) -> String {
+        match self.limit {
+            Some(limit) => format!("({}) limit:{limit}", self.query),
+            None => self.query.clone(),
+        }
+    }
+
+    /// A key for caching this query's results, which is different for each page of results.
+    pub fn cache_key(&self) -> String {
+        let query = self.limited_query();
+        if self.start > 0 {
+            format!("{query} --start {}", self.start)
+        } else {
+            query
+        }
+    }
+
+    /// The options for the page of results after this one, which had `count` changes.
+    pub fn next_page(&self, count: usize) -> Self {
+        let mut next = self.clone();
+        next.start += count;
+        next
+    }
+
     /// Convert this query into CLI options, to be appended to `gerrit`.
     pub fn into_args(self) -> Vec<String> {
         let mut args = vec!["query".to_owned(), "--format".to_owned(), "json".to_owned()];
@@ -73,6 +107,10 @@ impl QueryOptions {
         if self.current_patch_set {
             args.push("--current-patch-set".to_owned());
         }
+        if let Some(deadline) = self.deadline {
+            args.push("--deadline".to_owned());
+            args.push(format!("{}ms", deadline.as_millis()));
+        }
         if self.dependencies {
             args.push("--dependencies".to_owned());
         }
@@ -94,13 +132,12 @@ impl QueryOptions {
         }
 
         args.push("--".to_owned());
-        args.push(self.query.to_string());
+        args.push(self.limited_query());
 
         args
     }
 
     /// Include information about all patch sets and approvals.
-    #[allow(dead_code)]
     pub fn all_approvals(mut self) -> Self {
         self.all_approvals = true;
         self
@@ -134,6 +171,12 @@ impl QueryOptions {
         self
     }
 
+    /// Ask the server to give up on the query after `deadline`, unless a deadline is already set.
+    pub fn default_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline.get_or_insert(deadline);
+        self
+    }
+
     /// Include depends-on and needed-by information.
     #[allow(dead_code)]
     pub fn dependencies(mut self) -> Self {
@@ -148,6 +191,12 @@ impl QueryOptions {
         self
     }
 
+    /// Return at most `limit` results.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
     /// Return all results, overriding the default limit.
     #[allow(dead_code)]
     pub fn no_limit(mut self) -> Self {
@@ -163,7 +212,6 @@ impl QueryOptions {
     }
 
     /// Number of changes to skip.
-    #[allow(dead_code)]
     pub fn start(mut self, start: usize) -> Self {
         self.start = start;
         self
@@ -176,3 +224,125 @@ impl QueryOptions {
         self
     }
 }
+
+use crate::age::StalledAfter;
+use crate::owner_format::OwnerFormat;
+
+/// Options for formatting query results as a table.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct QueryTableOptions {
+    /// Show full subjects, rather than truncating them to the terminal width.
+    pub full: bool,
+    /// Show a column with the number of inserted and deleted lines.
+    pub size: bool,
+    /// How to show the owners of changes.
+    pub owner_format: OwnerFormat,
+    /// Show a column with the kind of each change's current patchset, like `trivial rebase`.
+    pub kind: bool,
+    /// Show a column with the votes on each change.
+    pub votes: bool,
+    /// Show a column with our own votes on each change.
+    pub my_vote: bool,
+    /// Show a column with the age of each change.
+    pub age: bool,
+    /// Highlight changes which have waited for review without updates for this long.
+    pub stalled_after: StalledAfter,
+    /// Sort the changes by how actionable they are for a reviewer, rather than in the server's
+    /// order.
+    pub review_priority: bool,
+    /// Show at most this many changes, rather than every change the server will return.
+    pub limit: Option<usize>,
+    /// Skip this many changes, to show later pages of results.
+    pub start: usize,
+    /// Fetch every page of results, rather than only the first.
+    pub all: bool,
+}
+
+/// Restrict a query to a project, unless it already mentions a project.
+pub fn in_project(query: &str, project: &str) -> String {
+    const PROJECT_OPERATORS: [&str; 5] = ["project:", "p:", "projects:", "parentproject:", "repo:"];
+
+    let mentions_project = query.split_whitespace().any(|term| {
+        let term = term.trim_start_matches(['-', '(']);
+        let term = term.strip_prefix("NOT").unwrap_or(term);
+        PROJECT_OPERATORS
+            .iter()
+            .any(|operator| term.starts_with(operator))
+    });
+
+    if mentions_project {
+        query.to_owned()
+    } else if query.trim().is_empty() {
+        format!("project:{project}")
+    } else {
+        format!("({query}) project:{project}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_in_project() {
+        assert_eq!(
+            in_project("is:open owner:self", "gr"),
+            "(is:open owner:self) project:gr"
+        );
+        assert_eq!(in_project("", "gr"), "project:gr");
+        assert_eq!(
+            in_project("is:open project:other", "gr"),
+            "is:open project:other"
+        );
+        assert_eq!(in_project("-p:other", "gr"), "-p:other");
+        assert_eq!(
+            in_project("message:project", "gr"),
+            "(message:project) project:gr"
+        );
+    }
+
+    #[test]
+    fn test_query_pagination() {
+        let query = QueryOptions::new("is:open".to_owned()).limit(25).start(50);
+        assert_eq!(query.cache_key(), "(is:open) limit:25 --start 50");
+        assert_eq!(
+            query.next_page(25).into_args(),
+            [
+                "query",
+                "--format",
+                "json",
+                "--start",
+                "75",
+                "--",
+                "(is:open) limit:25"
+            ]
+        );
+
+        let query = QueryOptions::new("owner:self OR reviewer:self".to_owned()).limit(25);
+        assert_eq!(query.cache_key(), "(owner:self OR reviewer:self) limit:25");
+
+        let query = QueryOptions::new("is:open".to_owned());
+        assert_eq!(query.cache_key(), "is:open");
+    }
+
+    #[test]
+    fn test_query_deadline() {
+        let query = QueryOptions::new("is:open".to_owned())
+            .default_deadline(Duration::from_secs(10))
+            .default_deadline(Duration::from_secs(30));
+        assert_eq!(query.cache_key(), "is:open");
+        assert_eq!(
+            query.into_args(),
+            [
+                "query",
+                "--format",
+                "json",
+                "--deadline",
+                "10000ms",
+                "--",
+                "is:open"
+            ]
+        );
+    }
+}
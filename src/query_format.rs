@@ -0,0 +1,13 @@
+/// How to print query results.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QueryFormat {
+    /// A table for the terminal.
+    #[default]
+    Table,
+    /// An Atom feed, for publishing lists of changes.
+    Atom,
+    /// Comma-separated values, for spreadsheets.
+    Csv,
+    /// Tab-separated values, for shell pipelines.
+    Tsv,
+}
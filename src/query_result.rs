@@ -42,8 +42,9 @@ where
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 pub struct QueryStatistics {
-    row_count: usize,
-    more_changes: bool,
+    /// The number of changes returned.
+    pub row_count: usize,
+    /// Are there more changes after these, which weren't returned because of a limit?
+    pub more_changes: bool,
 }
@@ -0,0 +1,77 @@
+use bstr::BStr;
+use bstr::ByteSlice;
+
+/// Format a path for display like Git does.
+///
+/// Paths containing control characters, double quotes, or backslashes are quoted with C-style
+/// escapes. If `quote_non_ascii` is set (like Git's default `core.quotePath = true`), bytes
+/// outside of ASCII are also quoted, as octal escapes. Otherwise, they're shown as UTF-8, with
+/// invalid sequences replaced.
+pub fn quote_path(path: &BStr, quote_non_ascii: bool) -> String {
+    let needs_quoting = |byte: u8| {
+        byte < 0x20
+            || byte == b'"'
+            || byte == b'\\'
+            || byte == 0x7f
+            || (quote_non_ascii && byte >= 0x80)
+    };
+    if !path.iter().copied().any(needs_quoting) {
+        return path.to_str_lossy().into_owned();
+    }
+
+    let mut quoted = String::from("\"");
+    let mut rest = path.as_bytes();
+    while !rest.is_empty() {
+        // Copy runs of bytes which don't need quoting as (lossy) UTF-8.
+        let end = rest
+            .iter()
+            .position(|&byte| needs_quoting(byte))
+            .unwrap_or(rest.len());
+        quoted.push_str(&rest[..end].to_str_lossy());
+        rest = &rest[end..];
+
+        if let Some((&byte, tail)) = rest.split_first() {
+            match byte {
+                0x07 => quoted.push_str("\\a"),
+                0x08 => quoted.push_str("\\b"),
+                b'\t' => quoted.push_str("\\t"),
+                b'\n' => quoted.push_str("\\n"),
+                0x0b => quoted.push_str("\\v"),
+                0x0c => quoted.push_str("\\f"),
+                b'\r' => quoted.push_str("\\r"),
+                b'"' => quoted.push_str("\\\""),
+                b'\\' => quoted.push_str("\\\\"),
+                byte => quoted.push_str(&format!("\\{byte:03o}")),
+            }
+            rest = tail;
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_quote_path() {
+        assert_eq!(quote_path(b"src/main.rs".as_bstr(), true), "src/main.rs");
+        assert_eq!(
+            quote_path("src/caf\u{e9}.rs".as_bytes().as_bstr(), true),
+            r#""src/caf\303\251.rs""#
+        );
+        assert_eq!(
+            quote_path("src/caf\u{e9}.rs".as_bytes().as_bstr(), false),
+            "src/caf\u{e9}.rs"
+        );
+        assert_eq!(
+            quote_path(b"a\tb\"c\\d".as_bstr(), false),
+            r#""a\tb\"c\\d""#
+        );
+        // Not UTF-8.
+        assert_eq!(quote_path(b"bad\xff".as_bstr(), true), r#""bad\377""#);
+        assert_eq!(quote_path(b"bad\xff".as_bstr(), false), "bad\u{fffd}");
+    }
+}
@@ -0,0 +1,128 @@
+use std::time::Duration;
+use std::time::Instant;
+
+/// Retry rate-limited requests this many times before giving up.
+pub const MAX_RETRIES: u32 = 4;
+
+/// A token-bucket rate limiter for requests to Gerrit.
+///
+/// Shared between SSH commands and REST API requests.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    /// Requests per second, or `None` for no limit.
+    rate: Option<f64>,
+    /// The maximum number of requests that can be made in a burst.
+    burst: f64,
+    /// The number of requests that can currently be made without waiting.
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate: Option<f64>, burst: f64) -> Self {
+        Self {
+            rate,
+            burst,
+            tokens: burst,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Get the rate limit from `GIT_GR_RATE_LIMIT`, in requests per second.
+    ///
+    /// Defaults to 10 requests per second, with bursts of up to 20 requests. Set to `0` to disable
+    /// rate limiting.
+    pub fn from_env() -> Self {
+        const DEFAULT_RATE: f64 = 10.0;
+
+        let rate = match std::env::var("GIT_GR_RATE_LIMIT") {
+            Ok(rate) if !rate.is_empty() => match rate.parse::<f64>() {
+                Ok(rate) if rate > 0.0 => Some(rate),
+                Ok(_) => None,
+                Err(error) => {
+                    tracing::warn!("Failed to parse `GIT_GR_RATE_LIMIT={rate}`: {error}");
+                    Some(DEFAULT_RATE)
+                }
+            },
+            _ => Some(DEFAULT_RATE),
+        };
+
+        Self::new(rate, rate.map(|rate| (rate * 2.0).max(1.0)).unwrap_or(1.0))
+    }
+
+    /// Take a token, returning how long to wait before making the request.
+    fn acquire_at(&mut self, now: Instant) -> Duration {
+        let rate = match self.rate {
+            Some(rate) => rate,
+            None => return Duration::ZERO,
+        };
+
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * rate).min(self.burst);
+        self.last_refill = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+
+    /// Wait until a request can be made.
+    pub fn acquire(&mut self) {
+        let wait = self.acquire_at(Instant::now());
+        if !wait.is_zero() {
+            tracing::debug!(?wait, "Rate limited; waiting");
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// How long to wait before retrying a request the server rejected for being rate limited.
+///
+/// Uses the server's `Retry-After` if given, and exponential backoff otherwise.
+pub fn backoff_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after.unwrap_or_else(|| Duration::from_secs(1 << attempt.min(5)))
+}
+
+/// Does this error message indicate the server is rate limiting us?
+pub fn is_rate_limit_message(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    message.contains("too many requests") || message.contains("too many concurrent connections")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_rate_limiter() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter {
+            rate: Some(2.0),
+            burst: 2.0,
+            tokens: 2.0,
+            last_refill: start,
+        };
+
+        assert_eq!(limiter.acquire_at(start), Duration::ZERO);
+        assert_eq!(limiter.acquire_at(start), Duration::ZERO);
+        assert_eq!(limiter.acquire_at(start), Duration::from_millis(500));
+        // The waiting request used the token that refilled.
+        assert_eq!(
+            limiter.acquire_at(start + Duration::from_millis(500)),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            limiter.acquire_at(start + Duration::from_secs(10)),
+            Duration::ZERO
+        );
+
+        let mut unlimited = RateLimiter::new(None, 1.0);
+        for _ in 0..100 {
+            assert_eq!(unlimited.acquire_at(start), Duration::ZERO);
+        }
+    }
+}
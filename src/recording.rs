@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+use calm_io::stderr;
+use calm_io::stdout;
+use camino::Utf8PathBuf;
+use fs_err as fs;
+use miette::miette;
+use miette::Context;
+use miette::IntoDiagnostic;
+use regex::Regex;
+use reqwest::Method;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::endpoint::Endpoint;
+
+/// A recorded SSH command or HTTP request and its response.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct Interaction {
+    /// The `gerrit` command or HTTP method and endpoint.
+    pub request: Vec<String>,
+    /// The exit code or HTTP status.
+    pub status: i32,
+    /// Standard output or the response body.
+    pub stdout: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+}
+
+/// Whether SSH and HTTP interactions are being recorded or replayed.
+///
+/// Set with `GIT_GR_RECORD=dir` or `GIT_GR_REPLAY=dir`. Recordings are stored as one JSON file
+/// per distinct request, holding each response in order; when a request is made several times,
+/// the responses are replayed in the same order. Generated HTTP passwords and email addresses
+/// are redacted, but account names and everything else are recorded as-is.
+#[derive(Debug, Clone)]
+pub enum Recording {
+    Record(Utf8PathBuf),
+    Replay(Utf8PathBuf),
+}
+
+impl Recording {
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(Utf8PathBuf::from)
+        };
+        var("GIT_GR_REPLAY")
+            .map(Self::Replay)
+            .or_else(|| var("GIT_GR_RECORD").map(Self::Record))
+    }
+
+    /// Prepare to record or replay, at the start of a top-level `git-gr` invocation.
+    ///
+    /// Replays start over from the first response to each request, but subprocesses (like the
+    /// `restack write-todo` sequence editor) continue where their parent is.
+    pub fn init() -> miette::Result<()> {
+        if std::env::var("GIT_GR_RECORDING_SESSION").is_ok() {
+            return Ok(());
+        }
+        std::env::set_var("GIT_GR_RECORDING_SESSION", std::process::id().to_string());
+
+        match Self::from_env() {
+            Some(Self::Record(dir)) => fs::create_dir_all(dir).into_diagnostic(),
+            Some(Self::Replay(dir)) => {
+                let state = dir.join(REPLAY_STATE);
+                if state.exists() {
+                    fs::remove_file(state).into_diagnostic()?;
+                }
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The name passed to `git-gr` to wrap SSH commands.
+    pub fn ssh_subcommand(&self) -> [&str; 2] {
+        match self {
+            Recording::Record(dir) => ["record-ssh", dir.as_str()],
+            Recording::Replay(dir) => ["replay-ssh", dir.as_str()],
+        }
+    }
+
+    fn dir(&self) -> &Utf8PathBuf {
+        match self {
+            Recording::Record(dir) | Recording::Replay(dir) => dir,
+        }
+    }
+
+    fn path(&self, request: &[String]) -> miette::Result<Utf8PathBuf> {
+        let hash = short_hash(&serde_json::to_vec(request).into_diagnostic()?);
+        let kind = request
+            .first()
+            .map(|kind| kind.as_str())
+            .unwrap_or("request");
+        Ok(self.dir().join(format!("{kind}-{hash}.json")))
+    }
+
+    /// Record an interaction, if we're recording.
+    pub fn record(&self, mut interaction: Interaction) -> miette::Result<()> {
+        if let Recording::Record(_) = self {
+            if interaction
+                .request
+                .iter()
+                .any(|arg| arg == "--generate-http-password")
+            {
+                interaction.stdout = "New password: REDACTED\n".to_owned();
+            }
+            interaction.stdout = redact_emails(&interaction.stdout);
+            interaction.stderr = redact_emails(&interaction.stderr);
+
+            let path = self.path(&interaction.request)?;
+            let mut interactions = if path.exists() {
+                serde_json::from_str::<Vec<Interaction>>(
+                    &fs::read_to_string(&path).into_diagnostic()?,
+                )
+                .into_diagnostic()?
+            } else {
+                Vec::new()
+            };
+            interactions.push(interaction);
+            fs::write(
+                &path,
+                serde_json::to_string_pretty(&interactions).into_diagnostic()?,
+            )
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to write recording to `{path}`"))?;
+        }
+        Ok(())
+    }
+
+    /// Get the next recorded response to a request, if we're replaying.
+    pub fn replay(&self, request: &[String]) -> miette::Result<Option<Interaction>> {
+        let dir = match self {
+            Recording::Replay(dir) => dir,
+            Recording::Record(_) => return Ok(None),
+        };
+
+        let path = self.path(request)?;
+        if !path.exists() {
+            return Err(miette!(
+                "No recorded response for `{}` in `{dir}`",
+                request.join(" ")
+            ));
+        }
+        let interactions =
+            serde_json::from_str::<Vec<Interaction>>(&fs::read_to_string(&path).into_diagnostic()?)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("Failed to read recording from `{path}`"))?;
+
+        // Track how many times each request has been replayed.
+        let state_path = dir.join(REPLAY_STATE);
+        let mut state = if state_path.exists() {
+            serde_json::from_str::<BTreeMap<String, usize>>(
+                &fs::read_to_string(&state_path).into_diagnostic()?,
+            )
+            .into_diagnostic()?
+        } else {
+            BTreeMap::new()
+        };
+        let count = state.entry(path.to_string()).or_default();
+        // Once we run out of responses, keep replaying the last one.
+        let interaction = interactions
+            .get(*count)
+            .or_else(|| interactions.last())
+            .cloned();
+        *count += 1;
+        fs::write(
+            &state_path,
+            serde_json::to_string(&state).into_diagnostic()?,
+        )
+        .into_diagnostic()?;
+
+        Ok(interaction)
+    }
+}
+
+const REPLAY_STATE: &str = ".replay-state.json";
+
+/// Replace email addresses with placeholders.
+///
+/// Each address gets its own placeholder, so recordings can still tell accounts apart.
+fn redact_emails(text: &str) -> String {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let email_re = RE.get_or_init(|| {
+        Regex::new(r"[[:alnum:]._%+-]+@[[:alnum:].-]+\.[[:alpha:]]{2,}").expect("Regex parses")
+    });
+    email_re
+        .replace_all(text, |captures: &regex::Captures| {
+            format!("user-{}@example.com", short_hash(captures[0].as_bytes()))
+        })
+        .into_owned()
+}
+
+/// The first 8 bytes of the SHA-256 hash of `bytes`, in hex.
+fn short_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The key to record an HTTP request by: its method, endpoint, and a hash of its body.
+///
+/// Requests to the same endpoint with different bodies (like reviews on the same change) are
+/// recorded separately.
+pub fn http_request(method: &Method, endpoint: &Endpoint, body: Option<&[u8]>) -> Vec<String> {
+    let mut request = vec!["http".to_owned(), method.to_string(), endpoint.to_string()];
+    if let Some(body) = body {
+        request.push(format!("body-sha256:{}", short_hash(body)));
+    }
+    request
+}
+
+/// The part of an SSH command line to key recordings by: the `gerrit` command and its arguments.
+///
+/// This leaves out the SSH options and destination, which depend on the environment.
+fn ssh_request(command: &[String]) -> Vec<String> {
+    let start = command
+        .iter()
+        .position(|arg| arg == "gerrit")
+        .unwrap_or_default();
+    std::iter::once("ssh".to_owned())
+        .chain(command[start..].iter().cloned())
+        .collect()
+}
+
+/// Run an SSH command, recording its output; used in place of `ssh`.
+pub fn record_ssh(recording: Recording, command: &[String]) -> miette::Result<()> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| miette!("No SSH command given"))?;
+    // `gerrit` commands like `review` can read from stdin.
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .into_diagnostic()
+        .wrap_err_with(|| format!("Failed to run `{program}`"))?;
+    let interaction = Interaction {
+        request: ssh_request(command),
+        status: output.status.code().unwrap_or(255),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    };
+    // Print the real output, not the redacted version.
+    let _ = stdout!("{}", interaction.stdout);
+    let _ = stderr!("{}", interaction.stderr);
+    let status = interaction.status;
+    recording.record(interaction)?;
+    std::process::exit(status)
+}
+
+/// Print a recorded SSH command's output; used in place of `ssh`.
+pub fn replay_ssh(recording: Recording, command: &[String]) -> miette::Result<()> {
+    match recording.replay(&ssh_request(command))? {
+        Some(interaction) => {
+            let _ = stdout!("{}", interaction.stdout);
+            let _ = stderr!("{}", interaction.stderr);
+            std::process::exit(interaction.status)
+        }
+        None => Err(miette!("Not replaying a recording")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_redact_emails() {
+        let redacted = redact_emails(
+            r#"{"name":"Rebecca Turner","email":"rbt@example.org"} rbt@example.org a@b.co"#,
+        );
+        assert!(!redacted.contains("rbt@example.org"), "{redacted}");
+        let placeholders = redacted
+            .split(['"', ' '])
+            .filter(|word| word.ends_with("@example.com"))
+            .collect::<Vec<_>>();
+        assert_eq!(placeholders.len(), 3);
+        assert_eq!(placeholders[0], placeholders[1]);
+        assert_ne!(placeholders[0], placeholders[2]);
+    }
+}
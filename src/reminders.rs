@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::BufReader;
+use std::io::BufWriter;
+
+use camino::Utf8PathBuf;
+use fs_err::File;
+use miette::miette;
+use miette::Context;
+use miette::IntoDiagnostic;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+use serde_with::serde_as;
+use serde_with::TimestampSeconds;
+use time::macros::format_description;
+use time::macros::time;
+use time::Date;
+use time::OffsetDateTime;
+use time::Weekday;
+
+use crate::age::parse_duration;
+use crate::change_number::ChangeNumber;
+use crate::gerrit::Gerrit;
+use crate::git::Git;
+use crate::timezone::local_time_zone;
+
+/// A local "review by" deadline for a change.
+#[serde_as]
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    /// The end of the day the review is due.
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    pub by: OffsetDateTime,
+}
+
+impl Reminder {
+    pub fn is_overdue(&self, now: OffsetDateTime) -> bool {
+        now > self.by
+    }
+}
+
+/// Review deadlines set with `git gr remind`.
+///
+/// These are never sent to Gerrit. Stored in the `.git` directory.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
+pub struct Reminders {
+    pub changes: BTreeMap<ChangeNumber, Reminder>,
+}
+
+impl Reminders {
+    pub fn read(git: &Git) -> miette::Result<Self> {
+        let path = reminders_path(git)?;
+        if path.exists() {
+            serde_json::from_reader(BufReader::new(File::open(&path).into_diagnostic()?))
+                .into_diagnostic()
+                .wrap_err_with(|| format!("Failed to read reminders from `{path}`"))
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn write(&self, git: &Git) -> miette::Result<()> {
+        let path = reminders_path(git)?;
+        let writer = BufWriter::new(File::create(path).into_diagnostic()?);
+        serde_json::to_writer(writer, self).into_diagnostic()?;
+        Ok(())
+    }
+
+    /// Set the deadline for a change, like `friday`, `tomorrow`, `3d`, or `2024-05-01`.
+    pub fn set(git: &Git, change: ChangeNumber, by: &str) -> miette::Result<Reminder> {
+        let time_zone = local_time_zone();
+        let today = time_zone.now().date();
+        let date = parse_deadline(by, today).map_err(|error| miette!("{error}"))?;
+        let end_of_day = date.with_time(time!(23:59:59)).assume_utc();
+        let reminder = Reminder {
+            by: end_of_day.replace_offset(time_zone.offset_at(end_of_day)),
+        };
+
+        let mut reminders = Self::read(git)?;
+        reminders.changes.insert(change, reminder.clone());
+        reminders.write(git)?;
+        Ok(reminder)
+    }
+
+    /// Remove the deadline for a change. Returns `false` if there was no deadline.
+    pub fn clear(git: &Git, change: ChangeNumber) -> miette::Result<bool> {
+        let mut reminders = Self::read(git)?;
+        let removed = reminders.changes.remove(&change).is_some();
+        if removed {
+            reminders.write(git)?;
+        }
+        Ok(removed)
+    }
+
+    /// Format the reminders, soonest first, with overdue changes highlighted.
+    pub fn format(&self, gerrit: &Gerrit) -> miette::Result<String> {
+        let now = OffsetDateTime::now_utc();
+        let mut reminders = self.changes.iter().collect::<Vec<_>>();
+        reminders.sort_by_key(|(_change, reminder)| reminder.by);
+
+        let mut output = String::new();
+        for (change, reminder) in reminders {
+            let by = local_time_zone()
+                .to_local(reminder.by)
+                .format(format_description!(
+                    "[weekday repr:short] [year]-[month]-[day]"
+                ))
+                .into_diagnostic()?;
+            let overdue = if reminder.is_overdue(now) {
+                format!(
+                    " {}",
+                    "overdue".if_supports_color(Stdout, |overdue| overdue.red())
+                )
+            } else {
+                String::new()
+            };
+            let _ = writeln!(output, "{} due {by}{overdue}", change.pretty(gerrit)?);
+        }
+        Ok(output)
+    }
+}
+
+/// Parse a deadline like `friday`, `tomorrow`, `3d`, or `2024-05-01`, relative to `today`.
+///
+/// Weekdays mean the next such day, including today.
+pub fn parse_deadline(deadline: &str, today: Date) -> Result<Date, String> {
+    let deadline = deadline.trim().to_lowercase();
+    match deadline.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + time::Duration::days(1)),
+        _ => {}
+    }
+
+    if let Some(weekday) = parse_weekday(&deadline) {
+        let mut date = today;
+        while date.weekday() != weekday {
+            date += time::Duration::days(1);
+        }
+        return Ok(date);
+    }
+
+    if let Ok(date) = Date::parse(&deadline, format_description!("[year]-[month]-[day]")) {
+        return Ok(date);
+    }
+
+    parse_duration(&deadline)
+        .map(|duration| today + duration)
+        .map_err(|_| {
+            format!(
+                "Expected a deadline like `friday`, `tomorrow`, `3d`, or `2024-05-01`: {deadline}"
+            )
+        })
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ]
+    .into_iter()
+    .find(|weekday| {
+        let name = weekday.to_string().to_lowercase();
+        s == name || s == &name[..3]
+    })
+}
+
+pub fn reminders_path(git: &Git) -> miette::Result<Utf8PathBuf> {
+    git.get_git_dir()
+        .map(|git_dir| git_dir.join("git-gr-reminders.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use time::macros::date;
+
+    #[test]
+    fn test_parse_deadline() {
+        // A Wednesday.
+        let today = date!(2024 - 05 - 01);
+        assert_eq!(parse_deadline("today", today), Ok(today));
+        assert_eq!(parse_deadline("tomorrow", today), Ok(date!(2024 - 05 - 02)));
+        assert_eq!(parse_deadline("friday", today), Ok(date!(2024 - 05 - 03)));
+        assert_eq!(parse_deadline("Fri", today), Ok(date!(2024 - 05 - 03)));
+        assert_eq!(parse_deadline("wednesday", today), Ok(today));
+        assert_eq!(parse_deadline("tue", today), Ok(date!(2024 - 05 - 07)));
+        assert_eq!(parse_deadline("3d", today), Ok(date!(2024 - 05 - 04)));
+        assert_eq!(parse_deadline("2w", today), Ok(date!(2024 - 05 - 15)));
+        assert_eq!(
+            parse_deadline("2024-06-30", today),
+            Ok(date!(2024 - 06 - 30))
+        );
+        assert!(parse_deadline("someday", today).is_err());
+    }
+}
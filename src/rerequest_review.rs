@@ -0,0 +1,100 @@
+use crate::attention_set_input::AttentionSetInput;
+use crate::change_number::ChangeNumber;
+use crate::config::Config;
+use crate::gerrit::GerritGitRemote;
+use crate::offline_queue::perform_or_queue;
+use crate::offline_queue::Action;
+use crate::output_template::OutputTemplate;
+use crate::review_input::ReviewInput;
+
+/// The message posted on restacked changes, if the `restack.review-message` setting isn't set.
+const DEFAULT_MESSAGE: &str =
+    "Rebased onto the latest {{ branch }}{% if trivial %}, with no content changes{% endif %}.";
+
+/// The context for rendering the `restack.review-message` template.
+#[derive(serde::Serialize, Debug)]
+struct MessageContext<'a> {
+    branch: &'a str,
+    old_patchset: u64,
+    new_patchset: u64,
+    /// The new patchset's kind, like `TRIVIAL_REBASE`.
+    kind: &'static str,
+    /// Is the new patchset a trivial rebase, with no content changes?
+    trivial: bool,
+}
+
+/// After uploading a new patchset for a restacked change, post a message about it and add the
+/// change's reviewers back to the attention set.
+///
+/// CCs, the change's owner, and we ourselves aren't added.
+pub fn rerequest_review(
+    gerrit: &mut GerritGitRemote,
+    change: ChangeNumber,
+    old_patchset: u64,
+) -> miette::Result<()> {
+    let change = gerrit.get_change(change)?;
+    let template = OutputTemplate::new(
+        Config::global()
+            .restack
+            .review_message
+            .clone()
+            .unwrap_or_else(|| DEFAULT_MESSAGE.to_owned()),
+    )?;
+    let kind = change.current_patch_set.kind;
+    let message = template.render(MessageContext {
+        branch: &change.branch,
+        old_patchset,
+        new_patchset: change.current_patch_set.number,
+        kind: kind.as_str(),
+        trivial: !kind.is_rework(),
+    })?;
+
+    let owner = change.owner.username.clone();
+    let me = gerrit.username().to_owned();
+    let add_to_attention_set = gerrit
+        .reviewers(change.number)?
+        .into_iter()
+        // CCs can't vote, so they don't have any approvals.
+        .filter(|reviewer| !reviewer.approvals.is_empty())
+        .filter(|reviewer| !reviewer.account.matches(&owner) && !reviewer.account.matches(&me))
+        .map(|reviewer| AttentionSetInput {
+            user: reviewer.account.to_string(),
+            reason: "Restacked with git-gr".to_owned(),
+        })
+        .collect();
+
+    let action = Action::Review(ReviewInput {
+        message: Some(message),
+        add_to_attention_set,
+        ..Default::default()
+    });
+    tracing::info!("{}: {action}", change.number.pretty(gerrit)?);
+    perform_or_queue(gerrit, change.number, action)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_default_message() {
+        let template = OutputTemplate::new(DEFAULT_MESSAGE.to_owned()).unwrap();
+        let context = |trivial| MessageContext {
+            branch: "main",
+            old_patchset: 2,
+            new_patchset: 3,
+            kind: "TRIVIAL_REBASE",
+            trivial,
+        };
+        assert_eq!(
+            template.render(context(true)).unwrap(),
+            "Rebased onto the latest main, with no content changes."
+        );
+        assert_eq!(
+            template.render(context(false)).unwrap(),
+            "Rebased onto the latest main."
+        );
+    }
+}
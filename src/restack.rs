@@ -4,10 +4,13 @@ use std::collections::VecDeque;
 use std::fmt::Display;
 use std::io::BufReader;
 use std::io::BufWriter;
+use std::io::IsTerminal;
 use std::ops::Deref;
 
+use calm_io::stdoutln;
 use camino::Utf8PathBuf;
 use command_error::CommandExt;
+use dialoguer::Confirm;
 use fs_err as fs;
 use fs_err::File;
 use miette::miette;
@@ -18,9 +21,16 @@ use crate::change_number::ChangeNumber;
 use crate::change_status::ChangeStatus;
 use crate::cli::RestackContinue;
 use crate::commit_hash::CommitHash;
+use crate::config::Config;
 use crate::dependency_graph::DependencyGraph;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+use crate::format_bulleted_list;
 use crate::gerrit::GerritGitRemote;
 use crate::git::Git;
+use crate::git_operation::GitOperation;
+use crate::restack_hashtag::RestackHashtag;
+use crate::restack_push::restack_push;
 use crate::restack_push::PushTodo;
 
 const CONTINUE_MESSAGE: &str = "Fix conflicts and then use `git-gr restack continue` to keep going. Alternatively, use `git-gr restack abort` to quit the restack.";
@@ -36,6 +46,12 @@ pub struct RestackTodo {
     pub refs: BTreeMap<ChangeNumber, RefUpdate>,
     /// Restack step in progress, if any.
     in_progress: Option<InProgress>,
+    /// Changes skipped by `git-gr restack continue --all-remaining`.
+    #[serde(default)]
+    needs_attention: BTreeMap<ChangeNumber, SkipReason>,
+    /// The hashtag added to the changes being restacked, if any.
+    #[serde(default)]
+    hashtag: Option<RestackHashtag>,
 }
 
 impl RestackTodo {
@@ -131,6 +147,24 @@ impl Display for Step {
     }
 }
 
+/// Why a restack step was skipped.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+enum SkipReason {
+    /// Restacking the change caused conflicts.
+    Conflicts,
+    /// The change's parent was skipped.
+    Parent(ChangeNumber),
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Conflicts => write!(f, "conflicts"),
+            SkipReason::Parent(parent) => write!(f, "depends on skipped change {parent}"),
+        }
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 enum RestackOnto {
     Branch { remote: String, branch: String },
@@ -168,10 +202,18 @@ pub fn restack(
     gerrit: &mut GerritGitRemote,
     branch: &str,
     options: Option<RestackContinue>,
+    include_others: bool,
 ) -> miette::Result<()> {
     let git = gerrit.git();
     let mut fetched = false;
-    let mut todo = get_or_create_todo(gerrit, branch)?;
+    let mut todo = get_or_create_todo(gerrit, branch, include_others)?;
+    let all_remaining = options
+        .as_ref()
+        .map(|options| options.all_remaining)
+        .unwrap_or(false);
+
+    // A rebase for the in-progress step is our own, and we'll continue it below.
+    check_foreign_operation(&git, todo.in_progress.is_some())?;
 
     if let Some(step) = todo.in_progress.take() {
         if options
@@ -204,6 +246,8 @@ pub fn restack(
                 .map(|_| ())
                 .into_diagnostic()
                 .wrap_err(CONTINUE_MESSAGE)
+                .classify(ErrorKind::Conflict)
+                .for_change(step.change)
             {
                 Ok(()) => {
                     todo.refs.insert(
@@ -243,7 +287,9 @@ pub fn restack(
                 return Err(miette!(
                     "Cannot find commit for change {}; use `git gr restack continue --in-progress-commit` or `--restart-in-progress` to continue",
                     expect_change.number.pretty(gerrit)?
-                ));
+                )
+                .classify(ErrorKind::Conflict)
+                .for_change(expect_change.number));
             }
         }
     }
@@ -251,12 +297,12 @@ pub fn restack(
     if todo.refs.is_empty() {
         tracing::info!(
             "Restacking changes:\n{}",
-            todo.graph.format_tree(gerrit, |_| Ok(Vec::new()))?
+            todo.graph.format_tree(gerrit, None, |_| Ok(Vec::new()))?
         );
     } else {
         tracing::info!(
             "Continuing to restack changes:\n{}",
-            todo.graph.format_tree(gerrit, |change| {
+            todo.graph.format_tree(gerrit, None, |change| {
                 Ok(todo
                     .refs
                     .get(&change)
@@ -267,7 +313,21 @@ pub fn restack(
         );
     }
 
-    while let Some(step) = todo.steps.pop_front() {
+    loop {
+        // The user may have started a rebase or merge of their own since the last step.
+        check_foreign_operation(&git, false)?;
+        let Some(step) = todo.steps.pop_front() else {
+            break;
+        };
+        if let RestackOnto::Change(parent) = &step.onto {
+            if todo.needs_attention.contains_key(parent) {
+                tracing::warn!("Skipping {step}; {parent} was skipped");
+                todo.needs_attention
+                    .insert(step.change, SkipReason::Parent(*parent));
+                todo.write(&git)?;
+                continue;
+            }
+        }
         let old_head = gerrit.fetch_cl(gerrit.get_change(step.change)?.patchset())?;
         let in_progress = InProgress {
             inner: step,
@@ -282,17 +342,35 @@ pub fn restack(
             Ok(()) => {
                 todo.write(&git)?;
             }
+            Err(error) if all_remaining && git.rebase_in_progress()? => {
+                tracing::warn!("Skipping {in_progress} due to conflicts: {error}");
+                git.command()
+                    .args(["rebase", "--abort"])
+                    .status_checked()
+                    .into_diagnostic()?;
+                todo.needs_attention
+                    .insert(in_progress.inner.change, SkipReason::Conflicts);
+                todo.write(&git)?;
+            }
             error @ Err(_) => {
+                let change = in_progress.inner.change;
                 todo.in_progress = Some(in_progress);
                 todo.write(&git)?;
-                return error.wrap_err(CONTINUE_MESSAGE);
+                return error
+                    .wrap_err(CONTINUE_MESSAGE)
+                    .classify(ErrorKind::Conflict)
+                    .for_change(change);
             }
         }
     }
 
     fs::remove_file(todo_path(&git)?).into_diagnostic()?;
+    if let Some(hashtag) = todo.hashtag.take() {
+        hashtag.remove(gerrit);
+    }
 
     let restore = todo.before.clone();
+    let needs_attention = std::mem::take(&mut todo.needs_attention);
 
     let mut todo = PushTodo::from(todo);
     if todo.is_empty() {
@@ -301,7 +379,7 @@ pub fn restack(
         todo.write(&git)?;
         tracing::info!(
             "Restacked changes:\n{}",
-            todo.graph.format_tree(gerrit, |change| {
+            todo.graph.format_tree(gerrit, None, |change| {
                 Ok(todo
                     .refs
                     .get(&change)
@@ -310,7 +388,9 @@ pub fn restack(
                     .collect())
             })?
         );
-        tracing::info!("Restack completed but changes have not been pushed; run `git-gr restack push` to sync changes with the remote.");
+        if !Config::global().restack.push.unwrap_or(false) {
+            tracing::info!("Restack completed but changes have not been pushed; run `git-gr restack push` to sync changes with the remote.");
+        }
     }
 
     let restore_commit = match restore.change {
@@ -324,6 +404,22 @@ pub fn restack(
 
     git.checkout(restore_commit)?;
 
+    if !todo.is_empty() && Config::global().restack.push.unwrap_or(false) {
+        let summary = restack_push(gerrit, None, &[], None)?;
+        let _ = stdoutln!("{summary}");
+    }
+
+    if !needs_attention.is_empty() {
+        let mut changes = Vec::with_capacity(needs_attention.len());
+        for (change, reason) in needs_attention {
+            changes.push(format!("{} ({reason})", change.pretty(gerrit)?));
+        }
+        tracing::warn!(
+            "These changes were skipped and need attention; restack them with `git-gr restack this`:\n{}",
+            format_bulleted_list(changes)
+        );
+    }
+
     Ok(())
 }
 
@@ -344,9 +440,39 @@ pub fn format_git_rebase_todo(gerrit: &mut GerritGitRemote) -> miette::Result<St
     }
 }
 
-pub fn restack_abort(git: &Git) -> miette::Result<()> {
-    let todo_path = todo_path(git)?;
+/// Error if the user has started a rebase, merge, or similar which a restack step would
+/// clobber.
+///
+/// If `own_rebase` is set, an in-progress rebase belongs to the restack and isn't an error.
+fn check_foreign_operation(git: &Git, own_rebase: bool) -> miette::Result<()> {
+    match git.operation_in_progress()? {
+        None | Some(GitOperation::Rebase) if own_rebase => Ok(()),
+        None => Ok(()),
+        Some(operation) => Err(miette!(
+            help = format!(
+                "Finish the {operation} or abort it with `{}`, then use `git-gr restack continue` to keep going",
+                operation.abort_command()
+            ),
+            "A {operation} which wasn't started by git-gr is in progress; pausing the restack"
+        )
+        .classify(ErrorKind::Conflict)),
+    }
+}
+
+pub fn restack_abort(gerrit: &mut GerritGitRemote) -> miette::Result<()> {
+    let git = gerrit.git();
+    let todo_path = todo_path(&git)?;
     if todo_path.exists() {
+        match get_todo(gerrit) {
+            Ok(todo) => {
+                if let Some(hashtag) = todo.and_then(|todo| todo.hashtag) {
+                    hashtag.remove(gerrit);
+                }
+            }
+            Err(error) => {
+                tracing::warn!("{error}");
+            }
+        }
         fs::remove_file(todo_path).into_diagnostic()?;
     }
     if git.rebase_in_progress()? {
@@ -358,16 +484,20 @@ pub fn restack_abort(git: &Git) -> miette::Result<()> {
     Ok(())
 }
 
-fn todo_path(git: &Git) -> miette::Result<Utf8PathBuf> {
+pub fn todo_path(git: &Git) -> miette::Result<Utf8PathBuf> {
     git.get_git_dir()
         .map(|git_dir| git_dir.join("git-gr-restack-todo.json"))
 }
 
-fn get_or_create_todo(gerrit: &mut GerritGitRemote, branch: &str) -> miette::Result<RestackTodo> {
+fn get_or_create_todo(
+    gerrit: &mut GerritGitRemote,
+    branch: &str,
+    include_others: bool,
+) -> miette::Result<RestackTodo> {
     match get_todo(gerrit)? {
         Some(todo) => Ok(todo),
         None => {
-            let todo = create_todo(gerrit, branch)?;
+            let todo = create_todo(gerrit, branch, include_others)?;
             todo.write(&gerrit.git())?;
             Ok(todo)
         }
@@ -387,11 +517,21 @@ pub fn get_todo(gerrit: &GerritGitRemote) -> miette::Result<Option<RestackTodo>>
     }
 }
 
-pub fn create_todo(gerrit: &mut GerritGitRemote, branch: &str) -> miette::Result<RestackTodo> {
+/// Plan a restack of the stack containing `branch`.
+///
+/// Restacking uploads new patchsets for every change in the stack, so unless `include_others` is
+/// set, changes owned by other people are listed and confirmation is requested first.
+pub fn create_todo(
+    gerrit: &mut GerritGitRemote,
+    branch: &str,
+    include_others: bool,
+) -> miette::Result<RestackTodo> {
     let git = gerrit.git();
     let todo_path = todo_path(&git)?;
     if todo_path.exists() {
-        return Err(miette!("Restack todo already exists at `{todo_path}`"));
+        return Err(
+            miette!("Restack todo already exists at `{todo_path}`").classify(ErrorKind::Conflict)
+        );
     }
 
     let head = git.rev_parse("HEAD")?;
@@ -417,6 +557,8 @@ pub fn create_todo(gerrit: &mut GerritGitRemote, branch: &str) -> miette::Result
         steps: Default::default(),
         refs: Default::default(),
         in_progress: Default::default(),
+        needs_attention: Default::default(),
+        hashtag: Default::default(),
     };
 
     let roots = todo.graph.depends_on_roots();
@@ -476,9 +618,66 @@ pub fn create_todo(gerrit: &mut GerritGitRemote, branch: &str) -> miette::Result
         }
     }
 
+    if !include_others {
+        confirm_others_changes(gerrit, &todo)?;
+    }
+
+    if let Some(hashtag) = &Config::global().restack.hashtag {
+        let hashtag = RestackHashtag {
+            hashtag: hashtag.clone(),
+            changes: todo.steps.iter().map(|step| step.change).collect(),
+        };
+        hashtag.add(gerrit);
+        todo.hashtag = Some(hashtag);
+    }
+
     Ok(todo)
 }
 
+/// If the restack would upload patchsets to changes owned by other people, list them and ask for
+/// confirmation.
+fn confirm_others_changes(gerrit: &GerritGitRemote, todo: &RestackTodo) -> miette::Result<()> {
+    let mut others = Vec::new();
+    for step in &todo.steps {
+        let change = gerrit.get_change(step.change)?;
+        if change.owner.username != gerrit.username() {
+            others.push(format!(
+                "{} (owned by {})",
+                change.number.pretty(gerrit)?,
+                change.owner.username
+            ));
+        }
+    }
+
+    if others.is_empty() {
+        return Ok(());
+    }
+
+    let message = format!(
+        "Restacking will upload new patchsets to changes owned by other people:\n{}",
+        format_bulleted_list(&others)
+    );
+    if !std::io::stdin().is_terminal() {
+        return Err(miette!(
+            help = "Use `--include-others` to restack them anyway",
+            "{message}"
+        )
+        .classify(ErrorKind::Usage));
+    }
+
+    let _ = stdoutln!("{message}");
+    let confirmed = Confirm::new()
+        .with_prompt("Restack them anyway?")
+        .default(false)
+        .interact()
+        .into_diagnostic()?;
+    if confirmed {
+        Ok(())
+    } else {
+        Err(miette!("Restack cancelled"))
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 struct InProgress {
     /// The step in progress.
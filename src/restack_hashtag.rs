@@ -0,0 +1,51 @@
+use std::collections::BTreeSet;
+
+use crate::change_number::ChangeNumber;
+use crate::gerrit::Gerrit;
+use crate::hashtags_input::HashtagsInput;
+
+/// A hashtag added to every change in a restack while it's in progress, so that teammates can
+/// see the stack is being rewritten.
+///
+/// Configured with `restack.hashtag` in the config file or `git-gr.restackHashtag` in Git
+/// config.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct RestackHashtag {
+    pub hashtag: String,
+    /// The changes the hashtag was added to.
+    pub changes: BTreeSet<ChangeNumber>,
+}
+
+impl RestackHashtag {
+    /// Add the hashtag to each change.
+    ///
+    /// Failures are logged rather than returned; the hashtag is only informational.
+    pub fn add(&self, gerrit: &mut Gerrit) {
+        self.update(
+            gerrit,
+            HashtagsInput {
+                add: vec![self.hashtag.clone()],
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Remove the hashtag from each change.
+    pub fn remove(&self, gerrit: &mut Gerrit) {
+        self.update(
+            gerrit,
+            HashtagsInput {
+                remove: vec![self.hashtag.clone()],
+                ..Default::default()
+            },
+        );
+    }
+
+    fn update(&self, gerrit: &mut Gerrit, input: HashtagsInput) {
+        for change in &self.changes {
+            if let Err(error) = gerrit.set_hashtags(*change, &input) {
+                tracing::warn!("Failed to update #{} on {change}: {error}", self.hashtag);
+            }
+        }
+    }
+}
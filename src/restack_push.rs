@@ -3,19 +3,31 @@ use std::collections::BTreeSet;
 use std::collections::VecDeque;
 use std::io::BufReader;
 use std::io::BufWriter;
+use std::io::IsTerminal;
 
+use calm_io::stdoutln;
 use camino::Utf8PathBuf;
+use dialoguer::Confirm;
 use fs_err::File;
 use miette::miette;
 use miette::Context;
 use miette::IntoDiagnostic;
 
 use crate::change_number::ChangeNumber;
+use crate::config::Config;
 use crate::dependency_graph::DependencyGraph;
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+use crate::format_bulleted_list;
 use crate::gerrit::GerritGitRemote;
 use crate::git::Git;
+use crate::label_vote::LabelVote;
+use crate::push_wip::PushWip;
 use crate::restack::RefUpdate;
 use crate::restack::RestackTodo;
+use crate::restack_summary::RestackSummary;
+use crate::restack_summary::RestackSummaryEntry;
+use crate::sticky_votes::log_sticky_votes;
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct PushTodo {
@@ -57,7 +69,20 @@ impl PushTodo {
     }
 }
 
-pub fn restack_push(gerrit: &GerritGitRemote) -> miette::Result<()> {
+/// Push the changes from a completed restack, returning a summary of the pushed stack.
+///
+/// Work-in-progress and private changes are pushed, skipped, or prompted for according to `wip`,
+/// falling back to the `restack.push-wip` setting. Changes without all of the `require_labels`
+/// votes are skipped.
+///
+/// If `rerequest_review` is set (falling back to the `restack.rerequest-review` setting), a
+/// message is posted on each pushed change and its reviewers are added back to the attention set.
+pub fn restack_push(
+    gerrit: &mut GerritGitRemote,
+    wip: Option<PushWip>,
+    require_labels: &[LabelVote],
+    rerequest_review: Option<bool>,
+) -> miette::Result<RestackSummary> {
     let mut todo = get_todo(gerrit)?;
     let git = gerrit.git();
 
@@ -69,7 +94,7 @@ pub fn restack_push(gerrit: &GerritGitRemote) -> miette::Result<()> {
 
     tracing::info!(
         "Pushing stack:\n{}",
-        todo.graph.format_tree(gerrit, |change| {
+        todo.graph.format_tree(gerrit, None, |change| {
             Ok(todo
                 .refs
                 .get(&change)
@@ -79,8 +104,31 @@ pub fn restack_push(gerrit: &GerritGitRemote) -> miette::Result<()> {
         })?
     );
 
+    let wip = wip
+        .or(Config::global().restack.push_wip)
+        .unwrap_or_default();
+    let rerequest_review = rerequest_review
+        .or(Config::global().restack.rerequest_review)
+        .unwrap_or_default();
+    let mut skipped = skipped_changes(gerrit, &todo, wip)?;
+    skipped.extend(unapproved_changes(gerrit, &todo, require_labels)?);
+
+    // Changes in the stack, from the bottom up, with their patchsets before pushing.
+    let mut stack = Vec::new();
+
     while let Some(change) = queue.pop_back() {
-        if let Some(RefUpdate { old, new }) = todo.refs.remove(&change) {
+        stack.push((change, gerrit.get_change(change)?.current_patch_set.number));
+
+        if let Some(parent) = todo.graph.depends_on(change) {
+            // Pushing a change would also push the new commits of the changes it depends on.
+            if skipped.contains(&parent) && skipped.insert(change) {
+                tracing::warn!("Skipping {change}; {parent} was skipped");
+            }
+        }
+
+        if skipped.contains(&change) {
+            // Leave the update in the todo, so it can be pushed later.
+        } else if let Some(RefUpdate { old, new }) = todo.refs.remove(&change) {
             tracing::info!(
                 "Pushing change {}: {}..{}",
                 change,
@@ -88,8 +136,22 @@ pub fn restack_push(gerrit: &GerritGitRemote) -> miette::Result<()> {
                 new.abbrev(),
             );
             let change = gerrit.get_change(change)?;
-            git.gerrit_push(&gerrit.remote, &new, &change.branch)?;
+            if let Err(error) = log_sticky_votes(gerrit, &change, &old, &new) {
+                tracing::debug!("Failed to determine sticky votes: {error}");
+            }
+            git.gerrit_push(&gerrit.remote, &new, &change.branch, &Default::default())?;
+            gerrit.forget_change(change.number)?;
             todo.write(&git)?;
+
+            if rerequest_review {
+                if let Err(error) = crate::rerequest_review::rerequest_review(
+                    gerrit,
+                    change.number,
+                    change.current_patch_set.number,
+                ) {
+                    tracing::warn!("Failed to re-request review on {}: {error}", change.number);
+                }
+            }
         }
 
         let needed_by = todo.graph.needed_by(change);
@@ -101,7 +163,109 @@ pub fn restack_push(gerrit: &GerritGitRemote) -> miette::Result<()> {
         }
     }
 
-    Ok(())
+    let mut summary = RestackSummary::default();
+    for (change, old_patchset) in stack {
+        let change = gerrit.get_change(change)?;
+        summary.changes.push(RestackSummaryEntry {
+            number: change.number,
+            url: change.web_url(),
+            subject: change.subject,
+            old_patchset,
+            new_patchset: change.current_patch_set.number,
+        });
+    }
+
+    Ok(summary)
+}
+
+/// Find the work-in-progress and private changes to leave alone when pushing.
+///
+/// Pushing new patchsets to these changes can be disruptive, especially if they belong to someone
+/// else.
+fn skipped_changes(
+    gerrit: &GerritGitRemote,
+    todo: &PushTodo,
+    wip: PushWip,
+) -> miette::Result<BTreeSet<ChangeNumber>> {
+    let mut unready = Vec::new();
+    for change in todo.refs.keys() {
+        let change = gerrit.get_change(*change)?;
+        if change.wip || change.private {
+            unready.push(change.number);
+        }
+    }
+
+    if unready.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+
+    let push = match wip {
+        PushWip::Push => true,
+        PushWip::Skip => false,
+        PushWip::Prompt => {
+            let changes = unready
+                .iter()
+                .map(|change| change.pretty(gerrit))
+                .collect::<miette::Result<Vec<_>>>()?;
+            let message = format!(
+                "The stack has work-in-progress or private changes:\n{}",
+                format_bulleted_list(changes)
+            );
+            if !std::io::stdin().is_terminal() {
+                return Err(miette!(
+                    help = "Use `git gr restack push --wip push` or `--wip skip`",
+                    "{message}"
+                )
+                .classify(ErrorKind::Usage));
+            }
+
+            let _ = stdoutln!("{message}");
+            Confirm::new()
+                .with_prompt("Push new patchsets to them?")
+                .default(false)
+                .interact()
+                .into_diagnostic()?
+        }
+    };
+
+    if push {
+        Ok(BTreeSet::new())
+    } else {
+        for change in &unready {
+            tracing::warn!("Skipping work-in-progress or private change {change}");
+        }
+        Ok(unready.into_iter().collect())
+    }
+}
+
+/// Find the changes which don't have all of the `require_labels` votes on their current
+/// patchsets, to leave alone when pushing.
+///
+/// This lets approved changes be rebased (keeping their sticky votes) while unapproved changes
+/// wait for content updates.
+fn unapproved_changes(
+    gerrit: &GerritGitRemote,
+    todo: &PushTodo,
+    require_labels: &[LabelVote],
+) -> miette::Result<BTreeSet<ChangeNumber>> {
+    let mut unapproved = BTreeSet::new();
+    for change in todo.refs.keys() {
+        let change = gerrit.get_change(*change)?;
+        let missing = require_labels
+            .iter()
+            .filter(|vote| !vote.is_satisfied_by(&change.current_patch_set.approvals))
+            .map(|vote| vote.to_string())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            tracing::warn!(
+                "Skipping {}; it doesn't have {}",
+                change.number,
+                missing.join(", ")
+            );
+            unapproved.insert(change.number);
+        }
+    }
+    Ok(unapproved)
 }
 
 fn get_todo(gerrit: &GerritGitRemote) -> miette::Result<PushTodo> {
@@ -123,7 +287,7 @@ pub fn maybe_get_todo(gerrit: &GerritGitRemote) -> miette::Result<Result<PushTod
     }
 }
 
-fn push_path(git: &Git) -> miette::Result<Utf8PathBuf> {
+pub fn push_path(git: &Git) -> miette::Result<Utf8PathBuf> {
     git.get_git_dir()
         .map(|git_dir| git_dir.join("git-gr-push-todo.json"))
 }
@@ -0,0 +1,98 @@
+use std::fmt::Display;
+
+use base64::Engine;
+
+use crate::change_number::ChangeNumber;
+
+/// A change in a pushed stack, for summarizing a restack.
+#[derive(Debug, Clone)]
+pub struct RestackSummaryEntry {
+    pub number: ChangeNumber,
+    pub url: String,
+    pub subject: Option<String>,
+    /// The patchset before the restack.
+    pub old_patchset: u64,
+    /// The patchset after the restack.
+    pub new_patchset: u64,
+}
+
+/// A Markdown summary of a pushed restack, suitable for pasting into chat or a tracking issue.
+#[derive(Debug, Clone, Default)]
+pub struct RestackSummary {
+    /// The changes in the stack, from the bottom up.
+    pub changes: Vec<RestackSummaryEntry>,
+}
+
+impl Display for RestackSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let updated = self
+            .changes
+            .iter()
+            .filter(|change| change.old_patchset != change.new_patchset)
+            .count();
+        writeln!(f, "Restacked {updated} of {} changes:", self.changes.len())?;
+        for change in &self.changes {
+            write!(f, "\n- [{}]({})", change.number, change.url)?;
+            if let Some(subject) = &change.subject {
+                write!(f, " {subject}")?;
+            }
+            if change.old_patchset == change.new_patchset {
+                write!(f, " (patchset {}, unchanged)", change.new_patchset)?;
+            } else {
+                write!(
+                    f,
+                    " (patchset {} → {})",
+                    change.old_patchset, change.new_patchset
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy text to the clipboard with an OSC 52 escape sequence.
+///
+/// This works in most terminals, including over SSH, without a clipboard library.
+pub fn copy_to_clipboard(text: &str) {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let _ = calm_io::stderr!("\x1b]52;c;{encoded}\x07");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_restack_summary_display() {
+        let summary = RestackSummary {
+            changes: vec![
+                RestackSummaryEntry {
+                    number: "123".parse().unwrap(),
+                    url: "https://gerrit.example.com/c/gr/+/123".to_owned(),
+                    subject: Some("Add a mock Gerrit server".to_owned()),
+                    old_patchset: 2,
+                    new_patchset: 2,
+                },
+                RestackSummaryEntry {
+                    number: "124".parse().unwrap(),
+                    url: "https://gerrit.example.com/c/gr/+/124".to_owned(),
+                    subject: Some("Test the mock Gerrit server".to_owned()),
+                    old_patchset: 1,
+                    new_patchset: 2,
+                },
+            ],
+        };
+        assert_eq!(
+            summary.to_string(),
+            indoc!(
+                "
+                Restacked 1 of 2 changes:
+
+                - [123](https://gerrit.example.com/c/gr/+/123) Add a mock Gerrit server (patchset 2, unchanged)
+                - [124](https://gerrit.example.com/c/gr/+/124) Test the mock Gerrit server (patchset 1 → 2)"
+            )
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use crate::chain;
+use crate::change_number::ChangeNumber;
+use crate::gerrit::Gerrit;
+use crate::offline_queue::Action;
+
+/// Post a review on each of the given changes.
+///
+/// When reviewing more than one change, the changes are listed and confirmation is requested
+/// first (unless `yes` is set).
+pub fn review_changes(
+    gerrit: &mut Gerrit,
+    changes: &[ChangeNumber],
+    action: &Action,
+    yes: bool,
+) -> miette::Result<()> {
+    if changes.is_empty() {
+        tracing::info!("No changes to review");
+        return Ok(());
+    }
+
+    if changes.len() > 1 && !yes && !chain::confirm_changes(gerrit, changes, action)? {
+        return Ok(());
+    }
+
+    chain::perform_on_changes(gerrit, changes, action)
+}
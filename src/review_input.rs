@@ -0,0 +1,25 @@
+use std::collections::BTreeMap;
+
+use crate::attention_set_input::AttentionSetInput;
+
+/// Votes and a message to post on a change.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#review-input>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct ReviewInput {
+    /// A top-level message to post on the change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Map from label names to votes, like `Code-Review` to `2`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, i32>,
+    /// A tag for the message, like `autogenerated:git-gr:chain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// What to do with draft comments, like `PUBLISH_ALL_REVISIONS`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drafts: Option<String>,
+    /// Accounts to add to the change's attention set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add_to_attention_set: Vec<AttentionSetInput>,
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crate::account_info::AccountInfo;
+
+/// A reviewer on a change, and their votes.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#reviewer-info>
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct ReviewerInfo {
+    #[serde(flatten)]
+    pub account: AccountInfo,
+    /// Map from label names to votes, like `Code-Review` to `+2`.
+    ///
+    /// Votes are formatted as strings, with a space for zero votes.
+    #[serde(default)]
+    pub approvals: BTreeMap<String, String>,
+}
+
+impl Display for ReviewerInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.account)?;
+        for (label, vote) in &self.approvals {
+            let vote = vote.trim();
+            if !vote.is_empty() && vote != "0" {
+                write!(f, " {label}{vote}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The result of adding a reviewer to a change.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#add-reviewer-result>
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+pub struct AddReviewerResult {
+    /// Why the reviewer couldn't be added, if they weren't.
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_reviewer_info() {
+        let reviewer: ReviewerInfo = serde_json::from_str(
+            r#"{
+                "_account_id": 1000096,
+                "name": "John Doe",
+                "username": "jdoe",
+                "approvals": { "Verified": "+1", "Code-Review": " 0" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(reviewer.to_string(), "jdoe Verified+1");
+    }
+}
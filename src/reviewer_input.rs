@@ -0,0 +1,16 @@
+/// A reviewer to add to a change, by username, email, or group name.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#reviewer-input>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ReviewerInput {
+    pub reviewer: String,
+    pub state: ReviewerState,
+}
+
+/// Whether a reviewer is added as a reviewer or CC'd.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewerState {
+    Reviewer,
+    Cc,
+}
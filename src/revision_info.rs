@@ -0,0 +1,20 @@
+use crate::account_info::AccountInfo;
+use crate::change_kind::ChangeKind;
+use crate::patchset::Patchset;
+
+/// A patchset of a change, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-changes.html#revision-info>
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct RevisionInfo {
+    #[serde(rename = "_number")]
+    pub number: Patchset,
+    pub kind: Option<ChangeKind>,
+    /// When the patchset was created, like `2013-02-01 09:59:32.126000000`.
+    pub created: String,
+    pub uploader: AccountInfo,
+    /// The Git ref for the patchset.
+    #[serde(rename = "ref")]
+    pub ref_name: String,
+}
@@ -0,0 +1,175 @@
+use std::fmt::Write;
+
+use miette::IntoDiagnostic;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+
+use crate::change::Change;
+use crate::change::TimestampFormat;
+use crate::change_status::ChangeStatus;
+use crate::commit_hash::CommitHash;
+use crate::date_format::DateFormat;
+use crate::submit_label_status::SubmitLabelStatus;
+use crate::timezone::local_time_zone;
+
+/// Format a detailed, multi-line view of a change, with the change's private note if it has one.
+pub fn format_change(change: &Change, note: Option<&str>) -> miette::Result<String> {
+    let mut output = String::new();
+
+    let status = match change.status {
+        ChangeStatus::New if change.wip => "wip".to_owned(),
+        ChangeStatus::New => "open".to_owned(),
+        status => status.to_string(),
+    };
+
+    let _ = writeln!(
+        output,
+        "{} {}",
+        change
+            .number
+            .if_supports_color(Stdout, |number| number.bold()),
+        change.subject.as_deref().unwrap_or_default()
+    );
+    let _ = writeln!(output, "Status:    {status}");
+    let _ = writeln!(output, "Project:   {}", change.project);
+    let _ = writeln!(output, "Branch:    {}", change.branch);
+    let _ = writeln!(
+        output,
+        "Owner:     {}",
+        match &change.owner.email {
+            Some(email) => format!("{} <{email}>", change.owner.name),
+            None => change.owner.name.clone(),
+        }
+    );
+    let _ = writeln!(
+        output,
+        "Updated:   {}",
+        change.format_last_updated(
+            TimestampFormat::from_env(),
+            &DateFormat::configured(),
+            local_time_zone()
+        )?
+    );
+    let _ = writeln!(
+        output,
+        "Patchset:  {} ({})",
+        change.current_patch_set.number,
+        CommitHash::new(change.current_patch_set.revision.clone()).abbrev()
+    );
+    let _ = writeln!(output, "URL:       {}", change.web_url());
+
+    if !change.hashtags.is_empty() {
+        let _ = writeln!(output, "Hashtags:  {}", change.hashtags.join(", "));
+    }
+
+    let votes = format_votes(change)?;
+    if !votes.is_empty() {
+        let _ = write!(output, "\nVotes:\n{votes}");
+    }
+
+    let requirements = format_requirements(change);
+    if !requirements.is_empty() {
+        let _ = write!(output, "\nSubmit requirements:\n{requirements}");
+    }
+
+    if let Some(note) = note {
+        let _ = writeln!(output, "\nNote:");
+        for line in note.lines() {
+            let _ = writeln!(output, "  {line}");
+        }
+    }
+
+    Ok(output)
+}
+
+/// Format the votes on a change's current patchset, one per line.
+fn format_votes(change: &Change) -> miette::Result<String> {
+    let date_format = DateFormat::configured();
+    let time_zone = local_time_zone();
+    let mut output = String::new();
+
+    for approval in &change.current_patch_set.approvals {
+        let value = match approval.numeric_value() {
+            Some(value) => {
+                let formatted = format!("{value:+}");
+                if value < 0 {
+                    formatted
+                        .if_supports_color(Stdout, |value| value.red())
+                        .to_string()
+                } else {
+                    formatted
+                        .if_supports_color(Stdout, |value| value.green())
+                        .to_string()
+                }
+            }
+            None => approval.value.clone(),
+        };
+        let mut line = format!("{} {value} by {}", approval.type_, approval.by.name);
+        if let Some(granted_on) = approval.granted_on {
+            line.push_str(&format!(
+                " on {}",
+                time_zone
+                    .to_local(granted_on)
+                    .format(&date_format.full)
+                    .into_diagnostic()?
+            ));
+        }
+        let _ = writeln!(output, "  {line}");
+    }
+
+    Ok(output)
+}
+
+/// Format the submit requirements for a change, one per line.
+///
+/// Falls back to legacy submit records if the server doesn't return submit requirements.
+fn format_requirements(change: &Change) -> String {
+    let mut output = String::new();
+
+    if !change.submit_requirements.is_empty() {
+        for requirement in &change.submit_requirements {
+            let mut line = format!(
+                "{} {}: {}",
+                check_mark(requirement.status.is_satisfied()),
+                requirement.name,
+                requirement.status
+            );
+            if let Some(result) = &requirement.submittability_expression_result {
+                if !result.fulfilled && !result.failing_atoms.is_empty() {
+                    line.push_str(&format!(" (needs {})", result.failing_atoms.join(", ")));
+                }
+            }
+            let _ = writeln!(output, "  {line}");
+        }
+    } else {
+        for label in change
+            .submit_records
+            .iter()
+            .flat_map(|record| &record.labels)
+        {
+            let satisfied = matches!(label.status, SubmitLabelStatus::Ok | SubmitLabelStatus::May);
+            let mut line = format!(
+                "{} {}: {}",
+                check_mark(satisfied),
+                label.label,
+                label.status
+            );
+            if let Some(by) = &label.by {
+                line.push_str(&format!(" by {}", by.name));
+            }
+            let _ = writeln!(output, "  {line}");
+        }
+    }
+
+    output
+}
+
+fn check_mark(satisfied: bool) -> String {
+    if satisfied {
+        "✔"
+            .if_supports_color(Stdout, |mark| mark.green())
+            .to_string()
+    } else {
+        "✗".if_supports_color(Stdout, |mark| mark.red()).to_string()
+    }
+}
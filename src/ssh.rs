@@ -0,0 +1,294 @@
+use std::process::Command;
+use std::str::FromStr;
+use std::time::Duration;
+
+use miette::miette;
+use miette::Context;
+use miette::IntoDiagnostic;
+
+use crate::current_exe::current_exe;
+use crate::gerrit_host::GerritHost;
+use crate::recording::Recording;
+use crate::tmpdir::ssh_control_path;
+use crate::tmpdir::ssh_option_path;
+use crate::tmpdir::SSH_CONNECTION_SHARING;
+
+/// The command-line style of an SSH client.
+///
+/// Like Git's `GIT_SSH_VARIANT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshVariant {
+    /// OpenSSH, which supports `ssh://` destinations and connection sharing.
+    OpenSsh,
+    /// PuTTY's `plink`, which takes a port with `-P`.
+    Plink,
+    /// Teleport's `tsh ssh`, which takes a port with `-p`.
+    Tsh,
+    /// A wrapper which only takes `USER@HOST`.
+    Simple,
+}
+
+impl SshVariant {
+    /// Guess the variant from the program name.
+    fn guess(program: &str) -> Self {
+        // Split on both kinds of separators so Windows paths work everywhere.
+        let name = program
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(program)
+            .to_ascii_lowercase();
+        let name = name.strip_suffix(".exe").unwrap_or(&name);
+        match name {
+            "plink" | "tortoiseplink" => Self::Plink,
+            "tsh" => Self::Tsh,
+            _ => Self::OpenSsh,
+        }
+    }
+}
+
+impl FromStr for SshVariant {
+    type Err = miette::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ssh" | "openssh" => Ok(Self::OpenSsh),
+            "plink" | "putty" | "tortoiseplink" => Ok(Self::Plink),
+            "tsh" | "teleport" => Ok(Self::Tsh),
+            "simple" => Ok(Self::Simple),
+            _ => Err(miette!(
+                "Unknown SSH variant `{s}`; expected one of `ssh`, `plink`, `tsh`, or `simple`"
+            )),
+        }
+    }
+}
+
+/// The SSH client used to run `gerrit` commands.
+#[derive(Debug, Clone)]
+pub struct SshCommand {
+    program: String,
+    args: Vec<String>,
+    variant: SshVariant,
+    /// How long to wait for the server to respond before disconnecting.
+    timeout: Option<Duration>,
+}
+
+impl SshCommand {
+    /// Get the SSH command from the environment.
+    ///
+    /// `GIT_GR_SSH_COMMAND` sets the command (like `tsh ssh` or `plink -batch`), which defaults
+    /// to `ssh`. `GIT_GR_SSH_VARIANT` sets its command-line style, which is otherwise guessed from
+    /// the program name.
+    pub fn from_env() -> miette::Result<Self> {
+        let command = std::env::var("GIT_GR_SSH_COMMAND")
+            .ok()
+            .filter(|command| !command.is_empty());
+        let variant = std::env::var("GIT_GR_SSH_VARIANT")
+            .ok()
+            .filter(|variant| !variant.is_empty())
+            .map(|variant| variant.parse::<SshVariant>())
+            .transpose()
+            .wrap_err("Failed to parse `GIT_GR_SSH_VARIANT`")?;
+
+        match command {
+            Some(command) => Self::parse(&command, variant),
+            None => Ok(Self {
+                program: "ssh".to_owned(),
+                args: Vec::new(),
+                variant: variant.unwrap_or(SshVariant::OpenSsh),
+                timeout: None,
+            }),
+        }
+    }
+
+    /// Send commands to a mock server with `git-gr mock-ssh` instead of using SSH.
+    pub fn mock(server: &str) -> miette::Result<Self> {
+        Ok(Self {
+            program: current_exe()?.into_string(),
+            args: vec!["mock-ssh".to_owned(), server.to_owned()],
+            variant: SshVariant::Simple,
+            timeout: None,
+        })
+    }
+
+    /// Run SSH commands through `git-gr record-ssh` or `git-gr replay-ssh`.
+    pub fn wrap(self, recording: &Recording) -> miette::Result<Self> {
+        let mut args = recording
+            .ssh_subcommand()
+            .iter()
+            .map(|arg| (*arg).to_owned())
+            .collect::<Vec<_>>();
+        args.push("--".to_owned());
+        args.push(self.program);
+        args.extend(self.args);
+        Ok(Self {
+            program: current_exe()?.into_string(),
+            args,
+            variant: self.variant,
+            timeout: self.timeout,
+        })
+    }
+
+    fn parse(command: &str, variant: Option<SshVariant>) -> miette::Result<Self> {
+        let mut words = shell_words::split(command)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to parse SSH command: {command}"))?
+            .into_iter();
+        let program = words
+            .next()
+            .ok_or_else(|| miette!("SSH command is empty"))?;
+        let variant = variant.unwrap_or_else(|| SshVariant::guess(&program));
+        Ok(Self {
+            program,
+            args: words.collect(),
+            variant,
+            timeout: None,
+        })
+    }
+
+    /// Disconnect if the server doesn't respond for about this long, so a hung network
+    /// connection doesn't block forever.
+    ///
+    /// OpenSSH is also told to send keepalives, so it notices a dead connection sooner.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// How long to wait for the server before giving up, if at all.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Does this SSH client support sharing connections between invocations?
+    pub fn supports_connection_sharing(&self) -> bool {
+        SSH_CONNECTION_SHARING && self.variant == SshVariant::OpenSsh
+    }
+
+    /// Construct a command connecting to the given host.
+    ///
+    /// Arguments for the remote command should be added to the returned [`Command`].
+    pub fn command(&self, host: &GerritHost) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+
+        if self.supports_connection_sharing() {
+            let control_path = ssh_control_path(&format!(
+                "git-gr-ssh-{}-{}-{}",
+                host.username, host.host, host.port
+            ));
+            cmd.args([
+                // Persist sessions in the background to speed up subsequent `ssh` calls.
+                "-o",
+                "ControlMaster=auto",
+                "-o",
+                &format!("ControlPath={}", ssh_option_path(control_path.as_str())),
+                "-o",
+                "ControlPersist=120",
+            ]);
+        }
+
+        if let (SshVariant::OpenSsh, Some(timeout)) = (self.variant, self.timeout) {
+            let seconds = timeout.as_secs().max(1);
+            cmd.args([
+                "-o",
+                &format!("ConnectTimeout={seconds}"),
+                // The server is dropped after two unanswered keepalives.
+                "-o",
+                &format!("ServerAliveInterval={}", (seconds / 2).max(1)),
+                "-o",
+                "ServerAliveCountMax=1",
+            ]);
+        }
+
+        let destination = format!("{}@{}", host.username, host.host);
+        match self.variant {
+            SshVariant::OpenSsh => {
+                cmd.arg(host.connect_to());
+            }
+            SshVariant::Plink => {
+                cmd.args(["-P", &host.port.to_string(), &destination]);
+            }
+            SshVariant::Tsh => {
+                cmd.args(["-p", &host.port.to_string(), &destination]);
+            }
+            SshVariant::Simple => {
+                cmd.arg(destination);
+            }
+        }
+
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn args(command: &str) -> Vec<String> {
+        command_args(SshCommand::parse(command, None).unwrap())
+    }
+
+    fn command_args(ssh: SshCommand) -> Vec<String> {
+        let host = GerritHost {
+            username: "rbt".to_owned(),
+            host: "gerrit.example.com".to_owned(),
+            port: 29418,
+        };
+        let cmd = ssh.command(&host);
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .filter_map(|arg| arg.to_str())
+            .map(|arg| arg.to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_ssh_command() {
+        assert_eq!(
+            args("tsh ssh"),
+            ["tsh", "ssh", "-p", "29418", "rbt@gerrit.example.com"]
+        );
+        assert_eq!(
+            args("plink -batch"),
+            ["plink", "-batch", "-P", "29418", "rbt@gerrit.example.com"]
+        );
+        assert_eq!(
+            args("'C:\\Program Files\\PuTTY\\plink.exe'"),
+            [
+                "C:\\Program Files\\PuTTY\\plink.exe",
+                "-P",
+                "29418",
+                "rbt@gerrit.example.com"
+            ]
+        );
+        assert_eq!(
+            args("ssh").last().unwrap(),
+            "ssh://rbt@gerrit.example.com:29418"
+        );
+    }
+
+    #[test]
+    fn test_ssh_timeout() {
+        let args = command_args(
+            SshCommand::parse("ssh", None)
+                .unwrap()
+                .with_timeout(Duration::from_secs(60)),
+        );
+        assert!(args.contains(&"ConnectTimeout=60".to_owned()), "{args:?}");
+        assert!(
+            args.contains(&"ServerAliveInterval=30".to_owned()),
+            "{args:?}"
+        );
+
+        let args = command_args(
+            SshCommand::parse("tsh ssh", None)
+                .unwrap()
+                .with_timeout(Duration::from_secs(60)),
+        );
+        assert_eq!(
+            args,
+            ["tsh", "ssh", "-p", "29418", "rbt@gerrit.example.com"]
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use calm_io::stdoutln;
+use command_error::CommandExt;
+use miette::IntoDiagnostic;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+
+use crate::change_number::ChangeNumber;
+use crate::dependency_graph::DependencyGraph;
+use crate::gerrit::GerritGitRemote;
+
+/// Show the commits of every change in a change's stack, like `git log --oneline`.
+///
+/// Each change's current patchset is fetched, and changes are listed from the top of the stack
+/// down, each followed by the commits in its patchset.
+pub fn stack_log(gerrit: &mut GerritGitRemote, change: ChangeNumber) -> miette::Result<()> {
+    let git = gerrit.git();
+    let head = git.rev_parse("HEAD").ok();
+    let mut graph = DependencyGraph::traverse(gerrit, change)?;
+    let mut changes = graph.changes_bottom_up()?;
+    changes.reverse();
+
+    for change in changes {
+        let change = gerrit.get_change(change)?;
+        let commit = gerrit.fetch_cl(change.patchset())?;
+        let log = git
+            .command()
+            .args(["log", "--oneline", "--no-walk", commit.as_str()])
+            .output_checked_utf8()
+            .into_diagnostic()?
+            .stdout;
+
+        let _ = stdoutln!(
+            "{} {}",
+            change
+                .number
+                .if_supports_color(Stdout, |number| number.bold().green().to_string()),
+            change.subject.as_deref().unwrap_or_default(),
+        );
+        let marker = if head.as_ref() == Some(&commit) {
+            " (HEAD)"
+        } else {
+            ""
+        };
+        for line in log.lines() {
+            let _ = stdoutln!("    {line}{marker}");
+        }
+    }
+
+    Ok(())
+}
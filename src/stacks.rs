@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::change::Change;
+use crate::change_number::ChangeNumber;
+use crate::dependency_graph::DependencyGraph;
+use crate::dependency_graph::DependsOnRelation;
+use crate::gerrit::Gerrit;
+use crate::query::in_project;
+use crate::query::QueryOptions;
+use crate::terminal_width::terminal_width;
+
+/// Whose stacks to show.
+#[derive(Debug, Clone)]
+pub enum StackOwner {
+    /// A username or email.
+    User(String),
+    /// A group, to show the stacks of each of its members.
+    Group(String),
+}
+
+impl StackOwner {
+    fn query(&self) -> String {
+        let (operator, value) = match self {
+            StackOwner::User(user) => ("owner", user),
+            StackOwner::Group(group) => ("ownerin", group),
+        };
+        // Group names can have spaces in them.
+        if value.contains(char::is_whitespace) {
+            format!("is:open {operator}:\"{value}\"")
+        } else {
+            format!("is:open {operator}:{value}")
+        }
+    }
+}
+
+/// Format every open stack owned by a user or group as a tree, with a summary of how many
+/// changes in each stack are ready to submit.
+///
+/// Stacks are the connected components of the dependency graph between the open changes.
+pub fn format_stacks(
+    gerrit: &Gerrit,
+    owner: &StackOwner,
+    all_projects: bool,
+    full: bool,
+) -> miette::Result<String> {
+    let mut query = owner.query();
+    if !all_projects {
+        query = in_project(&query, gerrit.project());
+    }
+    let changes = gerrit
+        .query(
+            QueryOptions::new(query)
+                .current_patch_set()
+                .dependencies()
+                .submit_records()
+                .no_limit(),
+        )?
+        .changes
+        .into_iter()
+        .map(|change| (change.number, change))
+        .collect::<BTreeMap<_, _>>();
+
+    let width = if full { None } else { terminal_width() };
+    let mut stacks = Vec::new();
+    for mut graph in stack_graphs(&changes)? {
+        let tree = graph.format_tree(gerrit, width, |change| {
+            Ok(changes
+                .get(&change)
+                .and_then(|change| change.readiness_label())
+                .into_iter()
+                .collect())
+        })?;
+        let members = graph
+            .changes()
+            .into_iter()
+            .filter_map(|change| changes.get(&change))
+            .collect::<Vec<_>>();
+        stacks.push(format!("{tree}\n{}", summarize(&members)));
+    }
+
+    if stacks.is_empty() {
+        Ok("No open changes".to_owned())
+    } else {
+        Ok(stacks.join("\n\n"))
+    }
+}
+
+/// Split changes into stacks, using their dependencies on each other.
+///
+/// Dependencies on changes outside of `changes` are ignored, so each stack is rooted at its
+/// bottom-most change in the set.
+fn stack_graphs(changes: &BTreeMap<ChangeNumber, Change>) -> miette::Result<Vec<DependencyGraph>> {
+    let parent = |change: &Change| {
+        change
+            .depends_on_numbers()
+            .into_iter()
+            .find(|depends_on| changes.contains_key(depends_on))
+    };
+
+    let mut children = BTreeMap::<ChangeNumber, BTreeSet<ChangeNumber>>::new();
+    for change in changes.values() {
+        if let Some(parent) = parent(change) {
+            children.entry(parent).or_default().insert(change.number);
+        }
+    }
+
+    let mut graphs = Vec::new();
+    for root in changes.values().filter(|change| parent(change).is_none()) {
+        let mut graph = DependencyGraph::new(root.number);
+        let mut queue = vec![root.number];
+        while let Some(change) = queue.pop() {
+            for child in children.get(&change).into_iter().flatten() {
+                graph.insert(DependsOnRelation {
+                    change: *child,
+                    depends_on: change,
+                })?;
+                queue.push(*child);
+            }
+        }
+        graphs.push(graph);
+    }
+    Ok(graphs)
+}
+
+/// Summarize a stack, like `3 changes: 1 ready, 1 work-in-progress`.
+fn summarize(changes: &[&Change]) -> String {
+    let ready = changes
+        .iter()
+        .filter(|change| change.is_submittable() == Some(true))
+        .count();
+    let wip = changes.iter().filter(|change| change.wip).count();
+
+    let mut summary = match changes.len() {
+        1 => "1 change".to_owned(),
+        count => format!("{count} changes"),
+    };
+    summary.push_str(&format!(": {ready} ready"));
+    if wip > 0 {
+        summary.push_str(&format!(", {wip} work-in-progress"));
+    }
+    summary
+}
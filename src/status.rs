@@ -0,0 +1,95 @@
+use std::fmt::Write;
+
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+
+use crate::approval::format_votes;
+use crate::change::Change;
+use crate::change_status::ChangeStatus;
+use crate::comment_threads::all_threads;
+use crate::commit_hash::CommitHash;
+use crate::gerrit::Gerrit;
+
+/// Format a one-screen summary of a change checked out at `head`: its status, votes,
+/// readiness, unresolved comments, and whether `head` is the latest patchset.
+pub fn format_status(
+    gerrit: &mut Gerrit,
+    change: &Change,
+    head: &CommitHash,
+) -> miette::Result<String> {
+    let mut output = String::new();
+
+    let _ = writeln!(
+        output,
+        "{} {}",
+        change
+            .number
+            .if_supports_color(Stdout, |number| number.bold()),
+        change.subject.as_deref().unwrap_or_default()
+    );
+
+    let status = match change.status {
+        ChangeStatus::New if change.wip => "open, work-in-progress".to_owned(),
+        ChangeStatus::New => "open".to_owned(),
+        status => status.to_string(),
+    };
+    let _ = writeln!(output, "Status:    {status}");
+    let _ = writeln!(
+        output,
+        "Patchset:  {}",
+        format_patchset(gerrit, change, head)?
+    );
+
+    let votes = format_votes(&change.current_patch_set.approvals);
+    let _ = writeln!(
+        output,
+        "Votes:     {}",
+        if votes.is_empty() { "none" } else { &votes }
+    );
+    if let Some(readiness) = change.readiness_label() {
+        let _ = writeln!(output, "Submit:    {readiness}");
+    }
+
+    let unresolved = all_threads(&gerrit.comments(change.number)?)
+        .iter()
+        .filter(|thread| !thread.is_resolved())
+        .count();
+    let _ = writeln!(
+        output,
+        "Comments:  {}",
+        match unresolved {
+            0 => "no unresolved threads".to_owned(),
+            1 => "1 unresolved thread".to_owned(),
+            count => format!("{count} unresolved threads"),
+        }
+    );
+    let _ = writeln!(output, "URL:       {}", change.web_url());
+
+    Ok(output)
+}
+
+/// Describe the current patchset, and how `head` relates to it.
+fn format_patchset(gerrit: &Gerrit, change: &Change, head: &CommitHash) -> miette::Result<String> {
+    let latest = &change.current_patch_set;
+    let description = format!(
+        "{} ({})",
+        latest.number,
+        CommitHash::new(latest.revision.clone()).abbrev()
+    );
+    if latest.revision == head.as_str() {
+        return Ok(format!("{description}, checked out"));
+    }
+
+    let checked_out = gerrit
+        .get_patch_sets(change.number)?
+        .into_iter()
+        .find(|patch_set| patch_set.revision == head.as_str());
+    let note = match checked_out {
+        Some(patch_set) => format!("HEAD is outdated patchset {}", patch_set.number),
+        None => "HEAD has changes which haven't been pushed".to_owned(),
+    };
+    Ok(format!(
+        "{description}, {}",
+        note.if_supports_color(Stdout, |note| note.yellow())
+    ))
+}
@@ -0,0 +1,104 @@
+use std::io::Read;
+use std::str::FromStr;
+
+use miette::miette;
+use miette::Context;
+use miette::IntoDiagnostic;
+
+use crate::change_number::ChangeNumber;
+
+/// A command-line argument which may be `-` to read input from stdin instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaybeStdin<T> {
+    Stdin,
+    Value(T),
+}
+
+impl<T: FromStr> FromStr for MaybeStdin<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            Ok(Self::Stdin)
+        } else {
+            s.parse().map(Self::Value)
+        }
+    }
+}
+
+fn read_stdin() -> miette::Result<String> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .into_diagnostic()
+        .wrap_err("Failed to read stdin")?;
+    Ok(input)
+}
+
+/// Read a query from stdin, joining lines with spaces.
+pub fn read_query() -> miette::Result<String> {
+    Ok(read_stdin()?
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// Read change numbers from stdin, one per line.
+pub fn read_changes() -> miette::Result<Vec<ChangeNumber>> {
+    parse_changes(&read_stdin()?)
+}
+
+/// Parse change numbers, one per line.
+///
+/// Blank lines are ignored, and only the first whitespace-separated field of each line is used,
+/// so tab-separated `query` output can be piped in directly.
+fn parse_changes(input: &str) -> miette::Result<Vec<ChangeNumber>> {
+    input
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|field| {
+            field
+                .parse()
+                .map_err(|_| miette!("Expected a change number on stdin, but got {field:?}"))
+        })
+        .collect()
+}
+
+/// Read exactly one change number from stdin.
+pub fn read_change() -> miette::Result<ChangeNumber> {
+    let changes = read_changes()?;
+    match changes.as_slice() {
+        [change] => Ok(*change),
+        _ => Err(miette!(
+            "Expected exactly one change number on stdin, but got {}",
+            changes.len()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maybe_stdin() {
+        assert_eq!(
+            "-".parse::<MaybeStdin<ChangeNumber>>().unwrap(),
+            MaybeStdin::Stdin
+        );
+        assert_eq!(
+            "123".parse::<MaybeStdin<ChangeNumber>>().unwrap(),
+            MaybeStdin::Value("123".parse().unwrap())
+        );
+        assert!("abc".parse::<MaybeStdin<ChangeNumber>>().is_err());
+    }
+
+    #[test]
+    fn test_parse_changes() {
+        assert_eq!(
+            parse_changes("123\n\n456\tAdd a thing\n").unwrap(),
+            vec!["123".parse().unwrap(), "456".parse().unwrap()]
+        );
+        assert!(parse_changes("123\nnope\n").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use crate::change::Change;
+use crate::change_kind::ChangeKind;
+use crate::copy_condition;
+use crate::copy_condition::VoteContext;
+use crate::gerrit::Gerrit;
+use crate::labels::format_vote;
+
+/// A vote on a change, and whether it will be copied to a new patchset.
+#[derive(Debug, Clone)]
+pub struct StickyVote {
+    pub label: String,
+    pub value: i32,
+    pub by: String,
+    /// `None` if we can't tell locally.
+    pub copied: Option<bool>,
+}
+
+impl Display for StickyVote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{} by {}: {}",
+            self.label,
+            format_vote(self.value).trim(),
+            self.by,
+            match self.copied {
+                Some(true) => "kept",
+                Some(false) => "removed",
+                None => "may be removed",
+            }
+        )
+    }
+}
+
+/// Determine which votes on a change's current patchset will be copied to a new patchset of
+/// the given kind.
+pub fn sticky_votes(
+    gerrit: &mut Gerrit,
+    change: &Change,
+    kind: ChangeKind,
+) -> miette::Result<Vec<StickyVote>> {
+    let labels = gerrit.project_labels()?;
+    let mut votes = Vec::new();
+
+    for approval in &change.current_patch_set.approvals {
+        let value = match approval.numeric_value() {
+            Some(0) | None => continue,
+            Some(value) => value,
+        };
+        let label = match labels.iter().find(|label| label.name == approval.type_) {
+            Some(label) => label,
+            None => continue,
+        };
+        let values = label.sorted_values();
+        let copied = match &label.copy_condition {
+            Some(condition) => copy_condition::evaluate(
+                condition,
+                VoteContext {
+                    kind,
+                    value,
+                    max: values.first().map(|(value, _)| *value).unwrap_or(value),
+                    min: values.last().map(|(value, _)| *value).unwrap_or(value),
+                },
+            ),
+            None => Some(false),
+        };
+
+        votes.push(StickyVote {
+            label: approval.type_.clone(),
+            value,
+            by: approval.by.name.clone(),
+            copied,
+        });
+    }
+
+    Ok(votes)
+}
+
+/// Log which votes on a change will be kept or removed when `new` is uploaded to replace the
+/// `old` commit.
+pub fn log_sticky_votes(
+    gerrit: &mut Gerrit,
+    change: &Change,
+    old: &str,
+    new: &str,
+) -> miette::Result<()> {
+    let kind = gerrit.git().change_kind(old, new)?;
+    let votes = sticky_votes(gerrit, change, kind)?;
+    if votes.is_empty() {
+        return Ok(());
+    }
+
+    tracing::info!("New patchset for {} is a {kind}", change.number);
+    for vote in votes {
+        match vote.copied {
+            Some(true) => tracing::info!("  {vote}"),
+            _ => tracing::warn!("  {vote}"),
+        }
+    }
+
+    Ok(())
+}
@@ -6,7 +6,7 @@ use crate::submit_label_status::SubmitLabelStatus;
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
 pub struct SubmitLabel {
-    label: String,
-    by: Option<Author>,
-    status: SubmitLabelStatus,
+    pub label: String,
+    pub by: Option<Author>,
+    pub status: SubmitLabelStatus,
 }
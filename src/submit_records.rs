@@ -8,5 +8,5 @@ use crate::submit_status::SubmitStatus;
 pub struct SubmitRecord {
     pub status: SubmitStatus,
     #[serde(default)]
-    labels: Vec<SubmitLabel>,
+    pub labels: Vec<SubmitLabel>,
 }
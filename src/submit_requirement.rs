@@ -0,0 +1,31 @@
+use crate::submit_requirement_status::SubmitRequirementStatus;
+
+/// A submit requirement result in a Gerrit change.
+///
+/// Newer Gerrit versions return these in addition to (or instead of) submit records.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+pub struct SubmitRequirement {
+    /// The name of the requirement, like `Code-Review`.
+    pub name: String,
+    pub status: SubmitRequirementStatus,
+    /// Is this requirement backfilled from a legacy submit record?
+    #[serde(default)]
+    pub is_legacy: bool,
+    pub submittability_expression_result: Option<SubmitRequirementExpression>,
+}
+
+/// The result of evaluating a submit requirement expression.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+pub struct SubmitRequirementExpression {
+    /// The query expression, like `label:Code-Review=MAX`.
+    pub expression: String,
+    #[serde(default)]
+    pub fulfilled: bool,
+    /// The atoms of the expression which are not fulfilled.
+    #[serde(default)]
+    pub failing_atoms: Vec<String>,
+}
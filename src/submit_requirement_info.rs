@@ -0,0 +1,15 @@
+/// A submit requirement defined for a project, as returned from the REST API.
+///
+/// See: <https://gerrit-review.googlesource.com/Documentation/rest-api-projects.html#submit-requirement-info>
+#[derive(serde::Deserialize, Debug, Clone)]
+#[allow(dead_code)]
+pub struct SubmitRequirementInfo {
+    pub name: String,
+    pub description: Option<String>,
+    /// The query determining if the requirement applies to a change.
+    pub applicability_expression: Option<String>,
+    /// The query determining if the requirement is satisfied.
+    pub submittability_expression: String,
+    /// The query determining if the requirement is overridden.
+    pub override_expression: Option<String>,
+}
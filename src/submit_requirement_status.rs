@@ -0,0 +1,38 @@
+use std::fmt::Display;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SubmitRequirementStatus {
+    Satisfied,
+    Unsatisfied,
+    Overridden,
+    NotApplicable,
+    Error,
+    Forced,
+}
+
+impl SubmitRequirementStatus {
+    /// Does this status allow the change to be submitted?
+    pub fn is_satisfied(&self) -> bool {
+        match self {
+            SubmitRequirementStatus::Satisfied
+            | SubmitRequirementStatus::Overridden
+            | SubmitRequirementStatus::NotApplicable
+            | SubmitRequirementStatus::Forced => true,
+            SubmitRequirementStatus::Unsatisfied | SubmitRequirementStatus::Error => false,
+        }
+    }
+}
+
+impl Display for SubmitRequirementStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitRequirementStatus::Satisfied => write!(f, "satisfied"),
+            SubmitRequirementStatus::Unsatisfied => write!(f, "unsatisfied"),
+            SubmitRequirementStatus::Overridden => write!(f, "overridden"),
+            SubmitRequirementStatus::NotApplicable => write!(f, "not applicable"),
+            SubmitRequirementStatus::Error => write!(f, "error"),
+            SubmitRequirementStatus::Forced => write!(f, "forced"),
+        }
+    }
+}
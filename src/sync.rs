@@ -0,0 +1,93 @@
+use calm_io::stdoutln;
+use dialoguer::Confirm;
+use miette::IntoDiagnostic;
+
+use crate::change_status::ChangeStatus;
+use crate::gerrit::GerritGitRemote;
+
+/// Update local branches to the latest patchsets of their changes.
+///
+/// Branches are matched to changes by the `Change-Id` of the commit they point to, and changes
+/// are fetched without the cache so recently uploaded patchsets aren't missed. Branches
+/// which are behind Gerrit are fast-forwarded; branches which have diverged are reset after
+/// confirmation (or unconditionally, if `yes` is set). Branches with local commits that haven't
+/// been pushed are left alone.
+pub fn sync_branches(gerrit: &GerritGitRemote, yes: bool) -> miette::Result<()> {
+    let git = gerrit.git();
+    let current_branch = git.current_branch()?;
+    let mut updated = 0;
+
+    for name in git.refs("refs/heads/")? {
+        let branch = name.strip_prefix("refs/heads/").unwrap_or(&name);
+        let change = match git
+            .change_id(&name)
+            .and_then(|change_id| gerrit.get_change_fresh(change_id))
+        {
+            Ok(change) => change,
+            Err(error) => {
+                tracing::debug!(branch, "Skipping branch: {error}");
+                continue;
+            }
+        };
+
+        if change.status != ChangeStatus::New {
+            tracing::debug!(branch, "Skipping branch for {} change", change.status);
+            continue;
+        }
+
+        let local = git.rev_parse(&name)?;
+        if *local == change.current_patch_set.revision {
+            continue;
+        }
+
+        let remote = gerrit.fetch_cl(change.patchset())?;
+        let description = format!(
+            "{branch} to {} patchset {}",
+            change.number.pretty(gerrit)?,
+            change.current_patch_set.number
+        );
+
+        if git.is_ancestor(&remote, &local)? {
+            tracing::info!("{branch} has commits which haven't been pushed; skipping");
+            continue;
+        }
+
+        let fast_forward = git.is_ancestor(&local, &remote)?;
+        if !fast_forward
+            && !yes
+            && !Confirm::new()
+                .with_prompt(format!(
+                    "{branch} has diverged from Gerrit; reset {description}? (local commit {} will be lost from the branch)",
+                    local.abbrev()
+                ))
+                .default(false)
+                .interact()
+                .into_diagnostic()?
+        {
+            continue;
+        }
+
+        if current_branch.as_deref() == Some(branch) {
+            // Refuses to discard uncommitted changes.
+            git.reset_keep(&remote)?;
+        } else {
+            git.update_ref(&name, &remote, &local)?;
+        }
+
+        let _ = stdoutln!(
+            "{} {description}",
+            if fast_forward {
+                "Fast-forwarded"
+            } else {
+                "Reset"
+            }
+        );
+        updated += 1;
+    }
+
+    if updated == 0 {
+        tracing::info!("All branches are up to date");
+    }
+
+    Ok(())
+}
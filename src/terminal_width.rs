@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+
+use unicode_width::UnicodeWidthChar;
+
+/// The width of the terminal, if output is going to one.
+///
+/// `COLUMNS` overrides the detected width.
+pub fn terminal_width() -> Option<usize> {
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse::<usize>().ok())
+        .filter(|columns| *columns > 0)
+    {
+        return Some(columns);
+    }
+    terminal_size::terminal_size().map(|(width, _height)| width.0 as usize)
+}
+
+/// Truncate a line to fit in `width` columns, ending it with an ellipsis if it's too long.
+///
+/// ANSI escape sequences are kept and don't count towards the width, and styles (and
+/// hyperlinks) are reset after the ellipsis.
+pub fn truncate_to_width(line: &str, width: usize) -> Cow<'_, str> {
+    const ELLIPSIS: char = '…';
+
+    if visible_width(line) <= width {
+        return Cow::Borrowed(line);
+    }
+
+    let mut truncated = String::with_capacity(line.len());
+    let mut used = 0;
+    let mut styled = false;
+    let mut linked = false;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if let Some(len) = escape_sequence_len(rest) {
+            let escape = &rest[..len];
+            if let Some(url) = escape.strip_prefix("\x1b]8;") {
+                // `ESC ] 8 ; params ; url ST`; an empty URL ends the link.
+                linked = url.split_once(';').is_some_and(|(_params, url)| {
+                    !url.trim_end_matches(['\x1b', '\\', '\x07']).is_empty()
+                });
+            } else {
+                styled = true;
+            }
+            truncated.push_str(escape);
+            rest = &rest[len..];
+            continue;
+        }
+
+        let c_width = c.width().unwrap_or(0);
+        if used + c_width + 1 > width {
+            break;
+        }
+        used += c_width;
+        truncated.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    if width > 0 {
+        truncated.push(ELLIPSIS);
+    }
+    if linked {
+        truncated.push_str("\x1b]8;;\x1b\\");
+    }
+    if styled {
+        truncated.push_str("\x1b[0m");
+    }
+    Cow::Owned(truncated)
+}
+
+/// The number of columns a line takes up, ignoring ANSI escape sequences.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if let Some(len) = escape_sequence_len(rest) {
+            rest = &rest[len..];
+        } else {
+            width += c.width().unwrap_or(0);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    width
+}
+
+/// The length in bytes of the ANSI escape sequence at the start of `s`, if there is one.
+///
+/// Handles CSI sequences like `ESC [ 1 m` and OSC sequences like `ESC ] 8 ; ; URL ST`
+/// hyperlinks, which end with `ESC \\` or `BEL`.
+fn escape_sequence_len(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix('\x1b')?;
+    let len = match rest.strip_prefix(']') {
+        Some(osc) => match osc.find(['\x07', '\x1b']) {
+            Some(end) if osc[end..].starts_with('\x07') => 1 + end + 1,
+            Some(end) => (1 + end + 2).min(rest.len()),
+            None => rest.len(),
+        },
+        None => rest
+            .find(|c: char| c.is_ascii_alphabetic())
+            .map(|end| end + 1)
+            .unwrap_or(rest.len()),
+    };
+    Some(1 + len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("puppy", 5), "puppy");
+        assert_eq!(truncate_to_width("puppy doggy", 5), "pupp…");
+        assert_eq!(
+            truncate_to_width("\x1b[1m123\x1b[0m (subject)", 6),
+            "\x1b[1m123\x1b[0m (…\x1b[0m"
+        );
+        assert_eq!(truncate_to_width("日本語", 4), "日…");
+    }
+
+    #[test]
+    fn test_truncate_to_width_hyperlink() {
+        let link = "\x1b]8;;https://gerrit.example.com/c/gr/+/123\x1b\\123 (subject)\x1b]8;;\x1b\\";
+        assert_eq!(visible_width(link), 13);
+        assert_eq!(truncate_to_width(link, 13), link);
+        assert_eq!(
+            truncate_to_width(link, 6),
+            "\x1b]8;;https://gerrit.example.com/c/gr/+/123\x1b\\123 (…\x1b]8;;\x1b\\"
+        );
+    }
+}
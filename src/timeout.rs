@@ -0,0 +1,236 @@
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use command_error::ChildExt;
+use command_error::CommandExt;
+use command_error::OutputContext;
+use miette::miette;
+use miette::IntoDiagnostic;
+use utf8_command::Utf8Output;
+
+use crate::error_kind::Classify;
+use crate::error_kind::ErrorKind;
+
+/// Does this error message indicate a connection timed out?
+///
+/// OpenSSH reports `Timeout, server HOST not responding.` when keepalives go unanswered, and
+/// `Connection timed out` when it can't connect at all.
+pub fn is_timeout_message(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    message.contains("timed out")
+        || (message.contains("timeout") && message.contains("not responding"))
+}
+
+/// Does this error message indicate Gerrit couldn't be reached at all?
+///
+/// Matches OpenSSH's connection errors, and the I/O and DNS errors `reqwest` reports when it
+/// can't connect.
+pub fn is_connection_message(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    is_timeout_message(&message)
+        || [
+            "connection refused",
+            "connection reset",
+            "connection closed by",
+            "could not resolve hostname",
+            "network is unreachable",
+            "no route to host",
+            "tcp connect error",
+            "dns error",
+            "failed to lookup address",
+        ]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// Does this error message indicate Gerrit gave up on a query after its `--deadline`?
+pub fn is_deadline_message(message: &str) -> bool {
+    message.to_ascii_lowercase().contains("deadline exceeded")
+}
+
+/// An error for a query Gerrit gave up on after the `query-deadline` setting.
+pub fn deadline_error(deadline: Duration) -> miette::Report {
+    miette!(
+        help = "Narrow the query, or set a longer `query-deadline` in the git-gr config \
+                (`git config git-gr.queryDeadline 2m`)",
+        "Gerrit gave up on the query after {}s",
+        deadline.as_secs()
+    )
+    .classify(ErrorKind::Timeout)
+}
+
+/// An error for an operation Gerrit didn't respond to within the `timeout` setting.
+pub fn timeout_error(operation: &str, timeout: Duration) -> miette::Report {
+    miette!(
+        help = "Check your network connection or VPN, or set a longer `timeout` in the git-gr \
+                config (`git config git-gr.timeout 5m`)",
+        "Timed out after {}s waiting for {operation}",
+        timeout.as_secs()
+    )
+    .classify(ErrorKind::Timeout)
+}
+
+/// Run a command like [`CommandExt::output_checked_as`], killing it if it's still running after
+/// `timeout`.
+///
+/// SSH keepalives only notice dead connections, so this also catches servers which accept a
+/// connection and never answer, and SSH clients which don't support keepalives at all.
+pub fn output_checked_with_timeout<R>(
+    mut command: Command,
+    timeout: Option<Duration>,
+    operation: &str,
+    succeeded: impl Fn(OutputContext<Utf8Output>) -> Result<R, command_error::Error>,
+) -> miette::Result<R> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return command.output_checked_as(succeeded).into_diagnostic(),
+    };
+
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn_checked()
+        .into_diagnostic()?;
+
+    let pid = child.child().id();
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let (done, wait_for_done) = mpsc::channel::<()>();
+    let watchdog = std::thread::spawn({
+        let timed_out = timed_out.clone();
+        move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = wait_for_done.recv_timeout(timeout) {
+                timed_out.store(true, Ordering::SeqCst);
+                kill(pid);
+            }
+        }
+    });
+
+    let result = child.output_checked_as(succeeded);
+    drop(done);
+    let _ = watchdog.join();
+
+    if timed_out.load(Ordering::SeqCst) {
+        Err(timeout_error(operation, timeout))
+    } else {
+        result.into_diagnostic()
+    }
+}
+
+/// Check a command's output like [`CommandExt::output_checked_utf8`], for
+/// [`output_checked_with_timeout`].
+pub fn succeeded_utf8(
+    context: OutputContext<Utf8Output>,
+) -> Result<Utf8Output, command_error::Error> {
+    if context.status().success() {
+        Ok(context.into_output())
+    } else {
+        Err(context.error())
+    }
+}
+
+/// Kill a process by its ID.
+///
+/// The [`std::process::Child`] is busy collecting the process's output on another thread, so
+/// this asks the OS instead.
+fn kill(pid: u32) {
+    tracing::debug!(pid, "Killing process which timed out");
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("taskkill");
+        command.args(["/F", "/PID", &pid.to_string()]);
+        command
+    } else {
+        let mut command = Command::new("kill");
+        command.arg(pid.to_string());
+        command
+    };
+    if let Err(error) = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status_checked()
+    {
+        tracing::debug!("Failed to kill process {pid}: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_timeout_message() {
+        assert!(is_timeout_message(
+            "Timeout, server gerrit.example.com not responding."
+        ));
+        assert!(is_timeout_message(
+            "ssh: connect to host gerrit.example.com port 29418: Connection timed out"
+        ));
+        assert!(!is_timeout_message("fatal: Permission denied (publickey)"));
+    }
+
+    #[test]
+    fn test_is_connection_message() {
+        assert!(is_connection_message(
+            "ssh: connect to host gerrit.example.com port 29418: Connection refused"
+        ));
+        assert!(is_connection_message(
+            "ssh: Could not resolve hostname gerrit.example.com: Name or service not known"
+        ));
+        assert!(is_connection_message(
+            "client error (Connect): tcp connect error"
+        ));
+        assert!(!is_connection_message(
+            "POST changes/123/abandon failed with status 409:\nchange is merged"
+        ));
+        assert!(!is_connection_message(
+            "fatal: Permission denied (publickey)"
+        ));
+    }
+
+    #[test]
+    fn test_is_deadline_message() {
+        assert!(is_deadline_message(
+            "fatal: Server Deadline Exceeded\n\nclient.timeout=30000ms"
+        ));
+        assert!(!is_deadline_message("fatal: Permission denied (publickey)"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_checked_with_timeout() {
+        let mut command = Command::new("echo");
+        command.arg("hello");
+        assert_eq!(
+            output_checked_with_timeout(
+                command,
+                Some(Duration::from_secs(10)),
+                "echo",
+                succeeded_utf8
+            )
+            .unwrap()
+            .stdout,
+            "hello\n"
+        );
+
+        let mut command = Command::new("sleep");
+        command.arg("10");
+        let start = std::time::Instant::now();
+        let error = output_checked_with_timeout(
+            command,
+            Some(Duration::from_millis(100)),
+            "sleep",
+            succeeded_utf8,
+        )
+        .unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            crate::error_kind::error_kind(&error),
+            Some(ErrorKind::Timeout)
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::OnceLock;
+
+use time::OffsetDateTime;
+use time::UtcOffset;
+
+use crate::config::Config;
+
+/// The time zone to display timestamps in.
+#[derive(Debug, Clone)]
+pub enum LocalTimeZone {
+    /// A full time zone, with daylight saving time transitions.
+    Named(tz::TimeZone),
+    /// A fixed offset from UTC.
+    Fixed(UtcOffset),
+}
+
+impl LocalTimeZone {
+    /// Determine the local time zone.
+    ///
+    /// Uses `GIT_GR_TIMEZONE` or the `timezone` setting if either is set (e.g. `Europe/Berlin`),
+    /// and otherwise the system time zone from `TZ` or `/etc/localtime`.
+    fn resolve() -> Self {
+        if let Ok(name) = std::env::var("GIT_GR_TIMEZONE") {
+            if !name.is_empty() {
+                match tz::TimeZone::from_posix_tz(&name) {
+                    Ok(time_zone) => {
+                        return Self::Named(time_zone);
+                    }
+                    Err(error) => {
+                        tracing::warn!(
+                            "Failed to load time zone `{name}` from `GIT_GR_TIMEZONE`: {error}"
+                        );
+                    }
+                }
+            }
+        }
+
+        // Checked when the config is loaded.
+        if let Some(time_zone) = Config::global()
+            .timezone
+            .as_deref()
+            .and_then(|name| tz::TimeZone::from_posix_tz(name).ok())
+        {
+            return Self::Named(time_zone);
+        }
+
+        match tz::TimeZone::local() {
+            Ok(time_zone) => Self::Named(time_zone),
+            Err(error) => {
+                tracing::debug!("Failed to load local time zone: {error}");
+                match *local_offset() {
+                    Ok(offset) => Self::Fixed(offset),
+                    Err(error) => {
+                        tracing::debug!("Failed to get local UTC offset, using UTC: {error}");
+                        Self::Fixed(UtcOffset::UTC)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the UTC offset in effect at the given time.
+    pub fn offset_at(&self, datetime: OffsetDateTime) -> UtcOffset {
+        match self {
+            LocalTimeZone::Fixed(offset) => *offset,
+            LocalTimeZone::Named(time_zone) => time_zone
+                .find_local_time_type(datetime.unix_timestamp())
+                .ok()
+                .and_then(|local_time_type| {
+                    UtcOffset::from_whole_seconds(local_time_type.ut_offset()).ok()
+                })
+                .unwrap_or(UtcOffset::UTC),
+        }
+    }
+
+    /// Convert a timestamp to local time.
+    pub fn to_local(&self, datetime: OffsetDateTime) -> OffsetDateTime {
+        datetime.to_offset(self.offset_at(datetime))
+    }
+
+    /// Get the current local time.
+    pub fn now(&self) -> OffsetDateTime {
+        self.to_local(OffsetDateTime::now_utc())
+    }
+}
+
+static LOCAL_TIME_ZONE: OnceLock<LocalTimeZone> = OnceLock::new();
+
+static LOCAL_OFFSET: OnceLock<Result<UtcOffset, time::error::IndeterminateOffset>> =
+    OnceLock::new();
+
+/// Get the system's UTC offset, used when the system time zone can't be loaded.
+///
+/// This fails if other threads are running, which is why [`init`] gets it at startup.
+fn local_offset() -> &'static Result<UtcOffset, time::error::IndeterminateOffset> {
+    LOCAL_OFFSET.get_or_init(UtcOffset::current_local_offset)
+}
+
+/// Get the system's UTC offset, in case the local time zone needs it later.
+///
+/// This should be called at startup, before any threads are spawned. The time zone itself is
+/// resolved when it's first used, after settings are loaded and logging is set up.
+pub fn init() {
+    let _ = local_offset();
+}
+
+/// Get the local time zone.
+pub fn local_time_zone() -> &'static LocalTimeZone {
+    LOCAL_TIME_ZONE.get_or_init(LocalTimeZone::resolve)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use time::macros::datetime;
+    use time::macros::offset;
+
+    #[test]
+    fn test_offset_at() {
+        let berlin = LocalTimeZone::Named(
+            tz::TimeZone::from_posix_tz("CET-1CEST,M3.5.0,M10.5.0/3").unwrap(),
+        );
+        assert_eq!(
+            berlin.offset_at(datetime!(2024-01-15 12:00 UTC)),
+            offset!(+1)
+        );
+        assert_eq!(
+            berlin.offset_at(datetime!(2024-07-15 12:00 UTC)),
+            offset!(+2)
+        );
+        assert_eq!(
+            berlin.to_local(datetime!(2024-07-15 23:30 UTC)).date(),
+            time::macros::date!(2024 - 07 - 16)
+        );
+
+        let fixed = LocalTimeZone::Fixed(offset!(-5));
+        assert_eq!(
+            fixed.offset_at(datetime!(2024-07-15 12:00 UTC)),
+            offset!(-5)
+        );
+    }
+}
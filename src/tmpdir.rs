@@ -1,17 +1,121 @@
+use std::borrow::Cow;
+
+use camino::Utf8Path;
 use camino::Utf8PathBuf;
+use sha2::Digest;
+use sha2::Sha256;
+
+/// Does `ssh` support connection sharing with `ControlMaster`?
+///
+/// Windows OpenSSH doesn't support `ControlMaster`, so connections aren't shared there.
+pub const SSH_CONNECTION_SHARING: bool = cfg!(not(windows));
+
+/// Get the directory to store temporary files in.
+///
+/// This is `%TEMP%` on Windows. Elsewhere, this is `$XDG_RUNTIME_DIR`, falling back to
+/// `$TMPDIR` and then `/tmp`.
+fn tmpdir() -> Utf8PathBuf {
+    if cfg!(windows) {
+        return Utf8PathBuf::try_from(std::env::temp_dir()).unwrap_or_else(|error| {
+            tracing::debug!("Temporary directory isn't UTF-8: {error}");
+            Utf8PathBuf::from(r"C:\Windows\Temp")
+        });
+    }
+
+    ["XDG_RUNTIME_DIR", "TMPDIR"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|dir| !dir.is_empty())
+        .map(Utf8PathBuf::from)
+        .unwrap_or_else(|| Utf8PathBuf::from("/tmp"))
+}
 
 /// Gets a temporary `ssh` `ControlPath` file.
 ///
-/// This path is persistent and truncated to 87 (???) bytes:
+/// This path is persistent and limited to 87 (???) bytes:
 /// <https://unix.stackexchange.com/questions/367008/why-is-socket-path-length-limited-to-a-hundred-chars>
-pub fn ssh_control_path(mut name: &str) -> Utf8PathBuf {
+pub fn ssh_control_path(name: &str) -> Utf8PathBuf {
+    control_path_in(&tmpdir(), name)
+}
+
+fn control_path_in(dir: &Utf8Path, name: &str) -> Utf8PathBuf {
     const SIZE_LIMIT: usize = 87;
-    let tmpdir = Utf8PathBuf::from("/tmp");
-    let total_len = tmpdir.as_str().len() + 1 + name.len();
-    if total_len > SIZE_LIMIT {
-        let truncate = total_len - SIZE_LIMIT;
-        // If your hostname contains non-ASCII this will explode.
-        name = &name[..name.len() - truncate];
+    let available = SIZE_LIMIT.saturating_sub(dir.as_str().len() + 1);
+    if name.len() <= available {
+        return dir.join(name);
+    }
+
+    // Too long! Keep as much of the name as we can and add a hash of the whole thing, so that
+    // names with a common prefix don't collide.
+    let hash = Sha256::digest(name.as_bytes())
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let mut prefix_len = available.saturating_sub(hash.len() + 1);
+    while !name.is_char_boundary(prefix_len) {
+        prefix_len -= 1;
+    }
+    if prefix_len == 0 {
+        dir.join(hash)
+    } else {
+        dir.join(format!("{}-{hash}", &name[..prefix_len]))
+    }
+}
+
+/// Quote a path for use as an `ssh -o` option value.
+///
+/// `ssh` expands `%` tokens in paths and splits option values on whitespace, so we escape the
+/// former and quote the latter.
+pub fn ssh_option_path(path: &str) -> Cow<'_, str> {
+    let escaped = if path.contains('%') {
+        Cow::Owned(path.replace('%', "%%"))
+    } else {
+        Cow::Borrowed(path)
+    };
+
+    if escaped.contains(char::is_whitespace) {
+        Cow::Owned(format!("\"{escaped}\""))
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_control_path_in() {
+        let dir = Utf8Path::new("/run/user/1000");
+        assert_eq!(
+            control_path_in(dir, "git-gr-ssh-rbt-gerrit.example.com-29418"),
+            "/run/user/1000/git-gr-ssh-rbt-gerrit.example.com-29418"
+        );
+
+        let long = control_path_in(
+            dir,
+            "git-gr-ssh-rbt-gerrit.a-very-long-hostname-for-a-gerrit-server.example.com-29418",
+        );
+        let other = control_path_in(
+            dir,
+            "git-gr-ssh-rbt-gerrit.a-very-long-hostname-for-a-gerrit-server.example.org-29418",
+        );
+        assert!(long.as_str().len() <= 87);
+        assert_ne!(long, other);
+
+        let unicode = control_path_in(dir, &"gërrït-".repeat(20));
+        assert!(unicode.as_str().len() <= 87);
+    }
+
+    #[test]
+    fn test_ssh_option_path() {
+        assert_eq!(ssh_option_path("/tmp/git-gr-ssh"), "/tmp/git-gr-ssh");
+        assert_eq!(
+            ssh_option_path(r"C:\Users\Jane Doe\AppData\Local\Temp\git-gr-ssh"),
+            r#""C:\Users\Jane Doe\AppData\Local\Temp\git-gr-ssh""#
+        );
+        assert_eq!(ssh_option_path("/tmp/100%"), "/tmp/100%%");
     }
-    tmpdir.join(name)
 }
@@ -0,0 +1,55 @@
+use std::str::FromStr;
+
+use miette::miette;
+use miette::Context;
+
+/// How to fetch patchsets from Gerrit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Transport {
+    /// Fetch over SSH, like `ssh://USER@HOST:PORT/PROJECT`.
+    #[default]
+    Ssh,
+    /// Fetch over HTTPS, like `https://USER@HOST/a/PROJECT`.
+    ///
+    /// Authentication is left to Git, so credential helpers and `http.cookieFile` work as usual.
+    Http,
+}
+
+impl Transport {
+    /// Read the transport from `GIT_GR_TRANSPORT`, defaulting to SSH.
+    pub fn from_env() -> miette::Result<Self> {
+        std::env::var("GIT_GR_TRANSPORT")
+            .ok()
+            .filter(|transport| !transport.is_empty())
+            .map(|transport| transport.parse())
+            .transpose()
+            .wrap_err("Failed to parse `GIT_GR_TRANSPORT`")
+            .map(Option::unwrap_or_default)
+    }
+}
+
+impl FromStr for Transport {
+    type Err = miette::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ssh" => Ok(Self::Ssh),
+            "http" | "https" => Ok(Self::Http),
+            _ => Err(miette!("Unknown transport `{s}`; expected `ssh` or `http`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_transport_from_str() {
+        assert_eq!("ssh".parse::<Transport>().unwrap(), Transport::Ssh);
+        assert_eq!("HTTPS".parse::<Transport>().unwrap(), Transport::Http);
+        assert_eq!("http".parse::<Transport>().unwrap(), Transport::Http);
+        assert!("git".parse::<Transport>().is_err());
+    }
+}
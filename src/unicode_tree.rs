@@ -2,11 +2,14 @@
 //!
 //! Modified from: <https://docs.rs/ascii_tree/0.1.1/src/ascii_tree/lib.rs.html>
 
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::sync::Arc;
 
 use parking_lot::Mutex;
 
+use crate::terminal_width::truncate_to_width;
+
 #[derive(Clone)]
 pub struct Tree {
     label: Vec<String>,
@@ -36,18 +39,27 @@ impl Tree {
     pub fn leaf_from(label: impl AsRef<str>) -> Self {
         Self::new_from(label, [])
     }
+
+    /// Render the tree, truncating lines to fit in `width` columns.
+    pub fn to_string_with_width(&self, width: Option<usize>) -> String {
+        let mut output = String::new();
+        write_tree_element(&mut output, self, &mut vec![], width)
+            .expect("Writing to a string doesn't fail");
+        output
+    }
 }
 
 impl Display for Tree {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write_tree_element(f, self, &mut vec![])
+        write_tree_element(f, self, &mut vec![], None)
     }
 }
 
 fn write_tree_element(
-    f: &mut std::fmt::Formatter<'_>,
+    f: &mut impl std::fmt::Write,
     tree: &Tree,
     level: &mut Vec<usize>,
+    width: Option<usize>,
 ) -> std::fmt::Result {
     const EMPTY: &str = "  ";
     const EDGE: &str = "└─";
@@ -55,32 +67,41 @@ fn write_tree_element(
     const BRANCH: &str = "├─";
 
     let maxpos = level.len();
+    let mut first_line = String::new();
     let mut second_line = String::new();
     for (pos, l) in level.iter().enumerate() {
         let prefix: &str = if pos == 0 { "" } else { " " };
         let last_row = pos == maxpos - 1;
+        first_line.push_str(prefix);
         second_line.push_str(prefix);
         if *l == 1 {
             if !last_row {
-                write!(f, "{prefix}{EMPTY}")?
+                first_line.push_str(EMPTY);
             } else {
-                write!(f, "{prefix}{EDGE}")?
+                first_line.push_str(EDGE);
             }
             second_line.push_str(EMPTY);
         } else {
             if !last_row {
-                write!(f, "{prefix}{PIPE}")?
+                first_line.push_str(PIPE);
             } else {
-                write!(f, "{prefix}{BRANCH}")?
+                first_line.push_str(BRANCH);
             }
             second_line.push_str(PIPE);
         }
     }
 
     let prefix: &str = if maxpos == 0 { "" } else { " " };
+    // The tree-drawing characters are all one column wide.
+    let label_width =
+        width.map(|width| width.saturating_sub(second_line.chars().count() + prefix.len()));
     for (i, s) in tree.label.iter().enumerate() {
+        let s = match label_width {
+            Some(label_width) => truncate_to_width(s, label_width),
+            None => Cow::Borrowed(s.as_str()),
+        };
         match i {
-            0 => writeln!(f, "{prefix}{s}")?,
+            0 => writeln!(f, "{first_line}{prefix}{s}")?,
             _ => writeln!(f, "{second_line}{prefix}{s}")?,
         }
     }
@@ -89,7 +110,7 @@ fn write_tree_element(
     for s in &tree.children {
         level.push(children_remaining);
         children_remaining -= 1;
-        write_tree_element(f, &s.lock(), level)?;
+        write_tree_element(f, &s.lock(), level, width)?;
         level.pop();
     }
 
@@ -154,4 +175,25 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_tree_display_width() {
+        assert_eq!(
+            Tree::new_from(
+                "a long label",
+                [Tree::new_from(
+                    "another long label",
+                    [Tree::leaf_from("short")]
+                )]
+            )
+            .to_string_with_width(Some(12)),
+            indoc!(
+                "
+                a long label
+                └─ another …
+                   └─ short
+                "
+            )
+        );
+    }
 }
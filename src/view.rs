@@ -0,0 +1,61 @@
+use calm_io::stdoutln;
+use dialoguer::Confirm;
+use miette::Context;
+use miette::IntoDiagnostic;
+
+use crate::change_key::ChangeKey;
+use crate::dependency_graph::DependencyGraph;
+use crate::gerrit::Gerrit;
+
+/// Ask for confirmation before opening more than this many browser tabs.
+const MAX_TABS: usize = 5;
+
+/// Open a change in a web browser, or every open change in its chain if `chain` is set.
+///
+/// Changes are opened from the bottom of the stack up. If `print` is set, the URLs are printed
+/// instead.
+pub fn view(
+    gerrit: &mut Gerrit,
+    change: ChangeKey,
+    chain: bool,
+    print: bool,
+) -> miette::Result<()> {
+    let change = gerrit.get_change(change)?;
+    let urls = if chain {
+        let mut graph = DependencyGraph::traverse(gerrit, change.number)?;
+        let mut urls = Vec::new();
+        for change in graph.changes_bottom_up()? {
+            let change = gerrit.get_change(change)?;
+            if change.open {
+                urls.push(change.web_url());
+            }
+        }
+        urls
+    } else {
+        vec![change.web_url()]
+    };
+
+    if print {
+        for url in &urls {
+            let _ = stdoutln!("{url}");
+        }
+        return Ok(());
+    }
+
+    if urls.len() > MAX_TABS
+        && !Confirm::new()
+            .with_prompt(format!("Open {} changes in your browser?", urls.len()))
+            .default(false)
+            .interact()
+            .into_diagnostic()?
+    {
+        return Ok(());
+    }
+
+    for url in &urls {
+        webbrowser::open(url)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to open browser for {url}"))?;
+    }
+    Ok(())
+}
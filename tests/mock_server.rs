@@ -0,0 +1,651 @@
+//! End-to-end tests running `git-gr` against its mock Gerrit server.
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::Child;
+use std::process::Command;
+use std::process::Stdio;
+
+const GIT_GR: &str = env!("CARGO_BIN_EXE_git-gr");
+
+/// A running `git-gr mock-server`, killed on drop.
+struct MockServer {
+    child: Child,
+    url: String,
+}
+
+impl MockServer {
+    fn start(fixture: &str) -> Self {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(fixture);
+        let mut child = Command::new(GIT_GR)
+            .arg("mock-server")
+            .arg(fixture)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut url = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut url)
+            .unwrap();
+        Self {
+            child,
+            url: url.trim().to_owned(),
+        }
+    }
+
+    /// Run `git-gr` in a repository, pointed at this server.
+    fn git_gr(&self, repo: &Path, args: &[&str]) -> String {
+        git_gr(repo, args, &[("GIT_GR_SERVER_OVERRIDE", &self.url)])
+    }
+
+    /// Run `git-gr` in a repository, pointed at this server, asserting that it fails.
+    ///
+    /// Returns its standard error.
+    fn git_gr_fails(&self, repo: &Path, args: &[&str]) -> String {
+        self.git_gr_fails_with(repo, args).1
+    }
+
+    /// Like [`MockServer::git_gr_fails`], but also returns the exit code.
+    fn git_gr_fails_with(&self, repo: &Path, args: &[&str]) -> (Option<i32>, String) {
+        let output = Command::new(GIT_GR)
+            .args(args)
+            .current_dir(repo)
+            .env("GIT_GR_SERVER_OVERRIDE", &self.url)
+            .env("GIT_GR_RATE_LIMIT", "0")
+            .env("XDG_CACHE_HOME", repo.join(".cache"))
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "git-gr {args:?} succeeded");
+        (
+            output.status.code(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    }
+}
+
+/// Run `git-gr` in a repository, asserting that it succeeds.
+fn git_gr(repo: &Path, args: &[&str], env: &[(&str, &str)]) -> String {
+    let output = Command::new(GIT_GR)
+        .args(args)
+        .current_dir(repo)
+        .envs(env.iter().copied())
+        .env("GIT_GR_RATE_LIMIT", "0")
+        .env("XDG_CACHE_HOME", repo.join(".cache"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git-gr {args:?} failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for args in [
+        &["init", "--quiet"][..],
+        &[
+            "remote",
+            "add",
+            "origin",
+            "ssh://rbt@gerrit.example.com:29418/gr",
+        ],
+    ] {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    dir
+}
+
+#[test]
+fn test_query() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["query", "status:open"]);
+    assert!(output.contains("123"), "{output}");
+    assert!(output.contains("Add a mock Gerrit server"), "{output}");
+
+    let output = server.git_gr(repo.path(), &["query", "--size", "status:open"]);
+    assert!(output.contains("+10/-2"), "{output}");
+
+    let output = server.git_gr(repo.path(), &["query", "--owner-format", "name"]);
+    assert!(output.contains("Rebecca Turner"), "{output}");
+
+    let output = server.git_gr(repo.path(), &["query", "--votes"]);
+    assert!(output.contains("CR+2"), "{output}");
+
+    let status = Command::new("git")
+        .args(["config", "git-gr.query.mine", "owner:rbt status:open"])
+        .current_dir(repo.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let output = server.git_gr(repo.path(), &["query", "@mine"]);
+    assert!(output.contains("Add a mock Gerrit server"), "{output}");
+    let stderr = server.git_gr_fails(repo.path(), &["query", "@theirs"]);
+    assert!(stderr.contains("No query named `theirs`"), "{stderr}");
+
+    let output = server.git_gr(repo.path(), &["query", "--kind", "status:open"]);
+    assert!(output.contains("trivial rebase"), "{output}");
+
+    let output = server.git_gr(repo.path(), &["query", "--format", "csv", "status:open"]);
+    assert!(
+        output.starts_with("number,subject,updated,owner,status,url\n123,"),
+        "{output}"
+    );
+    assert!(
+        output.contains(",rbt,open,https://gerrit.example.com/c/gr/+/123\n"),
+        "{output}"
+    );
+
+    let output = server.git_gr(repo.path(), &["query", "--format", "tsv", "status:open"]);
+    assert!(output.starts_with("number\tsubject\t"), "{output}");
+
+    let output = server.git_gr(
+        repo.path(),
+        &[
+            "query",
+            "--template",
+            "{{ number }} {{ owner.username }} {{ hashtags | join(',') }}",
+            "status:open",
+        ],
+    );
+    assert_eq!(output, "123 rbt mock,server\n");
+
+    let output = server.git_gr(repo.path(), &["query", "--my-vote"]);
+    assert!(output.contains("Mine"), "{output}");
+    assert!(output.contains("CR-1 (PS1)"), "{output}");
+
+    let output = server.git_gr(repo.path(), &["query", "--format", "atom", "status:open"]);
+    assert!(output.contains("<feed"), "{output}");
+    assert!(
+        output.contains("<title>123: Add a mock Gerrit server</title>"),
+        "{output}"
+    );
+    assert!(
+        output.contains("<id>https://gerrit.example.com/c/gr/+/123</id>"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_stacks() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["stacks", "--owner", "rbt"]);
+    assert!(output.contains("Add a mock Gerrit server"), "{output}");
+    assert!(output.contains("1 change: 0 ready"), "{output}");
+    assert!(!output.contains("Change in another project"), "{output}");
+}
+
+#[test]
+fn test_dashboard() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["dashboard"]);
+    assert!(output.contains("Your open changes"), "{output}");
+    assert!(output.contains("Add a mock Gerrit server"), "{output}");
+    assert!(output.contains("Recently merged\nNo changes"), "{output}");
+}
+
+#[test]
+fn test_view_print() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["view", "123", "--print"]);
+    assert_eq!(output, "https://gerrit.example.com/c/gr/+/123\n");
+}
+
+#[test]
+fn test_cleanup_dry_run() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(
+        repo.path(),
+        &["cleanup", "--older-than", "30d", "--dry-run"],
+    );
+    assert!(output.contains("Would abandon"), "{output}");
+    assert!(output.contains("Add a mock Gerrit server"), "{output}");
+    assert!(!output.contains("Change in another project"), "{output}");
+}
+
+#[test]
+fn test_remind() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    server.git_gr(repo.path(), &["remind", "123", "--by", "2020-01-01"]);
+
+    let output = server.git_gr(repo.path(), &["remind", "--list"]);
+    assert!(output.contains("123"), "{output}");
+    assert!(output.contains("due Wed 2020-01-01 overdue"), "{output}");
+
+    let output = server.git_gr(repo.path(), &["query", "status:open"]);
+    assert!(output.contains("⏰ Add a mock Gerrit server"), "{output}");
+
+    server.git_gr(repo.path(), &["remind", "123", "--clear"]);
+    let output = server.git_gr(repo.path(), &["query", "status:open"]);
+    assert!(!output.contains("⏰"), "{output}");
+}
+
+#[test]
+fn test_comment() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    git_gr(
+        repo.path(),
+        &["comment", "123"],
+        &[
+            ("GIT_GR_SERVER_OVERRIDE", &server.url),
+            ("GIT_EDITOR", "sed -i.bak 1s/^/Looks.good/"),
+        ],
+    );
+    let message = std::fs::read_to_string(repo.path().join(".git/GIT_GR_COMMENT_EDITMSG")).unwrap();
+    assert!(
+        message.starts_with("Looks.good\n# Commenting on 123"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_comments() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["comments", "123"]);
+    assert!(output.contains("src/main.rs"), "{output}");
+    assert!(output.contains("Line 12, patchset 2 [a]:"), "{output}");
+    assert!(output.contains("jade: Why?"), "{output}");
+
+    server.git_gr(
+        repo.path(),
+        &[
+            "comments",
+            "reply",
+            "src/main.rs:12",
+            "--change",
+            "123",
+            "--message",
+            "Because.",
+            "--resolve",
+        ],
+    );
+
+    server.git_gr(
+        repo.path(),
+        &["comments", "resolve", "--all", "--change", "123"],
+    );
+    server.git_gr(
+        repo.path(),
+        &[
+            "comments",
+            "unresolve",
+            "a",
+            "--change",
+            "123",
+            "-m",
+            "Not yet",
+        ],
+    );
+
+    let (code, error) = server.git_gr_fails_with(
+        repo.path(),
+        &[
+            "comments",
+            "reply",
+            "src/lib.rs:1",
+            "--change",
+            "123",
+            "-m",
+            "?",
+        ],
+    );
+    assert!(
+        error.contains("No comment thread found for `src/lib.rs:1`"),
+        "{error}"
+    );
+    assert_eq!(code, Some(4), "{error}");
+}
+
+#[test]
+fn test_drafts() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    server.git_gr(
+        repo.path(),
+        &[
+            "drafts",
+            "add",
+            "src/lib.rs:3",
+            "--change",
+            "123",
+            "-m",
+            "Typo here.",
+        ],
+    );
+
+    let output = server.git_gr(repo.path(), &["drafts", "list", "123"]);
+    assert!(output.contains("src/lib.rs"), "{output}");
+    assert!(output.contains("Line 3, patchset 2 [d1]:"), "{output}");
+
+    server.git_gr(
+        repo.path(),
+        &["drafts", "publish", "123", "--code-review", "-1"],
+    );
+}
+
+#[test]
+fn test_topic() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["topic", "--change", "123"]);
+    assert_eq!(output, "mock-server\n");
+    server.git_gr(repo.path(), &["topic", "--change", "123", "set", "other"]);
+}
+
+#[test]
+fn test_hashtag() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["hashtag", "--change", "123", "list"]);
+    assert_eq!(output, "mock\nserver\n");
+    server.git_gr(repo.path(), &["hashtag", "--change", "123", "add", "other"]);
+}
+
+#[test]
+fn test_attention() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["attention", "--change", "123", "list"]);
+    assert_eq!(output, "rbt (Reviewer replied)\n");
+    server.git_gr(
+        repo.path(),
+        &["attention", "--change", "123", "add", "self"],
+    );
+    server.git_gr(
+        repo.path(),
+        &["attention", "--change", "123", "remove", "rbt"],
+    );
+}
+
+#[test]
+fn test_reviewers() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["reviewers", "--change", "123", "list"]);
+    assert_eq!(output, "alyssa Code-Review+2\nben\n");
+    server.git_gr(
+        repo.path(),
+        &["reviewers", "--change", "123", "add", "--cc", "eva"],
+    );
+    server.git_gr(
+        repo.path(),
+        &["reviewers", "--change", "123", "remove", "ben"],
+    );
+}
+
+#[test]
+fn test_rebase() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    server.git_gr(repo.path(), &["rebase", "123"]);
+    server.git_gr(repo.path(), &["rebase", "123", "--base", "456"]);
+}
+
+#[test]
+fn test_cherry_pick_to() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(
+        repo.path(),
+        &["cherry-pick-to", "release", "--change", "123"],
+    );
+    assert_eq!(output, "456 https://gerrit.example.com/c/other/+/456\n");
+}
+
+#[test]
+fn test_abandon_restore() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    server.git_gr(repo.path(), &["abandon", "123", "-m", "Not needed"]);
+    server.git_gr(repo.path(), &["restore", "123"]);
+}
+
+#[test]
+fn test_wip_ready() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    server.git_gr(repo.path(), &["wip", "123"]);
+    server.git_gr(repo.path(), &["ready", "123", "-m", "PTAL"]);
+}
+
+#[test]
+fn test_resolve() {
+    let repo = repo();
+    let server = MockServer::start("basic.json");
+    let output = server.git_gr(
+        repo.path(),
+        &["resolve", "I0123456789abcdef0123456789abcdef01234567"],
+    );
+    assert_eq!(output.trim(), "123/2");
+    drop(server);
+
+    // Resolved from the local index, without the server.
+    let output = git_gr(
+        repo.path(),
+        &["resolve", "I0123456789abcdef0123456789abcdef01234567"],
+        &[],
+    );
+    assert_eq!(output.trim(), "123/2");
+}
+
+#[test]
+fn test_api() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let output = server.git_gr(repo.path(), &["api", "accounts/self"]);
+    assert!(output.contains("\"username\":\"rbt\""), "{output}");
+}
+
+#[test]
+fn test_record_replay() {
+    let repo = repo();
+    let recording = repo.path().join(".recording");
+    let recording = recording.to_str().unwrap();
+
+    let server = MockServer::start("basic.json");
+    let recorded = git_gr(
+        repo.path(),
+        &["query", "status:open"],
+        &[
+            ("GIT_GR_SERVER_OVERRIDE", &server.url),
+            ("GIT_GR_RECORD", recording),
+        ],
+    );
+    drop(server);
+
+    let replayed = git_gr(
+        repo.path(),
+        &["query", "status:open"],
+        &[("GIT_GR_REPLAY", recording)],
+    );
+    assert_eq!(recorded, replayed);
+    assert!(replayed.contains("Add a mock Gerrit server"), "{replayed}");
+}
+
+#[test]
+fn test_change_not_found_suggestions() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let stderr = server.git_gr_fails(repo.path(), &["show", "124"]);
+    assert!(stderr.contains("Didn't find change 124"), "{stderr}");
+    assert!(stderr.contains("Did you mean:"), "{stderr}");
+    assert!(stderr.contains("123 "), "{stderr}");
+}
+
+#[test]
+fn test_checkout_missing_patchset() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let stderr = server.git_gr_fails(repo.path(), &["checkout", "123", "--patchset", "3"]);
+    assert!(stderr.contains("Change 123 has no patchset 3"), "{stderr}");
+    assert!(
+        stderr.contains("2: trivial rebase by Rebecca Turner"),
+        "{stderr}"
+    );
+
+    let (code, stderr) = server.git_gr_fails_with(
+        repo.path(),
+        &["--output", "json", "checkout", "123", "--patchset", "3"],
+    );
+    assert_eq!(code, Some(4), "{stderr}");
+    let error: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(error["kind"], "not-found", "{stderr}");
+    assert_eq!(error["changes"], serde_json::json!([123]), "{stderr}");
+    assert!(
+        error["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Change 123 has no patchset 3"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_offline_queue() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    // Queueing an action shouldn't touch the network at all.
+    git_gr(
+        repo.path(),
+        &["abandon", "123"],
+        &[
+            ("GIT_GR_SERVER_OVERRIDE", "http://127.0.0.1:1"),
+            ("GIT_GR_OFFLINE", "1"),
+        ],
+    );
+    let queue =
+        std::fs::read_to_string(repo.path().join(".git/git-gr-offline-queue.json")).unwrap();
+    assert!(queue.contains(r#""change":{"Query":"123"}"#), "{queue}");
+
+    server.git_gr(repo.path(), &["sync", "--flush"]);
+    assert!(!repo.path().join(".git/git-gr-offline-queue.json").exists());
+}
+
+#[test]
+fn test_offline_queue_unreachable() {
+    let repo = repo();
+    git_gr(
+        repo.path(),
+        &["abandon", "123"],
+        &[("GIT_GR_SERVER_OVERRIDE", "http://127.0.0.1:1")],
+    );
+    let queue =
+        std::fs::read_to_string(repo.path().join(".git/git-gr-offline-queue.json")).unwrap();
+    assert!(queue.contains(r#""Abandon""#), "{queue}");
+}
+
+#[test]
+fn test_offline_queue_rejected() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let stderr = server.git_gr_fails(repo.path(), &["abandon", "999"]);
+    assert!(stderr.contains("Didn't find change 999"), "{stderr}");
+    assert!(!repo.path().join(".git/git-gr-offline-queue.json").exists());
+}
+
+#[test]
+fn test_groups_member_self() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let stdout = server.git_gr(repo.path(), &["groups", "Release Team", "--member", "self"]);
+    assert_eq!(stdout, "rbt is a member of Release Team\n");
+}
+
+#[test]
+fn test_checkout_url() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let stderr = server.git_gr_fails(
+        repo.path(),
+        &["checkout", "https://gerrit.example.com/c/gr/+/123/3"],
+    );
+    assert!(stderr.contains("Change 123 has no patchset 3"), "{stderr}");
+
+    let stderr = server.git_gr_fails(
+        repo.path(),
+        &["checkout", "https://gerrit.other.com/c/gr/+/123/3"],
+    );
+    assert!(
+        stderr.contains("Change URL is for another Gerrit host: gerrit.other.com"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_foreign_change_id() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=Rebecca Turner",
+            "-c",
+            "user.email=rbt@sent.as",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "--message",
+            "Cherry-picked\n\nChange-Id: I1111111111111111111111111111111111111111",
+        ])
+        .current_dir(repo.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let stderr = server.git_gr_fails(repo.path(), &["show"]);
+    assert!(stderr.contains("belongs to change 456"), "{stderr}");
+    assert!(stderr.contains("in project `other`"), "{stderr}");
+}
+
+#[test]
+fn test_status() {
+    let server = MockServer::start("basic.json");
+    let repo = repo();
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=Rebecca Turner",
+            "-c",
+            "user.email=rbt@sent.as",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "--message",
+            "Add a mock Gerrit server\n\nChange-Id: I0123456789abcdef0123456789abcdef01234567",
+        ])
+        .current_dir(repo.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = server.git_gr(repo.path(), &["status"]);
+    assert!(output.contains("Add a mock Gerrit server"), "{output}");
+    assert!(output.contains("Votes:     CR+2"), "{output}");
+    assert!(output.contains("haven't been pushed"), "{output}");
+    assert!(output.contains("unresolved thread"), "{output}");
+}